@@ -0,0 +1,11 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/rpc.proto");
+
+    // gRPC client codegen (requires `protoc`)
+    #[cfg(feature = "grpc")]
+    tonic_build::configure()
+        .build_server(false)
+        .compile(&["proto/rpc.proto"], &["proto"])?;
+
+    Ok(())
+}
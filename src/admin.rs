@@ -0,0 +1,658 @@
+use crate::imports::*;
+use crate::status::{json_response, session_from_req};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use crate::log::buffer::{self, Level, LogEvent};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures::StreamExt;
+use tokio::sync::broadcast::error::RecvError;
+
+const BODY_LIMIT: usize = 64 * 1024;
+
+/// Read a JSON request body from an authenticated request.
+/// Responds with `401` if the session is not valid and
+/// with `400` if the body can not be deserialized.
+async fn authorized_json<T: DeserializeOwned>(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> std::result::Result<T, StatusCode> {
+    if session_from_req(resolver, &req).is_none() {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let body = axum::body::to_bytes(req.into_body(), BODY_LIMIT)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    serde_json::from_slice::<T>(&body).map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+fn error_response(status: StatusCode, err: impl Display) -> axum::response::Response {
+    let body = serde_json::json!({ "error" : err.to_string() }).to_string();
+    json_response(status, body)
+}
+
+pub async fn list_changes_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let changes = resolver.monitor().pending_changes();
+    json_response(StatusCode::OK, serde_json::to_string(&changes).unwrap())
+}
+
+pub async fn list_annotations_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let annotations = resolver.annotations().to_map();
+    json_response(StatusCode::OK, serde_json::to_string(&annotations).unwrap())
+}
+
+pub async fn set_annotation_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    let annotation = match authorized_json::<Annotation>(resolver, req).await {
+        Ok(annotation) => annotation,
+        Err(status) => return json_response(status, ""),
+    };
+
+    match resolver.annotations().set(&uid, annotation) {
+        Ok(()) => json_response(StatusCode::OK, ""),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn remove_annotation_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    match resolver.annotations().set(&uid, Annotation::default()) {
+        Ok(()) => json_response(StatusCode::OK, ""),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn list_faults_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let faults = resolver
+        .connections()
+        .iter()
+        .filter_map(|connection| {
+            let active = connection.faults().active();
+            (!active.is_empty()).then(|| (connection.node().short_id().to_string(), active))
+        })
+        .collect::<BTreeMap<_, _>>();
+    json_response(StatusCode::OK, serde_json::to_string(&faults).unwrap())
+}
+
+pub async fn inject_fault_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    let request = match authorized_json::<FaultRequest>(resolver, req).await {
+        Ok(request) => request,
+        Err(status) => return json_response(status, ""),
+    };
+
+    let Some(connection) = resolver.find(&uid) else {
+        return json_response(StatusCode::NOT_FOUND, "");
+    };
+
+    let detail = format!("{} for {}s", request.fault, request.duration_sec);
+    match connection.faults().inject(request) {
+        Ok(()) => {
+            log_warn!("Fault", "{}: {detail}", connection.node());
+            Journal::record(JournalKind::FaultInjected, connection.node(), Some(detail));
+            connection.update();
+            json_response(StatusCode::OK, "")
+        }
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+pub async fn clear_faults_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let Some(connection) = resolver.find(&uid) else {
+        return json_response(StatusCode::NOT_FOUND, "");
+    };
+
+    connection.faults().clear();
+    log_success!("Fault", "{}: cleared", connection.node());
+    connection.update();
+    json_response(StatusCode::OK, "")
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TailQuery {
+    /// Node uid (hex) or short id
+    node: Option<String>,
+    /// Minimum level (default `info`)
+    level: Option<Level>,
+}
+
+/// Stream buffered and live log events as Server-Sent Events,
+/// optionally filtered by node and minimum level.
+pub async fn logs_tail_handler(
+    resolver: &Arc<Resolver>,
+    query: TailQuery,
+    req: Request<Body>,
+) -> Response {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    // log lines refer to a node by its `[short_id]` tag or its address
+    let node = match query.node {
+        Some(id) => match resolver.find(&id) {
+            Some(connection) => Some((
+                format!("[{}]", connection.node().short_id()),
+                connection.node().address().to_string(),
+            )),
+            None => return json_response(StatusCode::NOT_FOUND, ""),
+        },
+        None => None,
+    };
+    let level = query.level.unwrap_or(Level::Info);
+
+    let (recent, receiver) = buffer::subscribe();
+    let backlog = futures::stream::iter(recent);
+    let live = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let stream = backlog
+        .chain(live)
+        .filter(move |event: &LogEvent| {
+            let matches = event.level >= level
+                && node.as_ref().is_none_or(|(tag, address)| {
+                    event.message.contains(tag.as_str()) || event.message.contains(address.as_str())
+                });
+            futures::future::ready(matches)
+        })
+        .map(|event| Event::default().json_data(&event));
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+pub async fn list_pins_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let pins = resolver.monitor().pins().to_map();
+    json_response(StatusCode::OK, serde_json::to_string(&pins).unwrap())
+}
+
+pub async fn confirm_pin_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let Some(connection) = resolver.find(&uid) else {
+        return json_response(StatusCode::NOT_FOUND, "");
+    };
+
+    match resolver.monitor().pins().confirm(connection.node().uid_as_str()) {
+        Ok(true) => {
+            log_success!("Pinning", "{}: new addresses confirmed", connection.node());
+            connection.clear_pin_violation();
+            json_response(StatusCode::OK, "")
+        }
+        Ok(false) => json_response(StatusCode::NOT_FOUND, ""),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn remove_pin_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let Some(connection) = resolver.find(&uid) else {
+        return json_response(StatusCode::NOT_FOUND, "");
+    };
+
+    match resolver.monitor().pins().remove(connection.node().uid_as_str()) {
+        Ok(_) => {
+            connection.clear_pin_violation();
+            json_response(StatusCode::OK, "")
+        }
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn batch_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    let batch = match authorized_json::<Batch>(resolver, req).await {
+        Ok(batch) => batch,
+        Err(status) => return json_response(status, ""),
+    };
+
+    match resolver.apply_batch(&batch) {
+        Ok(()) => {
+            log_warn!("Admin", "Batch applied: {batch}");
+            Journal::record(JournalKind::BatchApplied, &batch, None);
+            json_response(StatusCode::OK, "")
+        }
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+pub async fn drills_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let reports = resolver.monitor().drills().reports();
+    json_response(StatusCode::OK, serde_json::to_string(&reports).unwrap())
+}
+
+pub async fn journal_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let journal = Journal::get().recent();
+    json_response(StatusCode::OK, serde_json::to_string(&journal).unwrap())
+}
+
+pub async fn list_features_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let features = Features::get().to_map();
+    json_response(StatusCode::OK, serde_json::to_string(&features).unwrap())
+}
+
+/// Set a feature override, the body is a JSON `true`, `false` or `null`
+/// (`null` restores the configured default).
+pub async fn set_feature_handler(
+    resolver: &Arc<Resolver>,
+    name: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    let enabled = match authorized_json::<Option<bool>>(resolver, req).await {
+        Ok(enabled) => enabled,
+        Err(status) => return json_response(status, ""),
+    };
+
+    match Features::get().set(&name, enabled) {
+        Ok(()) => json_response(StatusCode::OK, ""),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn budget_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let budget = resolver.monitor().budget();
+    json_response(StatusCode::OK, serde_json::to_string(&budget).unwrap())
+}
+
+pub async fn rollout_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let rollout = resolver.monitor().rollout();
+    json_response(StatusCode::OK, serde_json::to_string(&rollout).unwrap())
+}
+
+pub async fn archive_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let archived = resolver.monitor().archive().archived();
+    json_response(StatusCode::OK, serde_json::to_string(&archived).unwrap())
+}
+
+pub async fn stats_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let stats = resolver.monitor().stats().report(&resolver.connections());
+    json_response(StatusCode::OK, serde_json::to_string(&stats).unwrap())
+}
+
+pub async fn reload_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    match resolver.reload().await {
+        Ok(()) => json_response(StatusCode::ACCEPTED, ""),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn blocklist_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let entries = Blocklist::get().entries();
+    json_response(StatusCode::OK, serde_json::to_string(&entries).unwrap())
+}
+
+pub async fn unblock_handler(
+    resolver: &Arc<Resolver>,
+    ip: IpAddr,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    if Blocklist::get().remove(&ip) {
+        json_response(StatusCode::OK, "")
+    } else {
+        json_response(StatusCode::NOT_FOUND, "")
+    }
+}
+
+pub async fn shadow_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    match resolver.monitor().shadow() {
+        Some(report) => json_response(StatusCode::OK, serde_json::to_string(&report).unwrap()),
+        None => json_response(StatusCode::NOT_FOUND, ""),
+    }
+}
+
+pub async fn experiment_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    match Experiment::get().report() {
+        Some(report) => json_response(StatusCode::OK, serde_json::to_string(&report).unwrap()),
+        None => json_response(StatusCode::NOT_FOUND, ""),
+    }
+}
+
+/// Start a policy experiment, replacing a running experiment
+/// (the final report of the replaced experiment is returned)
+pub async fn start_experiment_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    let config = match authorized_json::<ExperimentConfig>(resolver, req).await {
+        Ok(config) => config,
+        Err(status) => return json_response(status, ""),
+    };
+
+    match Experiment::get().start(config) {
+        Ok(previous) => json_response(StatusCode::OK, serde_json::to_string(&previous).unwrap()),
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+/// Stop the running policy experiment, returning its final report
+pub async fn stop_experiment_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    match Experiment::get().stop() {
+        Some(report) => json_response(StatusCode::OK, serde_json::to_string(&report).unwrap()),
+        None => json_response(StatusCode::NOT_FOUND, ""),
+    }
+}
+
+pub async fn schedule_change_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    let change = match authorized_json::<ScheduledChange>(resolver, req).await {
+        Ok(change) => change,
+        Err(status) => return json_response(status, ""),
+    };
+
+    match resolver.monitor().schedule_change(change) {
+        Ok(id) => {
+            let body = serde_json::json!({ "id" : id }).to_string();
+            json_response(StatusCode::OK, body)
+        }
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+pub async fn dry_run_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    let changes = match authorized_json::<Vec<Hypothetical>>(resolver, req).await {
+        Ok(changes) => changes,
+        Err(status) => return json_response(status, ""),
+    };
+
+    match dryrun::run(&resolver.connections(), &changes) {
+        Ok(reports) => json_response(StatusCode::OK, serde_json::to_string(&reports).unwrap()),
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+pub async fn add_node_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    let node = match authorized_json::<NodeConfig>(resolver, req).await {
+        Ok(node) => node,
+        Err(status) => return json_response(status, ""),
+    };
+    if let Err(err) = node.validate() {
+        return error_response(StatusCode::BAD_REQUEST, err);
+    }
+
+    let uid = Node::from(node.clone()).uid_as_str().to_string();
+    let monitor = resolver.service_monitor(node.service);
+    match monitor.apply_change(Change::Add { node }).await {
+        Ok(()) => {
+            let body = serde_json::json!({ "uid" : uid }).to_string();
+            json_response(StatusCode::OK, body)
+        }
+        Err(err) => error_response(StatusCode::BAD_REQUEST, err),
+    }
+}
+
+pub async fn remove_node_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let Some(connection) = resolver.find(&uid) else {
+        return json_response(StatusCode::NOT_FOUND, "");
+    };
+
+    let uid = connection.node().uid_as_str().to_string();
+    let monitor = resolver.service_monitor(connection.node().service);
+    match monitor.apply_change(Change::Remove { uid }).await {
+        Ok(()) => json_response(StatusCode::OK, ""),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+/// Take a node out of rotation (`drain`) or return it to elections;
+/// the node stays configured and monitored
+pub async fn drain_handler(
+    resolver: &Arc<Resolver>,
+    uid: String,
+    drain: bool,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let Some(connection) = resolver.find(&uid) else {
+        return json_response(StatusCode::NOT_FOUND, "");
+    };
+
+    connection.set_draining(drain);
+    if drain {
+        log_warn!("Admin", "Draining {}", connection.node());
+    } else {
+        log_success!("Admin", "Resuming {}", connection.node());
+    }
+    let body = serde_json::json!({
+        "uid" : connection.node().uid_as_str(),
+        "draining" : connection.is_draining(),
+    });
+    json_response(StatusCode::OK, body.to_string())
+}
+
+pub async fn cancel_change_handler(
+    resolver: &Arc<Resolver>,
+    id: String,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    if resolver.monitor().cancel_change(&id) {
+        json_response(StatusCode::OK, "")
+    } else {
+        json_response(StatusCode::NOT_FOUND, "")
+    }
+}
+
+pub async fn zone_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> Response {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let text = zone::render(&resolver.connections()).await;
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        text,
+    )
+        .into_response()
+}
+
+/// Peer resolvers with their last fetched node tables
+pub async fn federation_handler(
+    resolver: &Arc<Resolver>,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    match resolver.federation() {
+        Some(federation) => {
+            json_response(StatusCode::OK, serde_json::to_string(&federation.report()).unwrap())
+        }
+        None => json_response(StatusCode::NOT_FOUND, ""),
+    }
+}
+
+/// Fleet topology as a graph (`?format=json|dot|graphml`)
+pub async fn topology_handler(
+    resolver: &Arc<Resolver>,
+    query: topology::TopologyQuery,
+    req: Request<Body>,
+) -> Response {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let format = query.format.unwrap_or_default();
+    let text = topology::Graph::new(&resolver.connections()).render(format);
+    (
+        [(axum::http::header::CONTENT_TYPE, format.content_type())],
+        text,
+    )
+        .into_response()
+}
+
+/// Correlate node down transitions of the journal (`?since=<UNIX seconds>`,
+/// the last `[flaps] history-hrs` by default)
+pub async fn flaps_handler(
+    resolver: &Arc<Resolver>,
+    query: flaps::FlapsQuery,
+    req: Request<Body>,
+) -> Response {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let since = query
+        .since
+        .unwrap_or_else(|| unix_now().saturating_sub(FlapsSettings::history().as_secs()));
+    match flaps::analyze(&resolver.connections(), since) {
+        Ok(report) => json_response(StatusCode::OK, serde_json::to_string(&report).unwrap()),
+        Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, err),
+    }
+}
+
+pub async fn config_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let config = effective::to_json(resolver.args());
+    json_response(StatusCode::OK, config.to_string())
+}
@@ -0,0 +1,73 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Operator notes and labels attached to a node.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Annotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.note.as_deref().map(str::is_empty).unwrap_or(true) && self.labels.is_empty()
+    }
+}
+
+/// Node annotations keyed by node uid (hex), persisted
+/// to `~/.vecno-resolver/annotations.json`.
+pub struct Annotations {
+    map: RwLock<BTreeMap<String, Annotation>>,
+    path: PathBuf,
+}
+
+impl Annotations {
+    pub fn load() -> Self {
+        let path = global_config_folder().join("annotations.json");
+        let map = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str::<BTreeMap<String, Annotation>>(&json)?))
+            {
+                Ok(map) => map,
+                Err(err) => {
+                    log_error!("Annotations", "Unable to load `{}`: {err}", path.display());
+                    BTreeMap::new()
+                }
+            }
+        } else {
+            BTreeMap::new()
+        };
+
+        Self {
+            map: RwLock::new(map),
+            path,
+        }
+    }
+
+    pub fn get(&self, uid: &str) -> Option<Annotation> {
+        self.map.read().unwrap().get(uid).cloned()
+    }
+
+    pub fn to_map(&self) -> BTreeMap<String, Annotation> {
+        self.map.read().unwrap().clone()
+    }
+
+    /// Set node annotation, an empty annotation removes the entry.
+    pub fn set(&self, uid: &str, annotation: Annotation) -> Result<()> {
+        let uid = uid.trim().to_lowercase();
+        let json = {
+            let mut map = self.map.write().unwrap();
+            if annotation.is_empty() {
+                map.remove(&uid);
+            } else {
+                map.insert(uid, annotation);
+            }
+            serde_json::to_string_pretty(&*map)?
+        };
+        std::fs::write(&self.path, json).map_err(|err| Error::file(&self.path, err))
+    }
+}
@@ -0,0 +1,36 @@
+use crate::imports::*;
+
+/// API keys entitled to reserved node capacity, loaded from
+/// `~/.vecno-resolver/api-keys.json` (a JSON array of keys).
+pub struct ApiKeys {
+    keys: HashSet<String>,
+}
+
+impl ApiKeys {
+    pub fn load() -> Self {
+        let path = global_config_folder().join("api-keys.json");
+        let keys = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str::<HashSet<String>>(&json)?))
+            {
+                Ok(keys) => {
+                    log_success!("Keys", "Loaded {} API keys", keys.len());
+                    keys
+                }
+                Err(err) => {
+                    log_error!("Keys", "Unable to load `{}`: {err}", path.display());
+                    HashSet::new()
+                }
+            }
+        } else {
+            HashSet::new()
+        };
+
+        Self { keys }
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+}
@@ -0,0 +1,142 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+const WEEK_SEC: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct State {
+    // node uid -> time (UNIX seconds) the node went offline
+    #[serde(default)]
+    offline: BTreeMap<String, u64>,
+    // node uid -> time (UNIX seconds) the node was archived
+    #[serde(default)]
+    archived: BTreeMap<String, u64>,
+}
+
+/// Nodes continuously offline for longer than the configured period
+/// are archived: they remain in the config but are disconnected,
+/// excluded from probing and logging, and only reconnected every
+/// `[archive] recheck-hrs`. Archived nodes returning online are
+/// restored. The state is persisted to `~/.vecno-resolver/archive.json`
+/// and shared by the monitors of all services (keyed by node uid).
+pub struct Archive {
+    state: RwLock<State>,
+    path: PathBuf,
+}
+
+impl Archive {
+    pub fn get() -> &'static Self {
+        static ARCHIVE: OnceLock<Archive> = OnceLock::new();
+        ARCHIVE.get_or_init(Archive::load)
+    }
+
+    fn load() -> Self {
+        let path = global_config_folder().join("archive.json");
+        let state = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str::<State>(&json)?))
+            {
+                Ok(state) => state,
+                Err(err) => {
+                    log_error!("Archive", "Unable to load `{}`: {err}", path.display());
+                    State::default()
+                }
+            }
+        } else {
+            State::default()
+        };
+
+        Self {
+            state: RwLock::new(state),
+            path,
+        }
+    }
+
+    pub fn is_archived(&self, uid: &str) -> bool {
+        self.state.read().unwrap().archived.contains_key(uid)
+    }
+
+    /// Archived nodes (uid -> archive time)
+    pub fn archived(&self) -> BTreeMap<String, u64> {
+        self.state.read().unwrap().archived.clone()
+    }
+
+    /// Track offline periods of `connections`, archiving and restoring nodes.
+    pub fn update(&self, connections: &[Arc<Connection>]) {
+        let Some(period) = ArchiveSettings::period() else {
+            return;
+        };
+
+        let now = unix_now();
+        let mut dirty = false;
+        let mut state = self.state.write().unwrap();
+        for connection in connections {
+            let node = connection.node();
+            let uid = node.uid_as_str();
+            if connection.is_online() {
+                dirty |= state.offline.remove(uid).is_some();
+                if state.archived.remove(uid).is_some() {
+                    dirty = true;
+                    log_success!("Archive", "Restored {node}");
+                    Journal::record(JournalKind::Unarchived, node, None);
+                }
+            } else {
+                let since = *state.offline.entry(uid.to_string()).or_insert_with(|| {
+                    dirty = true;
+                    now
+                });
+                if now.saturating_sub(since) > period.as_secs() && !state.archived.contains_key(uid) {
+                    state.archived.insert(uid.to_string(), now);
+                    dirty = true;
+                    let days = now.saturating_sub(since) / (24 * 60 * 60);
+                    log_warn!("Archive", "Archived {node} (offline for {days} days)");
+                    Journal::record(JournalKind::Archived, node, Some(format!("offline for {days} days")));
+                    // stop monitoring until the next recheck
+                    let _ = connection.reset();
+                }
+            }
+        }
+
+        if dirty {
+            if let Err(err) = self.store(&state) {
+                log_error!("Archive", "{err}");
+            }
+        }
+    }
+
+    /// Alert summarizing the nodes archived during the past week
+    pub fn summary(&self, connections: &[Arc<Connection>]) {
+        let since = unix_now().saturating_sub(WEEK_SEC);
+        let state = self.state.read().unwrap();
+        let nodes = connections
+            .iter()
+            .map(|connection| connection.node())
+            .filter(|node| {
+                state
+                    .archived
+                    .get(node.uid_as_str())
+                    .is_some_and(|archived_at| *archived_at >= since)
+            })
+            .map(|node| node.to_string())
+            .collect::<Vec<_>>();
+
+        if !nodes.is_empty() {
+            log_warn!("Archive", "{} node(s) archived this week:", nodes.len());
+            for node in nodes.iter() {
+                log_warn!("Archive", "  {node}");
+            }
+            Journal::record(
+                JournalKind::ArchiveSummary,
+                format!("{} node(s)", nodes.len()),
+                Some(nodes.join(", ")),
+            );
+        }
+    }
+
+    fn store(&self, state: &State) -> Result<()> {
+        let json = serde_json::to_string_pretty(state)?;
+        std::fs::write(&self.path, json).map_err(|err| Error::file(&self.path, err))
+    }
+}
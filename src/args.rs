@@ -2,6 +2,9 @@ pub use clap::{ArgAction, Parser};
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::blueprint::{self, Deployment};
+use crate::loadtest;
+use crate::policy::{Policy, Strategy};
 use crate::{log_error, log_success};
 
 #[derive(Debug)]
@@ -10,6 +13,15 @@ pub enum Action {
     Pack,
     Unpack,
     Update,
+    Simulate {
+        policy: Policy,
+        journal: Option<PathBuf>,
+    },
+    Init(blueprint::Options),
+    Schema {
+        dir: Option<PathBuf>,
+    },
+    LoadTest(loadtest::Options),
     Test,
     Run,
 }
@@ -26,12 +38,48 @@ pub struct Args {
     pub trace: bool,
     /// Debug mode
     pub debug: bool,
+    /// Validate state flag invariants on every update
+    pub audit: bool,
+    /// Monitor and serve every node independently (no delegate binding)
+    pub no_delegation: bool,
     /// Auto-update
     pub auto_update: bool,
     /// Custom config file
     pub user_config: Option<PathBuf>,
+    /// Configuration profile
+    pub profile: Option<String>,
+    /// S3-compatible bucket url for fleet snapshot publishing
+    pub snapshot_url: Option<String>,
+    /// Node registry url (node list fetched from the registry)
+    pub registry: Option<String>,
+    /// x-only public key verifying node registry signatures
+    pub registry_key: Option<String>,
+    /// Local control socket path
+    pub control: Option<PathBuf>,
+    /// PEM root CA bundle trusted for node connections
+    pub tls_ca: Option<PathBuf>,
+    /// PEM client certificate for node connections (mTLS)
+    pub tls_cert: Option<PathBuf>,
+    /// PEM client certificate private key
+    pub tls_key: Option<PathBuf>,
+    /// OTLP collector endpoint for span export
+    pub otlp: Option<String>,
+    /// Peer resolver urls (federation)
+    pub peers: Vec<String>,
+    /// Minimum node version (overridable per network)
+    pub min_node_version: Option<String>,
+    /// Node selection strategy
+    pub strategy: Strategy,
+    /// MaxMind GeoIP database for geo-aware routing
+    pub geoip: Option<PathBuf>,
     /// public status page
     pub public: bool,
+    /// Exit if no node connection becomes available at startup
+    pub strict: bool,
+    /// Serve only once the startup quorum of nodes is available
+    pub strict_startup: bool,
+    /// Argument ids supplied on the command line (as opposed to defaults)
+    pub explicit: Vec<String>,
     // Show node data on each election
     // pub election: bool,
     // Enable resolver status access via `/status`
@@ -56,8 +104,22 @@ impl Args {
             .arg(arg!(--version "Display software version"))
             .arg(arg!(--verbose "Enable verbose logging"))
             .arg(arg!(--public "Enable public status page"))
+            .arg(arg!(--strict "Exit if no node connection becomes available at startup"))
+            .arg(
+                Arg::new("strict-startup")
+                    .long("strict-startup")
+                    .action(ArgAction::SetTrue)
+                    .help("Serve only once the [startup] quorum of nodes is available, exit otherwise"),
+            )
             .arg(arg!(--trace "Enable trace log level"))
             .arg(arg!(--debug "Enable additional debug output"))
+            .arg(arg!(--audit "Log violations of connection state invariants"))
+            .arg(
+                Arg::new("no-delegation")
+                    .long("no-delegation")
+                    .action(ArgAction::SetTrue)
+                    .help("Monitor and serve each node independently, without delegate binding"),
+            )
             // .arg(arg!(--auto-update "Poll configuration updates"))
             // .arg(arg!(--election "Show node data on each election"))
             // .arg(arg!(--status "Enable `/status` endpoint"))
@@ -83,6 +145,111 @@ impl Args {
                     .require_equals(true)
                     .help("TOML config file (absolute or relative to working directory)"),
             )
+            .arg(
+                Arg::new("profile")
+                    .long("profile")
+                    .value_name("NAME")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Configuration profile defined in the config file as `[profile.<NAME>]`"),
+            )
+            .arg(
+                Arg::new("snapshot-url")
+                    .long("snapshot-url")
+                    .value_name("URL")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Publish signed fleet snapshots to an S3-compatible bucket (path-style url)"),
+            )
+            .arg(
+                Arg::new("registry")
+                    .long("registry")
+                    .value_name("URL")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Fetch the node list from a signed HTTP/JSON node registry"),
+            )
+            .arg(
+                Arg::new("registry-key")
+                    .long("registry-key")
+                    .value_name("HEX")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("x-only public key (hex) verifying node registry signatures"),
+            )
+            .arg(
+                Arg::new("control")
+                    .long("control")
+                    .value_name("PATH")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Local control socket accepting newline-delimited JSON commands"),
+            )
+            .arg(
+                Arg::new("tls-ca")
+                    .long("tls-ca")
+                    .value_name("PATH")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("PEM root CA bundle trusted for TLS node connections (in addition to the bundled WebPKI roots)"),
+            )
+            .arg(
+                Arg::new("tls-cert")
+                    .long("tls-cert")
+                    .value_name("PATH")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("PEM client certificate presented to nodes requiring mutual TLS"),
+            )
+            .arg(
+                Arg::new("tls-key")
+                    .long("tls-key")
+                    .value_name("PATH")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("PEM private key of the --tls-cert client certificate"),
+            )
+            .arg(
+                Arg::new("peer")
+                    .long("peer")
+                    .value_name("URL")
+                    .num_args(1)
+                    .require_equals(true)
+                    .action(ArgAction::Append)
+                    .help("Peer resolver exchanging node tables (repeatable, requires `RESOLVER_FEDERATION_KEY`)"),
+            )
+            .arg(
+                Arg::new("min-node-version")
+                    .long("min-node-version")
+                    .value_name("VERSION")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Exclude nodes reporting an older version (overridden by [version-gate.networks])"),
+            )
+            .arg(
+                Arg::new("strategy")
+                    .long("strategy")
+                    .value_name("STRATEGY")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Node selection strategy: `least-sockets`, `least-load`, `round-robin`, `random` or `latency` [default: least-sockets]"),
+            )
+            .arg(
+                Arg::new("geoip")
+                    .long("geoip")
+                    .value_name("PATH")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("MaxMind GeoIP database (.mmdb) preferring nodes in the region of the client"),
+            )
+            .arg(
+                Arg::new("otlp")
+                    .long("otlp")
+                    .value_name("URL")
+                    .num_args(1)
+                    .require_equals(true)
+                    .help("Export tracing spans to an OpenTelemetry collector (OTLP/gRPC, requires the `otel` feature)"),
+            )
             .arg(
                 Arg::new("listen")
                     .long("listen")
@@ -96,15 +263,160 @@ impl Args {
             .subcommand(Command::new("pack").about("Pack configuration"))
             .subcommand(Command::new("unpack").about("Unpack configuration"))
             .subcommand(Command::new("update").about("Update configuration from GitHub"))
+            .subcommand(
+                Command::new("simulate")
+                    .about("Replay the journal through a selection policy")
+                    .arg(
+                        Arg::new("policy")
+                            .long("policy")
+                            .value_name("POLICY")
+                            .num_args(1)
+                            .require_equals(true)
+                            .required(true)
+                            .help("Policy to evaluate: `weighted`, `least-loaded` or `random`"),
+                    )
+                    .arg(
+                        Arg::new("journal")
+                            .long("journal")
+                            .value_name("PATH")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Journal file [default: ~/.vecno-resolver/journal.jsonl]"),
+                    ),
+            )
+            .subcommand(
+                Command::new("schema")
+                    .about("Emit JSON Schemas and canonical examples of the public response types")
+                    .arg(
+                        Arg::new("dir")
+                            .long("dir")
+                            .value_name("PATH")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Write `<type>.schema.json` and `<type>.examples.json` files [default: stdout]"),
+                    ),
+            )
+            .subcommand(
+                Command::new("loadtest")
+                    .about("Send synthetic resolution traffic to a resolver and report latency and errors")
+                    .arg(
+                        Arg::new("target")
+                            .long("target")
+                            .value_name("URL")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Target resolver [default: `[loadtest] target` or the --listen address]"),
+                    )
+                    .arg(
+                        Arg::new("concurrency")
+                            .long("concurrency")
+                            .value_name("WORKERS")
+                            .num_args(1)
+                            .require_equals(true)
+                            .value_parser(clap::value_parser!(usize))
+                            .help("Concurrent workers [default: `[loadtest] concurrency`]"),
+                    )
+                    .arg(
+                        Arg::new("duration")
+                            .long("duration")
+                            .value_name("SECONDS")
+                            .num_args(1)
+                            .require_equals(true)
+                            .value_parser(clap::value_parser!(u64))
+                            .help("Test duration [default: `[loadtest] duration-sec`]"),
+                    )
+                    .arg(
+                        Arg::new("path")
+                            .long("path")
+                            .value_name("PATH")
+                            .num_args(1)
+                            .require_equals(true)
+                            .action(ArgAction::Append)
+                            .help("Resolution path, e.g. `/v2/vecno/mainnet/any/wrpc/borsh` (repeatable) [default: `[loadtest] paths`]"),
+                    )
+                    .arg(
+                        Arg::new("json")
+                            .long("json")
+                            .action(ArgAction::SetTrue)
+                            .help("Print the report as JSON"),
+                    ),
+            )
+            .subcommand(
+                Command::new("init")
+                    .about("Generate a deployment blueprint (prompts for options not given)")
+                    .arg(
+                        Arg::new("deployment")
+                            .long("deployment")
+                            .value_name("PROFILE")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Deployment profile: `public`, `private` or `dev` (non-interactive)"),
+                    )
+                    .arg(
+                        Arg::new("dir")
+                            .long("dir")
+                            .value_name("PATH")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Target directory [default: vecno-resolver]"),
+                    )
+                    .arg(
+                        Arg::new("domain")
+                            .long("domain")
+                            .value_name("FQDN")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Public domain of the resolver (`public` deployment)"),
+                    )
+                    .arg(
+                        Arg::new("network")
+                            .long("network")
+                            .value_name("NETWORK")
+                            .num_args(1)
+                            .require_equals(true)
+                            .help("Network of the nodes [default: mainnet]"),
+                    )
+                    .arg(
+                        Arg::new("node")
+                            .long("node")
+                            .value_name("FQDN[:PORT]")
+                            .num_args(1)
+                            .require_equals(true)
+                            .action(ArgAction::Append)
+                            .help("Node address (repeatable)"),
+                    )
+                    .arg(
+                        Arg::new("mtls")
+                            .long("mtls")
+                            .action(ArgAction::SetTrue)
+                            .help("Connect to nodes with mutual TLS (`private` deployment)"),
+                    )
+                    .arg(
+                        Arg::new("force")
+                            .long("force")
+                            .action(ArgAction::SetTrue)
+                            .help("Overwrite existing files"),
+                    ),
+            )
             // .subcommand(Command::new("reload").about("Reload configuration"))
         ;
 
         let matches = cmd.get_matches();
 
         let public = matches.get_one::<bool>("public").cloned().unwrap_or(false);
+        let strict = matches.get_one::<bool>("strict").cloned().unwrap_or(false);
+        let strict_startup = matches
+            .get_one::<bool>("strict-startup")
+            .cloned()
+            .unwrap_or(false);
         let trace = matches.get_one::<bool>("trace").cloned().unwrap_or(false);
         let verbose = matches.get_one::<bool>("verbose").cloned().unwrap_or(false);
         let debug = matches.get_one::<bool>("debug").cloned().unwrap_or(false);
+        let audit = matches.get_one::<bool>("audit").cloned().unwrap_or(false);
+        let no_delegation = matches
+            .get_one::<bool>("no-delegation")
+            .cloned()
+            .unwrap_or(false);
         let auto_update = matches
             .get_one::<bool>("auto-update")
             .cloned()
@@ -148,16 +460,49 @@ impl Args {
                     "Auto-update is not supported with custom local config file..."
                 );
                 log_error!("Config", "Halting...");
-                std::process::exit(1);
+                crate::exit::ExitCode::Config.exit();
             }
         }
 
+        let profile = matches.get_one::<String>("profile").cloned();
+        let snapshot_url = matches.get_one::<String>("snapshot-url").cloned();
+        let registry = matches.get_one::<String>("registry").cloned();
+        let registry_key = matches.get_one::<String>("registry-key").cloned();
+        let control = matches.get_one::<String>("control").map(PathBuf::from);
+        let tls_ca = matches.get_one::<String>("tls-ca").map(PathBuf::from);
+        let tls_cert = matches.get_one::<String>("tls-cert").map(PathBuf::from);
+        let tls_key = matches.get_one::<String>("tls-key").map(PathBuf::from);
+        let otlp = matches.get_one::<String>("otlp").cloned();
+        let min_node_version = matches.get_one::<String>("min-node-version").cloned();
+        let strategy = matches
+            .get_one::<String>("strategy")
+            .map(|strategy| {
+                strategy.parse::<Strategy>().unwrap_or_else(|err| {
+                    log_error!("Strategy", "{err}");
+                    crate::exit::ExitCode::Config.exit();
+                })
+            })
+            .unwrap_or_default();
+        let geoip = matches.get_one::<String>("geoip").map(PathBuf::from);
+        let peers = matches
+            .get_many::<String>("peer")
+            .map(|peers| peers.cloned().collect())
+            .unwrap_or_default();
+
         let rate_limit = matches.get_one::<RateLimit>("rate-limit").cloned();
         let listen = matches
             .get_one::<String>("listen")
             .cloned()
             .unwrap_or("127.0.0.1:8888".to_string());
 
+        let explicit = matches
+            .ids()
+            .filter(|id| {
+                matches.value_source(id.as_str()) == Some(clap::parser::ValueSource::CommandLine)
+            })
+            .map(|id| id.to_string())
+            .collect();
+
         let action = if matches.get_one::<bool>("version").cloned().unwrap_or(false) {
             println!("v{}", crate::VERSION);
             std::process::exit(0);
@@ -171,6 +516,47 @@ impl Args {
             Action::Unpack
         } else if let Some(_matches) = matches.subcommand_matches("update") {
             Action::Update
+        } else if let Some(matches) = matches.subcommand_matches("simulate") {
+            let policy = matches.get_one::<String>("policy").unwrap();
+            let policy = policy.parse::<Policy>().unwrap_or_else(|err| {
+                log_error!("Simulate", "{err}");
+                crate::exit::ExitCode::Config.exit();
+            });
+            let journal = matches.get_one::<String>("journal").map(PathBuf::from);
+            Action::Simulate { policy, journal }
+        } else if let Some(matches) = matches.subcommand_matches("schema") {
+            let dir = matches.get_one::<String>("dir").map(PathBuf::from);
+            Action::Schema { dir }
+        } else if let Some(matches) = matches.subcommand_matches("loadtest") {
+            Action::LoadTest(loadtest::Options {
+                target: matches.get_one::<String>("target").cloned(),
+                concurrency: matches.get_one::<usize>("concurrency").cloned(),
+                duration: matches.get_one::<u64>("duration").cloned(),
+                paths: matches
+                    .get_many::<String>("path")
+                    .map(|paths| paths.cloned().collect())
+                    .unwrap_or_default(),
+                json: matches.get_flag("json"),
+            })
+        } else if let Some(matches) = matches.subcommand_matches("init") {
+            let deployment = matches.get_one::<String>("deployment").map(|deployment| {
+                deployment.parse::<Deployment>().unwrap_or_else(|err| {
+                    log_error!("Init", "{err}");
+                    crate::exit::ExitCode::Config.exit();
+                })
+            });
+            Action::Init(blueprint::Options {
+                deployment,
+                dir: matches.get_one::<String>("dir").map(PathBuf::from),
+                domain: matches.get_one::<String>("domain").cloned(),
+                network: matches.get_one::<String>("network").cloned(),
+                nodes: matches
+                    .get_many::<String>("node")
+                    .map(|nodes| nodes.cloned().collect())
+                    .unwrap_or_default(),
+                mtls: matches.get_flag("mtls"),
+                force: matches.get_flag("force"),
+            })
         } else {
             Action::Run
         };
@@ -179,9 +565,27 @@ impl Args {
             trace,
             verbose,
             debug,
+            audit,
+            no_delegation,
             auto_update,
             user_config,
+            profile,
+            snapshot_url,
+            registry,
+            registry_key,
+            control,
+            tls_ca,
+            tls_cert,
+            tls_key,
+            otlp,
+            peers,
+            min_node_version,
+            strategy,
+            geoip,
             public,
+            strict,
+            strict_startup,
+            explicit,
             // election,
             // status,
             listen,
@@ -191,6 +595,41 @@ impl Args {
     }
 }
 
+#[cfg(test)]
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            listen: "127.0.0.1:8888".to_string(),
+            rate_limit: None,
+            verbose: false,
+            trace: false,
+            debug: false,
+            audit: false,
+            no_delegation: false,
+            auto_update: false,
+            user_config: None,
+            profile: None,
+            snapshot_url: None,
+            registry: None,
+            registry_key: None,
+            control: None,
+            tls_ca: None,
+            tls_cert: None,
+            tls_key: None,
+            otlp: None,
+            peers: vec![],
+            min_node_version: None,
+            strategy: Strategy::default(),
+            geoip: None,
+            public: false,
+            strict: false,
+            strict_startup: false,
+            explicit: vec![],
+            action: Action::Run,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RateLimit {
     pub requests: u64,
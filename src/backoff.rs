@@ -0,0 +1,34 @@
+use crate::imports::*;
+
+/// Reconnect delay schedule: starting at `[keepalive] retry-interval-sec`,
+/// doubling with each failed attempt up to `retry-max-sec`; each delay is
+/// reduced by a random fraction of up to `retry-jitter`, spreading the
+/// reconnect attempts of connections that failed at the same time.
+#[derive(Debug, Default)]
+pub struct Backoff {
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Delay before the next attempt
+    pub fn next(&mut self) -> Duration {
+        let base = KeepaliveSettings::retry_interval().as_secs_f64();
+        let max = KeepaliveSettings::retry_max().as_secs_f64().max(base);
+        let delay = (base * 2f64.powi(self.attempts.min(32) as i32)).min(max);
+        self.attempts = self.attempts.saturating_add(1);
+
+        let jitter = KeepaliveSettings::retry_jitter().clamp(0.0, 1.0);
+        let delay = delay * (1.0 - jitter * rand::thread_rng().gen::<f64>());
+        Duration::from_secs_f64(delay)
+    }
+
+    /// Restart the schedule (after a successful connect)
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+
+    /// Number of failed attempts since the last reset
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
@@ -0,0 +1,93 @@
+use crate::imports::*;
+
+/// Maximum number of operations in a batch
+const MAX_OPERATIONS: usize = 256;
+/// Valid range of node weights (percent, default 100)
+pub const WEIGHT_RANGE: std::ops::RangeInclusive<u64> = 1..=1000;
+
+/// Admin operation applied to a node, identified by hex uid or short id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Operation {
+    /// Exclude the node from elections
+    Drain { uid: String },
+    /// Return a drained node to elections
+    Undrain { uid: String },
+    /// Scale the node's share of elections (percent, 100 is neutral)
+    Weight { uid: String, weight: u64 },
+}
+
+impl Operation {
+    pub fn uid(&self) -> &str {
+        match self {
+            Operation::Drain { uid }
+            | Operation::Undrain { uid }
+            | Operation::Weight { uid, .. } => uid,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Operation::Weight { weight, .. } = self {
+            if !WEIGHT_RANGE.contains(weight) {
+                return Err(Error::custom(format!(
+                    "weight {weight} of `{}` is out of range ({}..={})",
+                    self.uid(),
+                    WEIGHT_RANGE.start(),
+                    WEIGHT_RANGE.end()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the operation to its (resolved) connection
+    pub fn apply(&self, connection: &Arc<Connection>) {
+        match self {
+            Operation::Drain { .. } => connection.set_draining(true),
+            Operation::Undrain { .. } => connection.set_draining(false),
+            Operation::Weight { weight, .. } => connection.set_weight(*weight),
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Drain { uid } => write!(f, "drain {uid}"),
+            Operation::Undrain { uid } => write!(f, "undrain {uid}"),
+            Operation::Weight { uid, weight } => write!(f, "weight {uid} {weight}"),
+        }
+    }
+}
+
+/// Batch of admin operations, validated as a whole and
+/// applied atomically (see [`Monitor::apply_batch`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Batch {
+    pub operations: Vec<Operation>,
+}
+
+impl Batch {
+    pub fn validate(&self) -> Result<()> {
+        if self.operations.is_empty() {
+            return Err(Error::custom("empty batch"));
+        }
+        if self.operations.len() > MAX_OPERATIONS {
+            return Err(Error::custom(format!(
+                "batch exceeds {MAX_OPERATIONS} operations"
+            )));
+        }
+        self.operations.iter().try_for_each(Operation::validate)
+    }
+}
+
+impl Display for Batch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let operations = self
+            .operations
+            .iter()
+            .map(|operation| operation.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", operations.join(", "))
+    }
+}
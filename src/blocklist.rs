@@ -0,0 +1,265 @@
+use crate::imports::*;
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::mpsc;
+
+// tracked addresses before idle trackers are dropped
+const MAX_TRACKERS: usize = 65536;
+
+/// Blocklist entry of a client address
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Entry {
+    // number of blocks, halved every half-life
+    pub offenses: u32,
+    // time (UNIX seconds) of the last block
+    pub last_offense: u64,
+    // time (UNIX seconds) the block expires
+    pub blocked_until: u64,
+}
+
+impl Entry {
+    /// Offense count after the exponential decay up to `now`
+    fn decayed(&self, now: u64) -> u32 {
+        let half_life = BlocklistSettings::half_life().as_secs().max(1);
+        let halvings = now.saturating_sub(self.last_offense) / half_life;
+        self.offenses.checked_shr(halvings.min(32) as u32).unwrap_or(0)
+    }
+}
+
+// in-memory request and strike accounting of a client address
+struct Tracker {
+    window_start: u64,
+    requests: u64,
+    strikes: u64,
+    struck: bool,
+}
+
+/// Client addresses repeatedly exceeding the per-address rate limit or
+/// probing admin endpoints without a session are blocked for a period
+/// doubling with each repeated offense. Blocked addresses are rejected
+/// by [`guard`] before routing. The blocklist is persisted to
+/// `~/.vecno-resolver/blocklist.json` by a background writer thread.
+pub struct Blocklist {
+    entries: RwLock<BTreeMap<IpAddr, Entry>>,
+    trackers: Mutex<HashMap<IpAddr, Tracker>>,
+    writer: mpsc::Sender<BTreeMap<IpAddr, Entry>>,
+}
+
+impl Blocklist {
+    pub fn get() -> &'static Self {
+        static BLOCKLIST: OnceLock<Blocklist> = OnceLock::new();
+        BLOCKLIST.get_or_init(Blocklist::load)
+    }
+
+    fn load() -> Self {
+        let path = global_config_folder().join("blocklist.json");
+        let entries = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str(&json)?))
+            {
+                Ok(entries) => entries,
+                Err(err) => {
+                    log_error!("Blocklist", "Unable to load `{}`: {err}", path.display());
+                    BTreeMap::default()
+                }
+            }
+        } else {
+            BTreeMap::default()
+        };
+
+        let (writer, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("blocklist".to_string())
+            .spawn(move || {
+                while let Ok(mut entries) = receiver.recv() {
+                    // only the latest of the queued snapshots is written
+                    while let Ok(newer) = receiver.try_recv() {
+                        entries = newer;
+                    }
+                    if let Err(err) = store(&path, &entries) {
+                        log_error!("Blocklist", "{err}");
+                    }
+                }
+            })
+            .expect("unable to start the blocklist writer");
+
+        Self {
+            entries: RwLock::new(entries),
+            trackers: Mutex::new(HashMap::new()),
+            writer,
+        }
+    }
+
+    pub fn is_blocked(&self, ip: &IpAddr) -> bool {
+        self.entries
+            .read()
+            .unwrap()
+            .get(ip)
+            .is_some_and(|entry| entry.blocked_until > unix_now())
+    }
+
+    /// Account a request from `ip`, `false` if the address exceeded
+    /// the rate limit (a strike, once per window)
+    fn request(&self, ip: IpAddr) -> bool {
+        let now = unix_now();
+        let strike = {
+            let mut trackers = self.trackers.lock().unwrap();
+            if trackers.len() >= MAX_TRACKERS {
+                let window = BlocklistSettings::window().as_secs();
+                trackers.retain(|_, tracker| {
+                    tracker.struck || now.saturating_sub(tracker.window_start) < window
+                });
+            }
+            let tracker = trackers.entry(ip).or_insert(Tracker {
+                window_start: now,
+                requests: 0,
+                strikes: 0,
+                struck: false,
+            });
+            if now.saturating_sub(tracker.window_start) >= BlocklistSettings::window().as_secs() {
+                // strikes only accumulate across consecutive windows
+                if !tracker.struck {
+                    tracker.strikes = 0;
+                }
+                tracker.window_start = now;
+                tracker.requests = 0;
+                tracker.struck = false;
+            }
+            tracker.requests += 1;
+            if tracker.requests <= BlocklistSettings::requests() {
+                return true;
+            }
+            tracker.requests == BlocklistSettings::requests() + 1
+        };
+
+        if strike {
+            self.strike(ip);
+        }
+        false
+    }
+
+    /// Record an offense of `ip`, blocking the address after too many strikes
+    fn strike(&self, ip: IpAddr) {
+        let is_blocked = {
+            let mut trackers = self.trackers.lock().unwrap();
+            let Some(tracker) = trackers.get_mut(&ip) else {
+                return;
+            };
+            tracker.strikes += 1;
+            tracker.struck = true;
+            if tracker.strikes >= BlocklistSettings::strikes() {
+                tracker.strikes = 0;
+                true
+            } else {
+                false
+            }
+        };
+
+        if is_blocked {
+            self.block(ip);
+        }
+    }
+
+    fn block(&self, ip: IpAddr) {
+        let now = unix_now();
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.entry(ip).or_default();
+        entry.offenses = entry.decayed(now) + 1;
+        entry.last_offense = now;
+        let ban = BlocklistSettings::ban(entry.offenses);
+        entry.blocked_until = now + ban.as_secs();
+        let detail = format!("{}s (offense {})", ban.as_secs(), entry.offenses);
+        log_warn!("Blocklist", "Blocked {ip} for {detail}");
+
+        // drop expired entries with no remaining offenses
+        entries.retain(|_, entry| entry.blocked_until > now || entry.decayed(now) > 0);
+        self.persist(&entries);
+        drop(entries);
+        Journal::record(JournalKind::Blocked, ip, Some(detail));
+    }
+
+    /// Blocklist entries (address -> entry)
+    pub fn entries(&self) -> BTreeMap<IpAddr, Entry> {
+        self.entries.read().unwrap().clone()
+    }
+
+    /// Remove `ip` from the blocklist, `false` if it was not listed
+    pub fn remove(&self, ip: &IpAddr) -> bool {
+        let mut entries = self.entries.write().unwrap();
+        if entries.remove(ip).is_some() {
+            self.trackers.lock().unwrap().remove(ip);
+            self.persist(&entries);
+            true
+        } else {
+            false
+        }
+    }
+
+    // queue a snapshot of `entries` for the writer thread (taken under
+    // the entries lock, so snapshots are queued in order)
+    fn persist(&self, entries: &BTreeMap<IpAddr, Entry>) {
+        if self.writer.send(entries.clone()).is_err() {
+            log_error!("Blocklist", "the blocklist writer has stopped");
+        }
+    }
+}
+
+/// Write the blocklist file (run by the writer thread)
+fn store(path: &Path, entries: &BTreeMap<IpAddr, Entry>) -> Result<()> {
+    let json = serde_json::to_string_pretty(entries)?;
+    std::fs::write(path, json).map_err(|err| Error::file(path, err))
+}
+
+/// Client address of the request. With `[blocklist] trust-forwarded`,
+/// the `x-forwarded-for` entry appended by the outermost of the
+/// `trusted-hops` proxies (counted from the right): entries to its left
+/// are set by the client and are not trusted.
+pub fn client_ip(addr: SocketAddr, headers: &HeaderMap) -> IpAddr {
+    if !BlocklistSettings::trust_forwarded() {
+        return addr.ip();
+    }
+    let entries = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect::<Vec<_>>();
+    entries
+        .len()
+        .checked_sub(BlocklistSettings::trusted_hops())
+        .and_then(|index| entries[index].trim().parse().ok())
+        .unwrap_or_else(|| addr.ip())
+}
+
+/// Middleware rejecting blocked and rate limited addresses
+/// and recording unauthorized admin requests as strikes
+pub async fn guard(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let blocklist = Blocklist::get();
+    let ip = client_ip(addr, request.headers());
+    if blocklist.is_blocked(&ip) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if !blocklist.request(ip) {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    let is_admin = request.uri().path().starts_with("/admin");
+    let response = next.run(request).await;
+    if is_admin && response.status() == StatusCode::UNAUTHORIZED {
+        blocklist.strike(ip);
+    }
+    response
+}
@@ -0,0 +1,337 @@
+//!
+//! Deployment blueprints (`vecno-resolver init`): generates a starter
+//! node configuration, a systemd unit and the TLS setup of a deployment
+//! profile into a target directory, prompting for the values not given
+//! as flags.
+//!
+
+use crate::imports::*;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const SERVICE: &str = include_str!("../templates/blueprint/vecno-resolver.service");
+const NGINX: &str = include_str!("../templates/blueprint/nginx.conf");
+const TLS_PUBLIC: &str = include_str!("../templates/blueprint/tls-public.md");
+const TLS_PRIVATE: &str = include_str!("../templates/blueprint/tls-private.md");
+
+/// Deployment profile of a blueprint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deployment {
+    /// Public resolver behind a TLS terminating reverse proxy
+    Public,
+    /// Resolver of a private node fleet (optionally with mutual TLS)
+    Private,
+    /// Local development against a single local node
+    Dev,
+}
+
+impl Deployment {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Deployment::Public => "public",
+            Deployment::Private => "private",
+            Deployment::Dev => "dev",
+        }
+    }
+
+    fn listen(&self) -> &'static str {
+        match self {
+            Deployment::Private => "0.0.0.0:8888",
+            Deployment::Public | Deployment::Dev => "127.0.0.1:8888",
+        }
+    }
+}
+
+impl Display for Deployment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Deployment {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(Deployment::Public),
+            "private" => Ok(Deployment::Private),
+            "dev" => Ok(Deployment::Dev),
+            _ => Err(format!(
+                "invalid deployment `{s}`, must be `public`, `private` or `dev`"
+            )),
+        }
+    }
+}
+
+/// `init` subcommand options; values not given are prompted for
+/// (without `--deployment`) or defaulted
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub deployment: Option<Deployment>,
+    pub dir: Option<PathBuf>,
+    pub domain: Option<String>,
+    pub network: Option<String>,
+    pub nodes: Vec<String>,
+    pub mtls: bool,
+    pub force: bool,
+}
+
+/// Values of a blueprint once all options are resolved
+struct Blueprint {
+    deployment: Deployment,
+    dir: PathBuf,
+    domain: Option<String>,
+    network: String,
+    nodes: Vec<String>,
+    mtls: bool,
+}
+
+impl Blueprint {
+    /// Resolve `options`, prompting for missing values unless the
+    /// deployment was given on the command line
+    fn try_new(options: Options) -> Result<Self> {
+        let interactive = options.deployment.is_none();
+        if interactive {
+            cliclack::intro("Vecno resolver deployment blueprint")?;
+        }
+
+        let deployment = match options.deployment {
+            Some(deployment) => deployment,
+            None => cliclack::select("Deployment profile")
+                .item(Deployment::Public, "public", "public resolver behind nginx (TLS)")
+                .item(Deployment::Private, "private", "resolver of a private node fleet")
+                .item(Deployment::Dev, "dev", "local development node")
+                .interact()?,
+        };
+
+        let dir = match options.dir {
+            Some(dir) => dir,
+            None if interactive => {
+                let dir: String = cliclack::input("Target directory")
+                    .default_input("vecno-resolver")
+                    .interact()?;
+                PathBuf::from(dir)
+            }
+            None => PathBuf::from("vecno-resolver"),
+        };
+
+        let network = match options.network {
+            Some(network) => network,
+            None if interactive => cliclack::input("Network")
+                .default_input("mainnet")
+                .interact()?,
+            None => "mainnet".to_string(),
+        };
+        NetworkId::from_str(&network)
+            .map_err(|err| Error::config(format!("Invalid network `{network}`: {err}")))?;
+
+        let domain = match (deployment, options.domain) {
+            (Deployment::Public, Some(domain)) => Some(domain),
+            (Deployment::Public, None) if interactive => {
+                Some(cliclack::input("Public domain of the resolver").interact()?)
+            }
+            (Deployment::Public, None) => {
+                return Err(Error::config("The `public` deployment requires `--domain`"));
+            }
+            (_, _) => None,
+        };
+
+        let mtls = match deployment {
+            Deployment::Private if !options.mtls && interactive => {
+                cliclack::confirm("Connect to nodes with mutual TLS?").interact()?
+            }
+            Deployment::Private => options.mtls,
+            Deployment::Public | Deployment::Dev => false,
+        };
+
+        let nodes = if !options.nodes.is_empty() {
+            options.nodes
+        } else if deployment == Deployment::Dev {
+            vec!["127.0.0.1:8110".to_string()]
+        } else if interactive {
+            let nodes: String = cliclack::input("Node addresses (fqdn[:port], comma separated)")
+                .interact()?;
+            nodes
+                .split(',')
+                .map(|node| node.trim().to_string())
+                .filter(|node| !node.is_empty())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if nodes.is_empty() {
+            return Err(Error::config(format!(
+                "The `{deployment}` deployment requires at least one `--node`"
+            )));
+        }
+
+        Ok(Self {
+            deployment,
+            dir,
+            domain,
+            network,
+            nodes,
+            mtls,
+        })
+    }
+
+    fn is_tls(&self) -> bool {
+        match self.deployment {
+            Deployment::Public => true,
+            Deployment::Private => self.mtls,
+            Deployment::Dev => false,
+        }
+    }
+
+    fn tls_dir(&self) -> PathBuf {
+        self.dir.join("tls")
+    }
+
+    /// Node list (`--config-file`)
+    fn config(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "#");
+        let _ = writeln!(
+            text,
+            "# Node configuration generated by `vecno-resolver init` ({} deployment)",
+            self.deployment
+        );
+        let _ = writeln!(
+            text,
+            "# See README.md (Configuring a Private Node Cluster) for all fields."
+        );
+        let _ = writeln!(text, "#");
+        for node in self.nodes.iter() {
+            let _ = writeln!(text);
+            let _ = writeln!(text, "[[node]]");
+            let _ = writeln!(text, "service = \"vecno\"");
+            let _ = writeln!(text, "transport-type = \"wrpc-borsh\"");
+            let _ = writeln!(text, "tls = {}", self.is_tls());
+            let _ = writeln!(text, "network = \"{}\"", self.network);
+            let _ = writeln!(text, "fqdn = \"{node}\"");
+        }
+        text
+    }
+
+    /// Resolver command line of the deployment
+    fn command(&self, exe: &str) -> String {
+        let config = absolute(&self.dir.join("resolver.toml"));
+        let mut args = vec![
+            exe.to_string(),
+            format!("--listen={}", self.deployment.listen()),
+            format!("--config-file={}", config.display()),
+        ];
+        match self.deployment {
+            Deployment::Public => {
+                args.push("--public".to_string());
+                args.push("--strict-startup".to_string());
+            }
+            Deployment::Private => {
+                args.push("--strict-startup".to_string());
+                if self.mtls {
+                    let tls = absolute(&self.tls_dir());
+                    args.push(format!("--tls-ca={}", tls.join("ca.pem").display()));
+                    args.push(format!("--tls-cert={}", tls.join("client.pem").display()));
+                    args.push(format!("--tls-key={}", tls.join("client.key").display()));
+                }
+            }
+            Deployment::Dev => {
+                args.push("--verbose".to_string());
+            }
+        }
+        args.join(" ")
+    }
+
+    /// Files of the blueprint, relative to the target directory
+    fn files(&self) -> Vec<(&'static str, String)> {
+        let exe = std::env::current_exe()
+            .map(|exe| exe.display().to_string())
+            .unwrap_or_else(|_| "/usr/local/bin/vecno-resolver".to_string());
+        let tpl = Tpl::from(
+            &[
+                ("deployment", self.deployment.to_string()),
+                ("exec", self.command(&exe)),
+                ("listen", self.deployment.listen().to_string()),
+                ("domain", self.domain.clone().unwrap_or_default()),
+                ("tls", absolute(&self.tls_dir()).display().to_string()),
+            ][..],
+        );
+
+        let mut files = vec![("resolver.toml", self.config())];
+        match self.deployment {
+            Deployment::Public => {
+                files.push(("vecno-resolver.service", tpl.render(SERVICE)));
+                files.push(("nginx.conf", tpl.render(NGINX)));
+                files.push(("TLS.md", tpl.render(TLS_PUBLIC)));
+            }
+            Deployment::Private => {
+                files.push(("vecno-resolver.service", tpl.render(SERVICE)));
+                if self.mtls {
+                    files.push(("TLS.md", tpl.render(TLS_PRIVATE)));
+                }
+            }
+            Deployment::Dev => {}
+        }
+        files
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Generate the blueprint of `options`
+pub fn run(options: Options) -> Result<()> {
+    let force = options.force;
+    let blueprint = Blueprint::try_new(options)?;
+    let files = blueprint.files();
+
+    if !force {
+        let existing = files
+            .iter()
+            .map(|(name, _)| blueprint.dir.join(name))
+            .filter(|path| path.exists())
+            .map(|path| format!("`{}`", path.display()))
+            .collect::<Vec<_>>();
+        if !existing.is_empty() {
+            return Err(Error::config(format!(
+                "Refusing to overwrite {} (use `--force`)",
+                existing.join(", ")
+            )));
+        }
+    }
+
+    std::fs::create_dir_all(&blueprint.dir)?;
+    if blueprint.mtls {
+        std::fs::create_dir_all(blueprint.tls_dir())?;
+    }
+    for (name, content) in files.iter() {
+        let path = blueprint.dir.join(name);
+        std::fs::write(&path, content).map_err(|err| Error::file(&path, err))?;
+        log_success!("Init", "Wrote `{}`", path.display());
+    }
+
+    match blueprint.deployment {
+        Deployment::Dev => {
+            let exe = std::env::current_exe()
+                .map(|exe| exe.display().to_string())
+                .unwrap_or_else(|_| "vecno-resolver".to_string());
+            log_info!("Init", "Run the resolver with: {}", blueprint.command(&exe));
+        }
+        Deployment::Public | Deployment::Private => {
+            log_info!(
+                "Init",
+                "Review the generated files, then install `vecno-resolver.service`"
+            );
+        }
+    }
+
+    Ok(())
+}
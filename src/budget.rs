@@ -0,0 +1,168 @@
+use crate::imports::*;
+use chrono::prelude::*;
+
+/// Alert severity derived from the consumed error budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Critical,
+    Exhausted,
+}
+
+impl Severity {
+    fn from_burn(burn: f64) -> Self {
+        if burn >= 1.0 {
+            Severity::Exhausted
+        } else if burn >= 0.9 {
+            Severity::Critical
+        } else if burn >= 0.5 {
+            Severity::Warning
+        } else {
+            Severity::Ok
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Severity::Ok => "ok",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+            Severity::Exhausted => "exhausted",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug)]
+struct Period {
+    // calendar month (`YYYY-MM`) the period belongs to
+    month: String,
+    // duration of the month in seconds
+    length: f64,
+    // accumulated time without an available synced node
+    downtime: Duration,
+    last_sample: Option<u64>,
+    severity: Severity,
+}
+
+impl Period {
+    fn new(now: DateTime<Utc>) -> Self {
+        let month = now.format("%Y-%m").to_string();
+        let start = Utc
+            .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+            .unwrap();
+        let (year, month_next) = if now.month() == 12 {
+            (now.year() + 1, 1)
+        } else {
+            (now.year(), now.month() + 1)
+        };
+        let end = Utc.with_ymd_and_hms(year, month_next, 1, 0, 0, 0).unwrap();
+        let length = (end - start).num_seconds() as f64;
+
+        Self {
+            month,
+            length,
+            downtime: Duration::ZERO,
+            last_sample: None,
+            severity: Severity::Ok,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BudgetReport {
+    pub network: NetworkId,
+    pub target: f64,
+    pub month: String,
+    pub budget_sec: f64,
+    pub downtime_sec: f64,
+    pub remaining_sec: f64,
+    pub remaining: f64,
+    pub severity: Severity,
+}
+
+/// Monthly availability error budget per network. Downtime is
+/// accumulated for periods where no synced node is available.
+#[derive(Debug, Default)]
+pub struct ErrorBudget {
+    periods: Mutex<AHashMap<NetworkId, Period>>,
+}
+
+impl ErrorBudget {
+    /// Record availability of `network` at the current time.
+    pub fn sample(&self, network: NetworkId, available: bool) {
+        let now = Utc::now();
+        let ts = now.timestamp_millis() as u64;
+        let month = now.format("%Y-%m").to_string();
+
+        let mut periods = self.periods.lock().unwrap();
+        let period = periods.entry(network).or_insert_with(|| Period::new(now));
+        if period.month != month {
+            *period = Period::new(now);
+        }
+
+        if let Some(last) = period.last_sample.replace(ts) {
+            if !available {
+                period.downtime += Duration::from_millis(ts.saturating_sub(last));
+            }
+        }
+
+        let report = Self::report(network, period);
+        if report.severity != period.severity {
+            let escalated = report.severity > period.severity;
+            period.severity = report.severity;
+            let remaining = report.remaining * 100.0;
+            if escalated && report.severity >= Severity::Critical {
+                log_error!("Budget", "{network} error budget {}: {remaining:.2}% remaining", report.severity);
+            } else if escalated {
+                log_warn!("Budget", "{network} error budget {}: {remaining:.2}% remaining", report.severity);
+            } else {
+                log_success!("Budget", "{network} error budget {}: {remaining:.2}% remaining", report.severity);
+            }
+            Journal::record(
+                JournalKind::BudgetSeverity,
+                network,
+                Some(format!("{}: {remaining:.2}% remaining", report.severity)),
+            );
+        }
+    }
+
+    pub fn reports(&self) -> Vec<BudgetReport> {
+        self.periods
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(network, period)| Self::report(*network, period))
+            .collect()
+    }
+
+    fn report(network: NetworkId, period: &Period) -> BudgetReport {
+        let target = BudgetSettings::target();
+        let budget_sec = period.length * (1.0 - target);
+        let downtime_sec = period.downtime.as_secs_f64();
+        let remaining_sec = (budget_sec - downtime_sec).max(0.0);
+        let burn = if budget_sec > 0.0 {
+            downtime_sec / budget_sec
+        } else if downtime_sec > 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        BudgetReport {
+            network,
+            target,
+            month: period.month.clone(),
+            budget_sec,
+            downtime_sec,
+            remaining_sec,
+            remaining: (1.0 - burn).max(0.0),
+            severity: Severity::from_burn(burn),
+        }
+    }
+}
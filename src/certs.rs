@@ -0,0 +1,125 @@
+//!
+//! TLS certificate expiry of `wss://` node endpoints. The wRPC client
+//! does not expose the certificates of its handshakes, so the endpoint
+//! certificate is observed with a separate TLS handshake, verified the
+//! same way (WebPKI roots and `--tls-ca`). The expiry of the end-entity
+//! certificate is recorded before verification, so that certificates
+//! that already expired (and fail the handshake) are reported as well.
+//!
+
+use crate::imports::*;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{
+    self,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    client::WebPkiServerVerifier,
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
+
+/// Verifier recording the expiry (`not_after`, UNIX seconds) of the
+/// end-entity certificate before delegating to the WebPKI verifier
+#[derive(Debug)]
+struct Observer {
+    inner: Arc<WebPkiServerVerifier>,
+    not_after: Mutex<Option<u64>>,
+}
+
+impl ServerCertVerifier for Observer {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        if let Ok((_, cert)) = X509Certificate::from_der(end_entity.as_ref()) {
+            let not_after = cert.validity().not_after.timestamp().max(0) as u64;
+            *self.not_after.lock().unwrap() = Some(not_after);
+        }
+        self.inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Certificate observed on a node endpoint
+#[derive(Debug)]
+pub struct Observation {
+    /// Expiry of the end-entity certificate (UNIX seconds)
+    pub not_after: u64,
+    /// Handshake (certificate verification) error, if any
+    pub error: Option<String>,
+}
+
+/// Handshake with the `wss://` endpoint `address`, returning the
+/// observed certificate (`None` for endpoints without TLS)
+pub async fn observe(address: &str) -> Result<Option<Observation>> {
+    let url = reqwest::Url::parse(address)
+        .map_err(|err| Error::custom(format!("Invalid node url `{address}`: {err}")))?;
+    if url.scheme() != "wss" {
+        return Ok(None);
+    }
+    let host = url
+        .host_str()
+        .map(|host| host.trim_matches(['[', ']']).to_string())
+        .ok_or_else(|| Error::custom(format!("Node url `{address}` has no host")))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(tls::roots()), provider.clone())
+        .build()
+        .map_err(|err| Error::custom(format!("TLS verifier: {err}")))?;
+    let observer = Arc::new(Observer {
+        inner,
+        not_after: Mutex::new(None),
+    });
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| Error::custom(format!("TLS config: {err}")))?
+        .dangerous()
+        .with_custom_certificate_verifier(observer.clone())
+        .with_no_client_auth();
+
+    let server_name = ServerName::try_from(host.clone())
+        .map_err(|err| Error::custom(format!("Invalid TLS server name `{host}`: {err}")))?;
+    let stream = TcpStream::connect((host.as_str(), port)).await?;
+    let result = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await;
+
+    let not_after = *observer.not_after.lock().unwrap();
+    match (not_after, result) {
+        (Some(not_after), result) => Ok(Some(Observation {
+            not_after,
+            error: result.err().map(|err| err.to_string()),
+        })),
+        (None, Err(err)) => Err(Error::custom(format!("TLS handshake: {err}"))),
+        (None, Ok(_)) => Err(Error::custom("TLS handshake: no server certificate")),
+    }
+}
@@ -0,0 +1,197 @@
+use crate::imports::*;
+
+/// Node list change submitted via the admin API.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum Change {
+    /// Add a node that is not present in the configuration
+    Add { node: NodeConfig },
+    /// Remove a node by its uid (hex)
+    Remove { uid: String },
+    /// Scale the node's share of elections (percent, 100 is neutral)
+    Weight { uid: String, weight: u64 },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Add { node } => write!(f, "add {}", node.fqdn),
+            Change::Remove { uid } => write!(f, "remove {}", uid),
+            Change::Weight { uid, weight } => write!(f, "weight {} {}", uid, weight),
+        }
+    }
+}
+
+/// A [`Change`] with an optional activation time (UNIX seconds).
+/// Changes without an activation time are applied immediately.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScheduledChange {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub activate_at: Option<u64>,
+    pub change: Change,
+}
+
+impl ScheduledChange {
+    pub fn is_due(&self, now: u64) -> bool {
+        self.activate_at.map(|ts| ts <= now).unwrap_or(true)
+    }
+}
+
+/// Pending admin changes and the overrides produced by
+/// the changes that have already been activated. Pending changes
+/// are persisted to `~/.vecno-resolver/changes-<service>.json`, so
+/// that changes scheduled for a later window survive a restart.
+#[derive(Debug)]
+pub struct Changes {
+    pending: Vec<ScheduledChange>,
+    added: AHashMap<u64, Arc<Node>>,
+    removed: HashSet<u64>,
+    weights: AHashMap<u64, u64>,
+    path: PathBuf,
+}
+
+impl Changes {
+    pub fn load(service: Service) -> Self {
+        let path = global_config_folder().join(format!("changes-{service}.json"));
+        let pending = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str::<Vec<ScheduledChange>>(&json)?))
+            {
+                Ok(pending) => pending,
+                Err(err) => {
+                    log_error!("Changes", "Unable to load `{}`: {err}", path.display());
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            pending,
+            added: AHashMap::default(),
+            removed: HashSet::default(),
+            weights: AHashMap::default(),
+            path,
+        }
+    }
+
+    fn store(&self) {
+        let result = serde_json::to_string_pretty(&self.pending)
+            .map_err(Error::from)
+            .and_then(|json| std::fs::write(&self.path, json).map_err(|err| Error::file(&self.path, err)));
+        if let Err(err) = result {
+            log_error!("Changes", "{err}");
+        }
+    }
+
+    pub fn schedule(&mut self, mut change: ScheduledChange) -> Result<String> {
+        match &change.change {
+            Change::Add { node } => {
+                if node.address.is_none() && node.fqdn.is_empty() {
+                    return Err(Error::custom("node requires `fqdn` or `address`"));
+                }
+            }
+            Change::Remove { uid } => {
+                parse_uid(uid)?;
+            }
+            Change::Weight { uid, weight } => {
+                parse_uid(uid)?;
+                if !WEIGHT_RANGE.contains(weight) {
+                    return Err(Error::custom(format!(
+                        "weight {weight} of `{uid}` is out of range ({}..={})",
+                        WEIGHT_RANGE.start(),
+                        WEIGHT_RANGE.end()
+                    )));
+                }
+            }
+        }
+
+        change.id = uuid::Uuid::new_v4().to_string();
+        let id = change.id.clone();
+        let is_scheduled = change.activate_at.is_some();
+        self.pending.push(change);
+        if is_scheduled {
+            self.store();
+        }
+        Ok(id)
+    }
+
+    pub fn cancel(&mut self, id: &str) -> bool {
+        let len = self.pending.len();
+        self.pending.retain(|change| change.id != id);
+        let cancelled = self.pending.len() != len;
+        if cancelled {
+            self.store();
+        }
+        cancelled
+    }
+
+    pub fn pending(&self) -> Vec<ScheduledChange> {
+        self.pending.clone()
+    }
+
+    /// Activate all changes that are due, returning the activated changes.
+    pub fn activate(&mut self, now: u64) -> Vec<Change> {
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|change| change.is_due(now));
+        self.pending = pending;
+        if due.iter().any(|change| change.activate_at.is_some()) {
+            self.store();
+        }
+
+        for ScheduledChange { change, .. } in due.iter() {
+            log_info!("Admin", "Applying scheduled change: {change}");
+            Journal::record(JournalKind::ChangeApplied, change, None);
+            match change.clone() {
+                Change::Add { node } => {
+                    let node = Arc::new(Node::from(node));
+                    self.removed.remove(&node.uid());
+                    self.added.insert(node.uid(), node);
+                }
+                Change::Remove { uid } => {
+                    // validated in `schedule()`
+                    if let Ok(uid) = parse_uid(&uid) {
+                        self.added.remove(&uid);
+                        self.removed.insert(uid);
+                    }
+                }
+                Change::Weight { uid, weight } => {
+                    // validated in `schedule()`
+                    if let Ok(uid) = parse_uid(&uid) {
+                        self.weights.insert(uid, weight);
+                    }
+                }
+            }
+        }
+
+        due.into_iter().map(|scheduled| scheduled.change).collect()
+    }
+
+    /// Apply activated overrides on top of the configured node list.
+    pub fn apply(&self, nodes: &[Arc<Node>]) -> Vec<Arc<Node>> {
+        nodes
+            .iter()
+            .filter(|node| !self.added.contains_key(&node.uid()))
+            .chain(self.added.values())
+            .filter(|node| !self.removed.contains(&node.uid()))
+            .map(|node| match self.weights.get(&node.uid()) {
+                Some(&weight) if weight != node.weight => Arc::new(Node {
+                    weight,
+                    ..(**node).clone()
+                }),
+                _ => node.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Parse a hex node uid
+pub fn parse_uid(uid: &str) -> Result<u64> {
+    u64::from_str_radix(uid.trim(), 16).map_err(|_| Error::custom(format!("invalid uid: `{uid}`")))
+}
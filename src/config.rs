@@ -13,11 +13,102 @@ pub struct Config {
     groups: Option<Vec<Group>>,
     #[serde(rename = "node")]
     nodes: Option<Vec<NodeConfig>>,
+    #[serde(rename = "profile")]
+    profiles: Option<HashMap<String, Profile>>,
+}
+
+/// Named configuration profile. Profile entries are merged on top of
+/// the entries inherited from the parent profile (or the top-level
+/// configuration); entries matching an inherited entry replace it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    inherits: Option<String>,
+    #[serde(rename = "transport")]
+    transports: Option<TransportDictionary>,
+    #[serde(rename = "group")]
+    groups: Option<Vec<Group>>,
+    #[serde(rename = "node")]
+    nodes: Option<Vec<NodeConfig>>,
 }
 
 impl Config {
+    /// Resolve the inheritance chain of the profile `name` and
+    /// merge it on top of the top-level configuration.
+    fn with_profile(mut self, name: &str) -> Result<Self> {
+        let profiles = self.profiles.take().unwrap_or_default();
+
+        let mut chain = Vec::new();
+        let mut current = Some(name.to_string());
+        while let Some(name) = current {
+            if chain.contains(&name) {
+                return Err(Error::config(format!(
+                    "Profile inheritance cycle at: `{name}`"
+                )));
+            }
+            let profile = profiles
+                .get(&name)
+                .ok_or_else(|| Error::config(format!("Unknown profile: `{name}`")))?;
+            current = profile.inherits.clone();
+            chain.push(name);
+        }
+
+        for name in chain.iter().rev() {
+            self.merge(profiles[name].clone());
+        }
+
+        Ok(self)
+    }
+
+    fn merge(&mut self, profile: Profile) {
+        let Profile {
+            transports,
+            groups,
+            nodes,
+            ..
+        } = profile;
+
+        if let Some(transports) = transports {
+            self.transports
+                .get_or_insert_with(Default::default)
+                .extend(transports);
+        }
+
+        if let Some(groups) = groups {
+            let list = self.groups.get_or_insert_with(Vec::new);
+            for group in groups {
+                list.retain(|g| g.fqdn != group.fqdn);
+                list.push(group);
+            }
+        }
+
+        if let Some(nodes) = nodes {
+            let list = self.nodes.get_or_insert_with(Vec::new);
+            for node in nodes {
+                list.retain(|n| !n.is_same(&node));
+                list.push(node);
+            }
+        }
+    }
+
     pub fn try_parse(toml: &str) -> Result<Vec<Arc<Node>>> {
-        let config = toml::from_str::<Config>(toml)?;
+        toml::from_str::<Config>(toml)?.try_into_nodes()
+    }
+
+    /// Parse the JSON representation of the node configuration
+    /// (same structure as the TOML config, used by node registries)
+    pub fn try_parse_json(json: &str) -> Result<Vec<Arc<Node>>> {
+        serde_json::from_str::<Config>(json)?.try_into_nodes()
+    }
+
+    fn try_into_nodes(self) -> Result<Vec<Arc<Node>>> {
+        let config = match profile() {
+            Some(name) => self.with_profile(name)?,
+            None => self,
+        };
+
+        for node in config.nodes.iter().flatten() {
+            node.validate()?;
+        }
 
         let mut nodes: Vec<Arc<Node>> = config
             .nodes
@@ -62,6 +153,9 @@ impl Config {
                     transports,
                     services,
                     network,
+                    probes,
+                    region,
+                    roles,
                     ..
                 } = group;
 
@@ -73,8 +167,18 @@ impl Config {
                                     let fqdn = fqdn.replace('*', &id.to_lowercase());
                                     let address =
                                         transport.make_address(&fqdn, service, network_id);
-                                    let node =
-                                        Node::new(service, *network_id, transport, fqdn, address);
+                                    let node = Node::new(
+                                        service,
+                                        *network_id,
+                                        transport,
+                                        fqdn,
+                                        address,
+                                        NodeAttributes {
+                                            probes: probes.clone(),
+                                            region: region.clone(),
+                                            roles: roles.clone(),
+                                        },
+                                    );
                                     nodes.push(node);
                                 } else {
                                     log_error!("Config", "Unknown transport: {}", transport);
@@ -86,6 +190,10 @@ impl Config {
             }
         }
 
+        for probe in nodes.iter().flat_map(|node| node.probes.iter()) {
+            probe.validate()?;
+        }
+
         Ok(nodes)
     }
 }
@@ -109,52 +217,83 @@ mod fs {
     }
 }
 
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Configuration profile selected via `--profile`
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().and_then(|profile| profile.as_deref())
+}
+
 static USER_CONFIG: LazyLock<Mutex<Option<Vec<Arc<Node>>>>> = LazyLock::new(|| Mutex::new(None));
+static USER_CONFIG_PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
 
 pub fn user_config() -> Option<Vec<Arc<Node>>> {
     USER_CONFIG.lock().unwrap().clone()
 }
 
-pub fn init(user_config: &Option<PathBuf>) -> Result<()> {
+/// Re-read the user config file (`local.toml` override or `--config`),
+/// returns `None` if no user config is in use. The node list is only
+/// retained by [`set_user_config`] once it has been applied.
+pub fn reload_user_config() -> Result<Option<Vec<Arc<Node>>>> {
+    let Some(path) = USER_CONFIG_PATH.get().and_then(Option::as_ref) else {
+        return Ok(None);
+    };
+    let toml = fs::read_to_string(path)?;
+    Ok(Some(Config::try_parse(toml.as_str())?))
+}
+
+/// Retain the applied user config node list
+pub fn set_user_config(nodes: Vec<Arc<Node>>) {
+    USER_CONFIG.lock().unwrap().replace(nodes);
+}
+
+pub fn init(user_config: &Option<PathBuf>, profile: &Option<String>) -> Result<()> {
     Settings::load();
 
+    if let Some(profile) = profile {
+        log_success!("Config", "Using profile: `{profile}`");
+    }
+    PROFILE.get_or_init(|| profile.clone());
+
     let global_config_folder = global_config_folder();
     if !global_config_folder.exists() {
         fs::create_dir_all(&global_config_folder)?;
     }
 
     let local_config_override = global_config_folder.join("local.toml");
-    if local_config_override.exists() {
+    let path = if local_config_override.exists() {
         log_warn!(
             "Config",
             "Using local config override: `{}`",
             local_config_override.display()
         );
-        let toml = fs::read_to_string(local_config_override)?;
-        USER_CONFIG
-            .lock()
-            .unwrap()
-            .replace(Config::try_parse(toml.as_str())?);
+        Some(local_config_override)
     } else if let Some(user_config) = user_config {
         // let config_path = Path::new(config);
         if !user_config.exists() {
-            Err(Error::custom(format!(
+            Err(Error::config(format!(
                 "Config file not found: `{}`",
                 user_config.display()
             )))?;
-        } else {
-            let toml = fs::read_to_string(user_config)?;
-            USER_CONFIG
-                .lock()
-                .unwrap()
-                .replace(Config::try_parse(toml.as_str())?);
         }
+        Some(user_config.clone())
+    } else {
+        None
+    };
+
+    USER_CONFIG_PATH.get_or_init(|| path);
+    if let Some(nodes) = reload_user_config()? {
+        set_user_config(nodes);
     }
 
     Ok(())
 }
 
 pub fn global_config_folder() -> PathBuf {
+    // tests must not touch the resolver state of the host
+    if cfg!(test) {
+        return std::env::temp_dir().join("vecno-resolver-test");
+    }
     dirs::home_dir()
         .expect("Could not find home folder")
         .join(".vecno-resolver")
@@ -293,13 +432,14 @@ pub async fn update_global_config() -> Result<Option<Vec<Arc<Node>>>> {
         Ok(None)
     } else {
         log_warn!("Config", "Changes detected");
-        *previous = Some(hash.as_slice().to_vec());
         let key = load_key()?;
         let toml = chacha20poly1305::decrypt_slice(&data, &key)?;
         let config = Config::try_parse(toml.as_str()?)?;
         let global_config_file = global_config_folder().join(global_config_file());
         fs::write(&global_config_file, data)?;
         log_info!("Config", "Updating: `{}`", global_config_file.display());
+        // a failed update is retried on the next check
+        *previous = Some(hash.as_slice().to_vec());
         Ok(Some(config))
     }
 }
@@ -416,11 +556,60 @@ pub fn unpack() -> Result<()> {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Settings {
+    archive: ArchiveSettings,
+    blocklist: BlocklistSettings,
+    budget: BudgetSettings,
+    discovery: DiscoverySettings,
+    drill: DrillSettings,
+    #[serde(default)]
+    features: FeatureSettings,
+    hints: HintSettings,
+    journal: JournalSettings,
     updates: Updates,
     limits: Limits,
+    metrics: MetricsSettings,
+    #[serde(default)]
+    notify: NotifySettings,
+    sort: SortSettings,
+    startup: StartupSettings,
+    shutdown: ShutdownSettings,
     sync: SyncSettings,
+    telemetry: TelemetrySettings,
+    seasonality: SeasonalitySettings,
+    snapshots: SnapshotSettings,
+    keepalive: KeepaliveSettings,
+    pinning: PinningSettings,
+    policy: PolicySettings,
+    probe: ProbeSettings,
+    #[serde(default)]
+    readiness: ReadinessSettings,
+    reconnect: ReconnectSettings,
+    registry: RegistrySettings,
+    reservations: ReservationSettings,
+    retry: RetrySettings,
+    rollout: RolloutSettings,
+    #[serde(default)]
+    version_gate: VersionGateSettings,
+    #[serde(default)]
+    geo: GeoSettings,
+    slo: SloSettings,
+    divergence: DivergenceSettings,
+    peer_health: PeerHealthSettings,
+    flaps: FlapsSettings,
+    fd_guard: FdGuardSettings,
+    cert_expiry: CertExpirySettings,
+    rate_limit: RateLimitSettings,
+    federation: FederationSettings,
+    fleet_feed: FleetFeedSettings,
+    subscribe: SubscribeSettings,
+    loadtest: LoadTestSettings,
+    stats: StatsSettings,
+    feedback: FeedbackSettings,
     ttl: TtlSettings,
+    timeouts: TimeoutSettings,
+    zone: ZoneSettings,
     http: HttpSettings,
 }
 
@@ -429,6 +618,10 @@ impl Settings {
         let _ = Settings::get();
         // validate ttl settings
         TtlSettings::ttl();
+        assert!(
+            !DiscoverySettings::order().is_empty(),
+            "[discovery] order must not be empty"
+        );
     }
 
     pub fn get() -> &'static Self {
@@ -459,14 +652,136 @@ impl Updates {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Limits {
     pub fd: u64,
+    pub panics_per_hour: u64,
 }
 
 impl Limits {
     pub fn fd() -> u64 {
         Settings::get().limits.fd
     }
+    pub fn panics_per_hour() -> u64 {
+        Settings::get().limits.panics_per_hour
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FdGuardSettings {
+    pub enable: bool,
+    pub interval_sec: u64,
+    pub high_percent: f64,
+    pub low_percent: f64,
+    pub max_shed_per_tick: usize,
+}
+
+impl FdGuardSettings {
+    pub fn enable() -> bool {
+        Settings::get().fd_guard.enable
+    }
+    pub fn interval() -> Duration {
+        Duration::from_secs(Settings::get().fd_guard.interval_sec.max(1))
+    }
+    /// Open descriptor count (of `limit`) at which connections are shed
+    pub fn high(limit: u64) -> u64 {
+        let percent = Settings::get().fd_guard.high_percent.clamp(1.0, 100.0);
+        (limit as f64 * percent / 100.0) as u64
+    }
+    /// Open descriptor count (of `limit`) shedding brings usage below
+    pub fn low(limit: u64) -> u64 {
+        let percent = Settings::get().fd_guard.low_percent.clamp(1.0, 100.0);
+        (limit as f64 * percent / 100.0) as u64
+    }
+    /// Connections shed (or restored) per sample
+    pub fn max_shed_per_tick() -> usize {
+        Settings::get().fd_guard.max_shed_per_tick.max(1)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StartupSettings {
+    pub timeout_sec: u64,
+    pub min_nodes: usize,
+    pub min_percent: f64,
+}
+
+impl StartupSettings {
+    /// Time allowed for a node connection to become available (`--strict`)
+    /// or for the startup quorum to be reached (`--strict-startup`)
+    pub fn timeout() -> Duration {
+        Duration::from_secs(Settings::get().startup.timeout_sec)
+    }
+    /// Minimum number of available nodes (`--strict-startup`)
+    pub fn min_nodes() -> usize {
+        Settings::get().startup.min_nodes
+    }
+    /// Minimum percentage of configured nodes available (`--strict-startup`)
+    pub fn min_percent() -> f64 {
+        Settings::get().startup.min_percent
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShutdownSettings {
+    pub timeout_sec: u64,
+}
+
+impl ShutdownSettings {
+    /// Time allowed for in-flight HTTP responses and for node
+    /// connections to close on shutdown (SIGTERM, Ctrl+C)
+    pub fn timeout() -> Duration {
+        Duration::from_secs(Settings::get().shutdown.timeout_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SortSettings {
+    pub burst: f64,
+    pub rate: f64,
+    pub max_delay_msec: u64,
+    pub shards: usize,
+}
+
+impl SortSettings {
+    pub fn burst() -> f64 {
+        Settings::get().sort.burst.max(1.0)
+    }
+    pub fn rate() -> f64 {
+        Settings::get().sort.rate
+    }
+    pub fn max_delay() -> Duration {
+        Duration::from_millis(Settings::get().sort.max_delay_msec)
+    }
+    pub fn shards() -> usize {
+        Settings::get().sort.shards.clamp(1, 64)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TelemetrySettings {
+    pub url: String,
+    pub interval_hrs: f64,
+    pub timeout_sec: f64,
+}
+
+impl TelemetrySettings {
+    pub fn url() -> Option<&'static str> {
+        let url = Settings::get().telemetry.url.as_str();
+        (!url.is_empty()).then_some(url)
+    }
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().telemetry.interval_hrs * 60.0 * 60.0)
+    }
+    /// Timeout of a telemetry report request
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().telemetry.timeout_sec)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -485,6 +800,932 @@ impl SyncSettings {
     }
 }
 
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct FeatureSettings(HashMap<String, bool>);
+
+impl FeatureSettings {
+    pub fn default_for(name: &str) -> Option<bool> {
+        Settings::get().features.0.get(name).copied()
+    }
+    pub fn defaults() -> Vec<(String, bool)> {
+        Settings::get()
+            .features
+            .0
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BudgetSettings {
+    pub target: f64,
+}
+
+impl BudgetSettings {
+    pub fn target() -> f64 {
+        Settings::get().budget.target.clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ArchiveSettings {
+    pub enable: bool,
+    pub offline_days: f64,
+    pub recheck_hrs: f64,
+}
+
+impl ArchiveSettings {
+    /// Offline period after which a node is archived (if enabled)
+    pub fn period() -> Option<Duration> {
+        let archive = &Settings::get().archive;
+        archive
+            .enable
+            .then(|| Duration::from_secs_f64(archive.offline_days * 24.0 * 60.0 * 60.0))
+    }
+    /// Interval of the connection attempts of archived nodes
+    pub fn recheck() -> Duration {
+        Duration::from_secs_f64(Settings::get().archive.recheck_hrs * 60.0 * 60.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BlocklistSettings {
+    pub enable: bool,
+    pub trust_forwarded: bool,
+    pub trusted_hops: usize,
+    pub requests: u64,
+    pub window_sec: u64,
+    pub strikes: u64,
+    pub ban_sec: u64,
+    pub max_ban_sec: u64,
+    pub half_life_hrs: f64,
+}
+
+impl BlocklistSettings {
+    pub fn enable() -> bool {
+        Settings::get().blocklist.enable
+    }
+    /// Take the client address from `x-forwarded-for` (behind a proxy)
+    pub fn trust_forwarded() -> bool {
+        Settings::get().blocklist.trust_forwarded
+    }
+    /// Proxies appending to `x-forwarded-for` in front of the resolver
+    pub fn trusted_hops() -> usize {
+        Settings::get().blocklist.trusted_hops.max(1)
+    }
+    /// Requests allowed per client address within [`Self::window`]
+    pub fn requests() -> u64 {
+        Settings::get().blocklist.requests
+    }
+    pub fn window() -> Duration {
+        Duration::from_secs(Settings::get().blocklist.window_sec)
+    }
+    /// Strikes (rate limit excesses, unauthorized admin requests) before a block
+    pub fn strikes() -> u64 {
+        Settings::get().blocklist.strikes
+    }
+    /// Block duration for `offenses`, doubling with each repeated offense
+    pub fn ban(offenses: u32) -> Duration {
+        let blocklist = &Settings::get().blocklist;
+        let ban = blocklist
+            .ban_sec
+            .saturating_mul(1u64 << offenses.saturating_sub(1).min(32));
+        Duration::from_secs(ban.min(blocklist.max_ban_sec))
+    }
+    /// Period after which the offense count of an address is halved
+    pub fn half_life() -> Duration {
+        Duration::from_secs_f64(Settings::get().blocklist.half_life_hrs * 60.0 * 60.0)
+    }
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReadinessSettings {
+    #[serde(default)]
+    pub networks: Vec<NetworkId>,
+}
+
+impl ReadinessSettings {
+    /// Networks required for serving readiness (`None` for all configured networks)
+    pub fn networks() -> Option<Vec<NetworkId>> {
+        let networks = &Settings::get().readiness.networks;
+        (!networks.is_empty()).then(|| networks.clone())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReservationSettings {
+    pub enable: bool,
+    pub reserve: f64,
+}
+
+impl ReservationSettings {
+    /// Fraction of node capacity reserved for API keys (0 if disabled)
+    pub fn reserve() -> f64 {
+        let reservations = &Settings::get().reservations;
+        if reservations.enable {
+            reservations.reserve.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RolloutSettings {
+    pub enable: bool,
+    pub version: String,
+    pub threshold: f64,
+    pub ramp_hrs: f64,
+}
+
+impl RolloutSettings {
+    /// Rollout target version if the rollout is enabled
+    pub fn target() -> Option<String> {
+        let rollout = &Settings::get().rollout;
+        rollout.enable.then(|| rollout.version.clone())
+    }
+    pub fn threshold() -> f64 {
+        Settings::get().rollout.threshold
+    }
+    pub fn ramp() -> Duration {
+        Duration::from_secs_f64(Settings::get().rollout.ramp_hrs * 60.0 * 60.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SeasonalitySettings {
+    pub enable: bool,
+    pub lookahead_min: f64,
+    pub alpha: f64,
+    pub weight: f64,
+}
+
+impl SeasonalitySettings {
+    pub fn enable() -> bool {
+        Settings::get().seasonality.enable
+    }
+    pub fn lookahead() -> Duration {
+        Duration::from_secs_f64(Settings::get().seasonality.lookahead_min * 60.0)
+    }
+    pub fn alpha() -> f64 {
+        Settings::get().seasonality.alpha.clamp(0.0, 1.0)
+    }
+    pub fn weight() -> f64 {
+        Settings::get().seasonality.weight
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SnapshotSettings {
+    pub interval_sec: f64,
+    pub timeout_sec: f64,
+}
+
+impl SnapshotSettings {
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().snapshots.interval_sec)
+    }
+    /// Timeout of a snapshot upload request
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().snapshots.timeout_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegistrySettings {
+    pub interval_sec: f64,
+    pub timeout_sec: f64,
+}
+
+impl RegistrySettings {
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().registry.interval_sec)
+    }
+    /// Timeout of a registry request
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().registry.timeout_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DiscoverySettings {
+    pub order: Vec<TransportKind>,
+}
+
+impl DiscoverySettings {
+    pub fn order() -> &'static [TransportKind] {
+        Settings::get().discovery.order.as_slice()
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VersionGateSettings {
+    // per-network minimum node versions
+    #[serde(default)]
+    pub networks: HashMap<String, String>,
+}
+
+impl VersionGateSettings {
+    /// Minimum node version of `network`, falling back to `default`
+    /// (`--min-node-version`)
+    pub fn min_version(network: &NetworkId, default: Option<&str>) -> Option<String> {
+        Settings::get().version_gate.network_min_version(network, default)
+    }
+
+    fn network_min_version(&self, network: &NetworkId, default: Option<&str>) -> Option<String> {
+        self.networks
+            .get(&network.to_string())
+            .map(String::as_str)
+            .or(default)
+            .map(String::from)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GeoSettings {
+    // region label -> ISO country and continent codes
+    #[serde(default)]
+    pub regions: HashMap<String, Vec<String>>,
+}
+
+impl GeoSettings {
+    /// Country and continent codes served by `region`
+    pub fn codes(region: &str) -> &'static [String] {
+        Settings::get()
+            .geo
+            .regions
+            .get(region)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SloSettings {
+    pub enable: bool,
+    pub window_min: f64,
+    pub percentile: f64,
+    pub latency_msec: u64,
+    pub min_samples: usize,
+    pub recover_ratio: f64,
+    pub recover_min: f64,
+    // per-network latency objective overrides (msec)
+    #[serde(default)]
+    pub networks: HashMap<String, u64>,
+}
+
+impl SloSettings {
+    pub fn enable() -> bool {
+        Settings::get().slo.enable
+    }
+    pub fn window() -> Duration {
+        Duration::from_secs_f64(Settings::get().slo.window_min * 60.0)
+    }
+    pub fn percentile() -> f64 {
+        Settings::get().slo.percentile.clamp(0.0, 100.0)
+    }
+    /// Latency objective of `network`, falling back to `latency-msec`
+    pub fn latency(network: &NetworkId) -> Duration {
+        let slo = &Settings::get().slo;
+        let msec = slo
+            .networks
+            .get(&network.to_string())
+            .copied()
+            .unwrap_or(slo.latency_msec);
+        Duration::from_millis(msec)
+    }
+    pub fn min_samples() -> usize {
+        Settings::get().slo.min_samples
+    }
+    pub fn recover_ratio() -> f64 {
+        Settings::get().slo.recover_ratio
+    }
+    pub fn recover() -> Duration {
+        Duration::from_secs_f64(Settings::get().slo.recover_min * 60.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RateLimitSettings {
+    pub enable: bool,
+    pub burst: f64,
+    pub rate: f64,
+}
+
+impl RateLimitSettings {
+    pub fn enable() -> bool {
+        Settings::get().rate_limit.enable
+    }
+    /// Requests a client address may issue at once
+    pub fn burst() -> f64 {
+        Settings::get().rate_limit.burst.max(1.0)
+    }
+    /// Requests per second refilled per client address
+    pub fn rate() -> f64 {
+        Settings::get().rate_limit.rate.max(0.001)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FederationSettings {
+    pub interval_sec: f64,
+    pub stale_sec: f64,
+    pub timeout_sec: f64,
+}
+
+impl FederationSettings {
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().federation.interval_sec)
+    }
+    /// Age after which a peer table is no longer used for elections
+    pub fn stale() -> Duration {
+        Duration::from_secs_f64(Settings::get().federation.stale_sec)
+    }
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().federation.timeout_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CertExpirySettings {
+    pub enable: bool,
+    pub window_days: f64,
+    pub interval_hrs: f64,
+    pub min_interval_min: f64,
+}
+
+impl CertExpirySettings {
+    pub fn enable() -> bool {
+        Settings::get().cert_expiry.enable
+    }
+    /// Remaining validity below which a certificate is reported as expiring
+    pub fn window() -> Duration {
+        Duration::from_secs_f64(Settings::get().cert_expiry.window_days * 24.0 * 60.0 * 60.0)
+    }
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().cert_expiry.interval_hrs * 60.0 * 60.0)
+    }
+    /// Shortest time between two checks of a node (on connect or interval)
+    pub fn min_interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().cert_expiry.min_interval_min * 60.0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DivergenceSettings {
+    pub enable: bool,
+    pub max_lag: u64,
+    pub recover_lag: u64,
+    pub min_nodes: usize,
+}
+
+impl DivergenceSettings {
+    pub fn enable() -> bool {
+        Settings::get().divergence.enable
+    }
+    /// DAA score lag behind the network median flagging a node
+    pub fn max_lag() -> u64 {
+        Settings::get().divergence.max_lag
+    }
+    /// DAA score lag below which a lagging node is reinstated
+    pub fn recover_lag() -> u64 {
+        let divergence = &Settings::get().divergence;
+        divergence.recover_lag.min(divergence.max_lag)
+    }
+    pub fn min_nodes() -> usize {
+        Settings::get().divergence.min_nodes.max(2)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PeerHealthSettings {
+    pub enable: bool,
+    pub demote: bool,
+    pub min_peers: u64,
+    pub isolation_ratio: f64,
+    pub recover_ratio: f64,
+    pub min_nodes: usize,
+}
+
+impl PeerHealthSettings {
+    pub fn enable() -> bool {
+        Settings::get().peer_health.enable
+    }
+    /// Exclude isolated nodes from elections
+    pub fn demote() -> bool {
+        let peer_health = &Settings::get().peer_health;
+        peer_health.enable && peer_health.demote
+    }
+    /// Peer count below which a synced node is isolated
+    pub fn min_peers() -> u64 {
+        Settings::get().peer_health.min_peers
+    }
+    /// Fraction of the network median peer count below which a node is isolated
+    pub fn isolation_ratio() -> f64 {
+        Settings::get().peer_health.isolation_ratio.clamp(0.0, 1.0)
+    }
+    /// Fraction of the network median peer count reinstating an isolated node
+    pub fn recover_ratio() -> f64 {
+        let peer_health = &Settings::get().peer_health;
+        peer_health.recover_ratio.clamp(Self::isolation_ratio(), 1.0)
+    }
+    pub fn min_nodes() -> usize {
+        Settings::get().peer_health.min_nodes.max(2)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FlapsSettings {
+    pub window_sec: u64,
+    pub min_nodes: usize,
+    pub fleet_ratio: f64,
+    pub history_hrs: u64,
+}
+
+impl FlapsSettings {
+    /// Maximum gap between down transitions of one correlated event
+    pub fn window() -> Duration {
+        Duration::from_secs(Settings::get().flaps.window_sec.max(1))
+    }
+    /// Distinct nodes of a correlated (fleet-wide) event
+    pub fn min_nodes() -> usize {
+        Settings::get().flaps.min_nodes.max(2)
+    }
+    /// Share of the fleet flapping together classified as fleet-wide
+    pub fn fleet_ratio() -> f64 {
+        Settings::get().flaps.fleet_ratio.clamp(0.0, 1.0)
+    }
+    /// Default period analyzed by `/admin/flaps`
+    pub fn history() -> Duration {
+        Duration::from_secs(Settings::get().flaps.history_hrs * 3600)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FleetFeedSettings {
+    pub enable: bool,
+    pub history: usize,
+    pub requests_per_min: u64,
+    pub max_streams: u64,
+}
+
+impl FleetFeedSettings {
+    pub fn enable() -> bool {
+        Settings::get().fleet_feed.enable
+    }
+    pub fn history() -> usize {
+        Settings::get().fleet_feed.history.max(1)
+    }
+    pub fn requests_per_min() -> u64 {
+        Settings::get().fleet_feed.requests_per_min
+    }
+    pub fn max_streams() -> u64 {
+        Settings::get().fleet_feed.max_streams
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SubscribeSettings {
+    pub check_msec: u64,
+    pub max_clients: u64,
+}
+
+impl SubscribeSettings {
+    pub fn check() -> Duration {
+        Duration::from_millis(Settings::get().subscribe.check_msec.max(100))
+    }
+    pub fn max_clients() -> u64 {
+        Settings::get().subscribe.max_clients
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct LoadTestSettings {
+    pub target: String,
+    pub concurrency: usize,
+    pub duration_sec: u64,
+    pub timeout_msec: u64,
+    pub paths: Vec<String>,
+}
+
+impl LoadTestSettings {
+    /// Target resolver url, `None` for the local `--listen` address
+    pub fn target() -> Option<String> {
+        Some(Settings::get().loadtest.target.clone()).filter(|target| !target.is_empty())
+    }
+    pub fn concurrency() -> usize {
+        Settings::get().loadtest.concurrency
+    }
+    pub fn duration() -> Duration {
+        Duration::from_secs(Settings::get().loadtest.duration_sec)
+    }
+    pub fn timeout() -> Duration {
+        Duration::from_millis(Settings::get().loadtest.timeout_msec)
+    }
+    pub fn paths() -> Vec<String> {
+        Settings::get().loadtest.paths.clone()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StatsSettings {
+    pub enable: bool,
+    pub history_days: usize,
+    pub persist_sec: f64,
+}
+
+impl StatsSettings {
+    pub fn enable() -> bool {
+        Settings::get().stats.enable
+    }
+    pub fn history_days() -> usize {
+        Settings::get().stats.history_days.max(1)
+    }
+    pub fn persist() -> Duration {
+        Duration::from_secs_f64(Settings::get().stats.persist_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct JournalSettings {
+    pub max_mb: f64,
+}
+
+impl JournalSettings {
+    /// Size of the journal file before it is rotated
+    pub fn max_bytes() -> u64 {
+        (Settings::get().journal.max_mb.max(0.1) * 1024.0 * 1024.0) as u64
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FeedbackSettings {
+    pub enable: bool,
+    pub window_sec: f64,
+    pub min_reports: usize,
+    pub min_clients: usize,
+    pub cooldown_sec: f64,
+}
+
+impl FeedbackSettings {
+    pub fn enable() -> bool {
+        Settings::get().feedback.enable
+    }
+    pub fn window() -> Duration {
+        Duration::from_secs_f64(Settings::get().feedback.window_sec)
+    }
+    pub fn min_reports() -> usize {
+        Settings::get().feedback.min_reports.max(1)
+    }
+    pub fn min_clients() -> usize {
+        Settings::get().feedback.min_clients.max(1)
+    }
+    pub fn cooldown() -> Duration {
+        Duration::from_secs_f64(Settings::get().feedback.cooldown_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ZoneSettings {
+    pub path: String,
+    pub name: String,
+    pub ttl_sec: u32,
+    pub interval_sec: f64,
+    pub max_records: usize,
+}
+
+impl ZoneSettings {
+    pub fn path() -> Option<PathBuf> {
+        let path = Settings::get().zone.path.as_str();
+        (!path.is_empty()).then(|| PathBuf::from(path))
+    }
+    pub fn name() -> &'static str {
+        Settings::get().zone.name.as_str()
+    }
+    pub fn ttl() -> u32 {
+        Settings::get().zone.ttl_sec
+    }
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().zone.interval_sec)
+    }
+    pub fn max_records() -> usize {
+        Settings::get().zone.max_records.max(1)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct KeepaliveSettings {
+    pub ping_sec: f64,
+    pub silence_sec: f64,
+    pub connect_timeout_sec: f64,
+    pub retry_interval_sec: f64,
+    pub retry_max_sec: f64,
+    pub retry_jitter: f64,
+    pub tcp_idle_sec: f64,
+    pub tcp_interval_sec: f64,
+    pub tcp_retries: u32,
+    pub tunnel_ws: bool,
+}
+
+impl KeepaliveSettings {
+    pub fn ping() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.ping_sec)
+    }
+    pub fn silence() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.silence_sec)
+    }
+    pub fn connect_timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.connect_timeout_sec)
+    }
+    pub fn retry_interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.retry_interval_sec)
+    }
+    pub fn retry_max() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.retry_max_sec)
+    }
+    pub fn retry_jitter() -> f64 {
+        Settings::get().keepalive.retry_jitter
+    }
+    /// Idle time of a node socket before TCP keepalive probes are sent
+    pub fn tcp_idle() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.tcp_idle_sec)
+    }
+    pub fn tcp_interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().keepalive.tcp_interval_sec)
+    }
+    /// Unanswered TCP keepalive probes before the socket is closed
+    pub fn tcp_retries() -> u32 {
+        Settings::get().keepalive.tcp_retries.max(1)
+    }
+    /// Route plain `ws://` node connections through a tunnel for TCP keepalive
+    pub fn tunnel_ws() -> bool {
+        Settings::get().keepalive.tunnel_ws
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DrillSettings {
+    pub enable: bool,
+    pub interval_hrs: f64,
+    pub window_sec: u64,
+    pub min_available: usize,
+}
+
+impl DrillSettings {
+    pub fn enable() -> bool {
+        Settings::get().drill.enable
+    }
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().drill.interval_hrs * 60.0 * 60.0)
+    }
+    pub fn window() -> Duration {
+        Duration::from_secs(Settings::get().drill.window_sec)
+    }
+    /// Available nodes that must remain in the group of the drained node
+    pub fn min_available() -> usize {
+        Settings::get().drill.min_available
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HintSettings {
+    pub ttl_sec: u64,
+    #[serde(default)]
+    pub switch_reason: bool,
+    #[serde(default)]
+    pub networks: HashMap<String, u64>,
+}
+
+impl HintSettings {
+    /// Advisory re-resolve interval (seconds) for clients of `network`;
+    /// a network id entry takes precedence over a network type entry
+    pub fn ttl(network: &NetworkId) -> u64 {
+        let hints = &Settings::get().hints;
+        hints
+            .networks
+            .get(&network.to_string())
+            .or_else(|| hints.networks.get(&network.network_type().to_string()))
+            .copied()
+            .unwrap_or(hints.ttl_sec)
+    }
+    /// Report why clients are moved off their previous node
+    pub fn switch_reason() -> bool {
+        Settings::get().hints.switch_reason
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MetricsSettings {
+    pub enable: bool,
+}
+
+impl MetricsSettings {
+    pub fn enable() -> bool {
+        Settings::get().metrics.enable
+    }
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct NotifySettings {
+    pub url: String,
+    pub content_type: String,
+    pub kinds: Vec<String>,
+    pub template: String,
+    pub templates: HashMap<String, String>,
+    pub timeout_sec: Option<f64>,
+}
+
+impl NotifySettings {
+    /// Webhook url, `None` if notifications are disabled
+    pub fn url() -> Option<&'static str> {
+        let url = Settings::get().notify.url.as_str();
+        (!url.is_empty()).then_some(url)
+    }
+    /// Journal kinds to notify (all if none are listed)
+    pub fn is_enabled_for(kind: &str) -> bool {
+        let kinds = &Settings::get().notify.kinds;
+        kinds.is_empty() || kinds.iter().any(|k| k == kind)
+    }
+    pub fn content_type() -> &'static str {
+        Settings::get().notify.content_type.as_str()
+    }
+    /// Timeout of a webhook request
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().notify.timeout_sec.unwrap_or(10.0))
+    }
+    /// Template values are JSON-escaped for JSON payloads
+    pub fn is_json() -> bool {
+        Self::content_type().contains("json")
+    }
+    /// Template for `kind`, falling back to the default template
+    pub fn template(kind: &str) -> &'static str {
+        let notify = &Settings::get().notify;
+        notify
+            .templates
+            .get(kind)
+            .unwrap_or(&notify.template)
+            .as_str()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PinningSettings {
+    pub enable: bool,
+}
+
+impl PinningSettings {
+    pub fn enable() -> bool {
+        Settings::get().pinning.enable
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PolicySettings {
+    pub active: Policy,
+    pub shadow: Option<Policy>,
+}
+
+impl PolicySettings {
+    pub fn active() -> Policy {
+        Settings::get().policy.active
+    }
+    pub fn shadow() -> Option<Policy> {
+        Settings::get().policy.shadow
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProbeSettings {
+    pub enable: bool,
+    pub interval_sec: f64,
+    pub timeout_sec: f64,
+    pub threads: usize,
+    pub concurrency: usize,
+}
+
+impl ProbeSettings {
+    pub fn enable() -> bool {
+        Settings::get().probe.enable
+    }
+    pub fn interval() -> Duration {
+        Duration::from_secs_f64(Settings::get().probe.interval_sec)
+    }
+    pub fn timeout() -> Duration {
+        Duration::from_secs_f64(Settings::get().probe.timeout_sec)
+    }
+    /// Worker threads of the probe runtime
+    pub fn threads() -> usize {
+        Settings::get().probe.threads.max(1)
+    }
+    /// Maximum number of probes in flight
+    pub fn concurrency() -> usize {
+        Settings::get().probe.concurrency.max(1)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReconnectSettings {
+    pub min_clients: u64,
+    pub restart_ratio: f64,
+    pub restart_grace_sec: u64,
+    pub partition_grace_sec: u64,
+}
+
+impl ReconnectSettings {
+    pub fn min_clients() -> u64 {
+        Settings::get().reconnect.min_clients
+    }
+    pub fn restart_ratio() -> f64 {
+        Settings::get().reconnect.restart_ratio
+    }
+    pub fn restart_grace() -> Duration {
+        Duration::from_secs(Settings::get().reconnect.restart_grace_sec)
+    }
+    pub fn partition_grace() -> Duration {
+        Duration::from_secs(Settings::get().reconnect.partition_grace_sec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetrySettings {
+    pub enable: bool,
+    pub attempts: u32,
+    pub delay_msec: u64,
+}
+
+impl RetrySettings {
+    pub fn enable() -> bool {
+        Settings::get().retry.enable
+    }
+    pub fn attempts() -> u32 {
+        if Self::enable() && Features::is_enabled(crate::features::TRANSIENT_RETRY) {
+            Settings::get().retry.attempts
+        } else {
+            0
+        }
+    }
+    pub fn delay() -> Duration {
+        Duration::from_millis(Settings::get().retry.delay_msec)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TimeoutSettings {
+    pub call_msec: u64,
+    pub cycle_msec: u64,
+}
+
+impl TimeoutSettings {
+    /// Bound of a poll cycle (all calls of a state update)
+    /// within the TTL window `ttl`
+    pub fn cycle(ttl: Duration) -> Duration {
+        Duration::from_millis(Settings::get().timeouts.cycle_msec.max(1)).min(ttl)
+    }
+    /// Bound of a single RPC call attempt, at most the poll cycle bound
+    pub fn call() -> Duration {
+        let timeouts = &Settings::get().timeouts;
+        Duration::from_millis(timeouts.call_msec.clamp(1, timeouts.cycle_msec.max(1)))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TtlSettings {
@@ -505,6 +1746,11 @@ impl TtlSettings {
             .map(|sec| sec * 1000.0)
             .or_else(|| ttl.period_hrs.map(|hrs| hrs * 3600.0 * 1000.0))
             .expect("TTL period not set");
+        Self::with_noise(Duration::from_secs_f64(period_msec / 1000.0))
+    }
+    /// `period` randomized by `[ttl] noise`
+    pub fn with_noise(period: Duration) -> Duration {
+        let period_msec = period.as_secs_f64() * 1000.0;
         let noise = Settings::get().ttl.noise;
         let range = (period_msec * noise) as i64;
         let mut rng = rand::thread_rng();
@@ -541,3 +1787,42 @@ impl HttpStatus {
         Duration::from_secs_f64(ttl_sec)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded() -> Settings {
+        toml::from_str::<Settings>(include_str!("../Resolver.toml")).unwrap()
+    }
+
+    #[test]
+    fn embedded_settings_load() {
+        let settings = embedded();
+        assert_eq!(settings.limits.panics_per_hour, 16);
+        assert_eq!(settings.cert_expiry.window_days, 14.0);
+        assert_eq!(settings.rate_limit.burst, 20.0);
+        assert!(settings.fd_guard.enable);
+        assert_eq!(settings.peer_health.min_nodes, 3);
+    }
+
+    #[test]
+    fn version_gate_network_overrides_default() {
+        let toml = include_str!("../Resolver.toml").replace(
+            "[version-gate.networks]\n",
+            "[version-gate.networks]\nmainnet = \"1.2.0\"\n",
+        );
+        let settings = toml::from_str::<Settings>(&toml).unwrap();
+        let mainnet = NetworkId::new(NetworkType::Mainnet);
+        let testnet = NetworkId::with_suffix(NetworkType::Testnet, 10);
+        assert_eq!(
+            settings.version_gate.network_min_version(&mainnet, Some("1.0.0")),
+            Some("1.2.0".to_string())
+        );
+        assert_eq!(
+            settings.version_gate.network_min_version(&testnet, Some("1.0.0")),
+            Some("1.0.0".to_string())
+        );
+        assert_eq!(settings.version_gate.network_min_version(&testnet, None), None);
+    }
+}
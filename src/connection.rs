@@ -1,13 +1,27 @@
 // src/connection.rs
 use crate::imports::*;
+use axum::body::Bytes;
 use std::fmt;
+use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::Instrument;
 
 // workflow_core time utilities (for elapsed time only)
 use workflow_core::time::Instant;
 
+// score offset ranking nodes violating the latency SLO after all other nodes
+const SLOW_RANK: u64 = 1 << 48;
+// score offset ranking nodes with a client failure spike pending a probe last
+const SUSPECT_RANK: u64 = 1 << 49;
+// longest delegate chain followed from a connection
+const MAX_DELEGATE_DEPTH: usize = 8;
+// `least-load` score resolution (load of 1.0)
+const LOAD_SCALE: f64 = 1_000_000.0;
+// `latency` score of nodes without latency samples, ranked after measured nodes
+const LATENCY_UNKNOWN: u64 = 1 << 40;
+
 /// Returns a ready-to-print UTC timestamp like `2025-10-30T12:34:56.789Z`
 /// using only `std::time::SystemTime` (no chrono needed)
 fn timestamp() -> String {
@@ -53,24 +67,144 @@ fn timestamp() -> String {
     )
 }
 
+/// Await `future` for at most `timeout`; on timeout the future is
+/// dropped, cancelling the pending call
+pub(crate) async fn deadline<T>(
+    future: impl std::future::Future<Output = Result<T>>,
+    timeout: Duration,
+    what: &'static str,
+) -> Result<T> {
+    let future = future.fuse();
+    let timeout = workflow_core::task::sleep(timeout).fuse();
+    futures::pin_mut!(future, timeout);
+    select! {
+        result = future => result,
+        _ = timeout => Err(Error::Timeout(what)),
+    }
+}
+
+/// Await `future` unless a shutdown is signalled first, in which case
+/// the future is dropped (cancelled) and `None` is returned
+async fn or_shutdown<T>(
+    future: impl std::future::Future<Output = T>,
+    shutdown: &Receiver<()>,
+) -> Option<T> {
+    let future = future.fuse();
+    let shutdown = shutdown.recv().fuse();
+    futures::pin_mut!(future, shutdown);
+    select! {
+        output = future => Some(output),
+        _ = shutdown => None,
+    }
+}
+
 impl fmt::Display for Connection {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let load = self
+        let state = self.state();
+        let load = state
             .load()
             .map(|load| format!("{:1.2}%", load * 100.0))
             .unwrap_or_else(|| "n/a  ".to_string());
         write!(
             f,
-            "[{:016x}:{:016x}] [{:>4}] [{:>7}] {}",
-            self.system_id(),
-            self.node.uid(),
-            self.clients(),
+            "[{}] [{:>4}] [{:>7}] {}",
+            self.node.short_id(),
+            state.clients,
             load,
             self.node.address
         )
     }
 }
 
+/// Consistent snapshot of the connection state. Published by the
+/// connection task after each state change so that readers never
+/// observe fields from different updates.
+#[derive(Debug, Default)]
+pub struct State {
+    pub version: u64,
+    pub is_connected: bool,
+    pub is_online: bool,
+    pub is_synced: bool,
+    pub clients: u64,
+    pub peers: u64,
+    pub caps: Option<Arc<Caps>>,
+}
+
+impl State {
+    #[inline]
+    pub fn sockets(&self) -> u64 {
+        self.clients + self.peers
+    }
+
+    pub fn load(&self) -> Option<f64> {
+        self.caps
+            .as_ref()
+            .map(|caps| self.clients as f64 / caps.capacity as f64)
+    }
+
+    #[inline]
+    pub fn system_id(&self) -> u64 {
+        self.caps.as_ref().map(|c| c.system_id).unwrap_or_default()
+    }
+
+    /// Node has spare capacity for new clients
+    pub fn has_capacity(&self) -> bool {
+        self.caps.as_ref().is_some_and(|caps| {
+            self.clients < caps.clients_limit && self.sockets() < caps.fd_limit
+        })
+    }
+
+    /// Node has spare capacity outside of the share reserved for API keys
+    pub fn has_unreserved_capacity(&self) -> bool {
+        self.has_capacity()
+            && self.caps.as_ref().is_some_and(|caps| {
+                let limit = caps.clients_limit as f64 * (1.0 - ReservationSettings::reserve());
+                (self.clients as f64) < limit
+            })
+    }
+
+    /// Invariants of the state flags violated by this snapshot (`--audit`)
+    pub fn violations(&self) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        if self.is_online && !self.is_connected {
+            violations.push("online while disconnected");
+        }
+        if self.is_synced && !self.is_connected {
+            violations.push("synced while disconnected");
+        }
+        if self.clients > 0 && self.caps.is_none() {
+            violations.push("clients reported without caps");
+        }
+        violations
+    }
+}
+
+/// Node state recorded on disconnect, used to classify the reconnect
+#[derive(Debug, Clone, Copy)]
+struct Outage {
+    since: u64,
+    clients: u64,
+    system_id: Option<u64>,
+}
+
+/// Classification of a disconnect-reconnect cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconnect {
+    /// The node process restarted (or the node was replaced)
+    Restart,
+    /// The node kept running while unreachable
+    Partition,
+}
+
+impl Display for Reconnect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Reconnect::Restart => write!(f, "restart"),
+            Reconnect::Partition => write!(f, "partition"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Connection {
     args: Arc<Args>,
@@ -78,14 +212,61 @@ pub struct Connection {
     is_synced: AtomicBool,
     clients: AtomicU64,
     peers: AtomicU64,
+    // `peers` was sampled since the node last became synced
+    is_peers_sampled: AtomicBool,
     node: Arc<Node>,
     monitor: Arc<Monitor>,
     params: PathParams,
     client: rpc::Client,
     shutdown_ctl: DuplexChannel<()>,
+    reset_ctl: Channel<()>,
     delegate: ArcSwap<Option<Arc<Connection>>>,
     is_connected: AtomicBool,
     is_online: AtomicBool,
+    is_draining: AtomicBool,
+    // drained by a recovery drill (independent of operator drains)
+    is_drilled: AtomicBool,
+    // disconnected by the fd guard (socket exhaustion)
+    is_shed: AtomicBool,
+    weight: AtomicU64,
+    is_pin_violation: AtomicBool,
+    // endpoint certificate expiry (UNIX seconds, 0 if not observed)
+    cert_expiry: AtomicU64,
+    is_cert_expiring: AtomicBool,
+    // last certificate check (UNIX msec, 0 if never checked)
+    cert_checked: AtomicU64,
+    retries: AtomicU64,
+    recoveries: AtomicU64,
+    // monotonic counters exported as metrics
+    connects: AtomicU64,
+    rpc_failures: AtomicU64,
+    state: ArcSwap<State>,
+    seasonality: Seasonality,
+    faults: Faults,
+    last_activity: AtomicU64,
+    // a keepalive ping is pending
+    is_pinging: AtomicBool,
+    is_probe_ok: AtomicBool,
+    probe_failures: AtomicU64,
+    custom_probe_error: ArcSwapOption<String>,
+    outage: Mutex<Option<Outage>>,
+    is_resetting: AtomicBool,
+    grace_until: AtomicU64,
+    output: ArcSwapOption<CachedOutput>,
+    latency: Latency,
+    // score snapshot of the last node list sort
+    rank: AtomicU64,
+    feedback: Reports,
+    daa_score: AtomicU64,
+    is_lagging: AtomicBool,
+    // peer count collapsed below the network health thresholds
+    is_isolated: AtomicBool,
+    // reporting a version below the minimum node version
+    is_outdated: AtomicBool,
+    // last status published to the status feed
+    last_status: Mutex<&'static str>,
+    // tracing span labeling the connection task and its RPC calls
+    span: tracing::Span,
 }
 
 impl Connection {
@@ -95,23 +276,28 @@ impl Connection {
         _sender: Sender<PathParams>,
         args: &Arc<Args>,
     ) -> Result<Self> {
-        let params = *node.params();
-
-        let client = match node.transport_kind {
-            TransportKind::WrpcBorsh => {
-                rpc::vecno::Client::try_new(WrpcEncoding::Borsh, &node.address)?
-            }
-            TransportKind::WrpcJson => {
-                rpc::vecno::Client::try_new(WrpcEncoding::SerdeJson, &node.address)?
-            }
-            TransportKind::Grpc => {
-                unimplemented!("gRPC support is not currently implemented")
-            }
-        };
+        let client = rpc::try_new(&node)?;
+        Ok(Self::with_client(monitor, node, client, args))
+    }
 
-        let client = rpc::Client::from(client);
+    /// Create a connection driven by the supplied RPC `client`
+    pub fn with_client(
+        monitor: Arc<Monitor>,
+        node: Arc<Node>,
+        client: rpc::Client,
+        args: &Arc<Args>,
+    ) -> Self {
+        let params = *node.params();
+        let weight = node.weight;
+        let span = tracing::debug_span!(
+            "connection",
+            uid = node.uid_as_str(),
+            id = node.short_id(),
+            network = %node.network,
+            transport = %node.transport_kind,
+        );
 
-        Ok(Self {
+        Self {
             args: args.clone(),
             caps: ArcSwapOption::new(None),
             monitor,
@@ -119,28 +305,268 @@ impl Connection {
             node,
             client,
             shutdown_ctl: DuplexChannel::oneshot(),
+            reset_ctl: Channel::unbounded(),
             delegate: ArcSwap::new(Arc::new(None)),
             is_connected: AtomicBool::new(false),
             is_synced: AtomicBool::new(false),
             clients: AtomicU64::new(0),
             peers: AtomicU64::new(0),
+            is_peers_sampled: AtomicBool::new(false),
             is_online: AtomicBool::new(false),
-        })
+            is_draining: AtomicBool::new(false),
+            is_drilled: AtomicBool::new(false),
+            is_shed: AtomicBool::new(false),
+            weight: AtomicU64::new(weight),
+            is_pin_violation: AtomicBool::new(false),
+            cert_expiry: AtomicU64::new(0),
+            cert_checked: AtomicU64::new(0),
+            is_cert_expiring: AtomicBool::new(false),
+            retries: AtomicU64::new(0),
+            recoveries: AtomicU64::new(0),
+            connects: AtomicU64::new(0),
+            rpc_failures: AtomicU64::new(0),
+            state: ArcSwap::new(Arc::new(State::default())),
+            seasonality: Seasonality::with_buckets(&Stats::get().seasonality(node.uid_as_str())),
+            faults: Faults::default(),
+            last_activity: AtomicU64::new(0),
+            is_pinging: AtomicBool::new(false),
+            is_probe_ok: AtomicBool::new(true),
+            probe_failures: AtomicU64::new(0),
+            custom_probe_error: ArcSwapOption::new(None),
+            outage: Mutex::new(None),
+            is_resetting: AtomicBool::new(false),
+            grace_until: AtomicU64::new(0),
+            output: ArcSwapOption::new(None),
+            latency: Latency::default(),
+            rank: AtomicU64::new(0),
+            feedback: Reports::default(),
+            daa_score: AtomicU64::new(0),
+            is_lagging: AtomicBool::new(false),
+            is_isolated: AtomicBool::new(false),
+            is_outdated: AtomicBool::new(false),
+            last_status: Mutex::new("offline"),
+            span,
+        }
     }
 
     #[inline] pub fn verbose(&self) -> bool { self.args.verbose }
-    #[inline] pub fn score(self: &Arc<Self>) -> u64 { self.delegate().sockets() }
+    /// Ranking score (lower is better) of the `--strategy`: number of
+    /// sockets (`least-sockets`, `round-robin`, `random`), client load
+    /// (`least-load`) or RPC latency (`latency`). Sockets and load
+    /// include the expected near-term rise in clients if seasonality is
+    /// enabled. The score is scaled by the inverse of the node weight;
+    /// nodes violating the latency SLO are ranked after all other nodes,
+    /// followed by nodes suspect of failing clients (see [`crate::feedback`]).
+    pub fn score(self: &Arc<Self>) -> u64 {
+        let delegate = self.delegate();
+        let state = delegate.state();
+        let mut rise = 0.0;
+        if SeasonalitySettings::enable() {
+            if let Some(predicted) = delegate.seasonality.predict(SeasonalitySettings::lookahead()) {
+                rise = (predicted - state.clients as f64).max(0.0) * SeasonalitySettings::weight();
+            }
+        }
+        let score = match self.args.strategy {
+            Strategy::LeastLoad => state
+                .caps
+                .as_ref()
+                .map(|caps| ((state.clients as f64 + rise) / caps.capacity as f64 * LOAD_SCALE) as u64)
+                // unknown capacity is ranked as fully loaded
+                .unwrap_or(LOAD_SCALE as u64),
+            Strategy::Latency => self
+                .latency()
+                .map(|latency| latency.as_micros() as u64)
+                .unwrap_or(LATENCY_UNKNOWN),
+            Strategy::LeastSockets | Strategy::RoundRobin | Strategy::Random => {
+                state.sockets() + rise as u64
+            }
+        };
+        let mut score = score.saturating_mul(100) / self.weight().max(1);
+        if self.is_slow() {
+            score = score.saturating_add(SLOW_RANK);
+        }
+        if self.is_suspect() {
+            score = score.saturating_add(SUSPECT_RANK);
+        }
+        score
+    }
+
+    /// Score snapshot taken when the node lists were last sorted,
+    /// merged across shards without re-scoring (see [`shard::merge`])
+    #[inline]
+    pub fn rank(&self) -> u64 {
+        self.rank.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot the current score as the rank of the connection
+    pub fn update_rank(self: &Arc<Self>) -> u64 {
+        let score = self.score();
+        self.rank.store(score, Ordering::Relaxed);
+        score
+    }
 
     #[inline]
     pub fn is_available(self: &Arc<Self>) -> bool {
-        let delegate = self.delegate();
-        self.is_connected()
-            && delegate.is_online()
-            && delegate.caps.load().as_ref().as_ref().is_some_and(|caps| {
-                let clients = delegate.clients();
-                let peers = delegate.peers();
-                clients < caps.clients_limit && clients + peers < caps.fd_limit
-            })
+        let delegate = self.delegate().state();
+        self.state().is_connected
+            && !self.is_draining()
+            && !self.is_pin_violation()
+            && self.is_probe_ok()
+            && self.is_custom_probe_ok()
+            && !self.is_in_grace()
+            && !self.delegate().is_lagging()
+            && !self.delegate().is_outdated()
+            && !(self.delegate().is_isolated() && PeerHealthSettings::demote())
+            && delegate.is_online
+            && delegate.has_capacity()
+    }
+
+    /// Serialized election response for this node, cached until the
+    /// delegate publishes a new state (the only mutable [`Output`] input)
+    pub fn output_json(self: &Arc<Self>) -> Bytes {
+        let state = self.delegate().state();
+        let cached = self.output.load();
+        if let Some(cached) = cached.as_ref().filter(|cached| Arc::ptr_eq(&cached.state, &state)) {
+            return cached.json.clone();
+        }
+
+        let json = Bytes::from(serde_json::to_vec(&Output::from(self)).unwrap_or_default());
+        self.output.store(Some(Arc::new(CachedOutput { state, json: json.clone() })));
+        json
+    }
+
+    /// Election filters failed by this connection (empty if the node
+    /// is a candidate for unreserved requests), see [`Self::is_available_for`]
+    pub fn exclusions(self: &Arc<Self>) -> Vec<&'static str> {
+        let delegate = self.delegate().state();
+        let mut exclusions = Vec::new();
+        if !self.is_delegate() {
+            exclusions.push("delegator");
+        }
+        if !self.state().is_connected {
+            exclusions.push("disconnected");
+        }
+        if !delegate.is_online {
+            exclusions.push(if delegate.is_synced || delegate.caps.is_none() {
+                "offline"
+            } else {
+                "unsynced"
+            });
+        }
+        if self.is_draining() {
+            exclusions.push("draining");
+        }
+        if self.is_shed() {
+            exclusions.push("shed");
+        }
+        if self.is_pin_violation() {
+            exclusions.push("pin-violation");
+        }
+        if !self.is_probe_ok() {
+            exclusions.push("probe");
+        }
+        if !self.is_custom_probe_ok() {
+            exclusions.push("custom-probe");
+        }
+        if self.is_in_grace() {
+            exclusions.push("grace");
+        }
+        if self.delegate().is_lagging() {
+            exclusions.push("lagging");
+        }
+        if self.delegate().is_outdated() {
+            exclusions.push("outdated");
+        }
+        if self.delegate().is_isolated() && PeerHealthSettings::demote() {
+            exclusions.push("isolated");
+        }
+        if !delegate.has_capacity() {
+            exclusions.push("capacity");
+        } else if !delegate.has_unreserved_capacity() {
+            exclusions.push("reserved-capacity");
+        }
+        exclusions
+    }
+
+    /// Reason clients are moved off this node, `None` if the node
+    /// is still available for `reserved` or unreserved requests
+    pub fn switch_reason(self: &Arc<Self>, reserved: bool) -> Option<&'static str> {
+        if self.is_available_for(reserved) {
+            None
+        } else if self.is_draining() {
+            Some("drained")
+        } else if self.delegate().is_outdated() {
+            Some("outdated")
+        } else if self.delegate().is_isolated() && PeerHealthSettings::demote() {
+            Some("isolated")
+        } else {
+            let delegate = self.delegate().state();
+            if self.state().is_connected && delegate.is_online && !delegate.has_unreserved_capacity() {
+                Some("overloaded")
+            } else {
+                Some("offline")
+            }
+        }
+    }
+
+    /// Available for a request, `reserved` requests (carrying an
+    /// API key) may use the capacity reserved for API keys.
+    #[inline]
+    pub fn is_available_for(self: &Arc<Self>, reserved: bool) -> bool {
+        self.is_available() && (reserved || self.delegate().state().has_unreserved_capacity())
+    }
+
+    /// Consistent snapshot of the connection state
+    #[inline]
+    pub fn state(&self) -> Arc<State> {
+        self.state.load_full()
+    }
+
+    /// Publish a new state snapshot from the current state fields
+    #[track_caller]
+    fn publish_state(&self) {
+        let version = self.state.load().version + 1;
+        self.state.store(Arc::new(State {
+            version,
+            is_connected: self.is_connected(),
+            is_online: self.is_online(),
+            is_synced: self.is_synced(),
+            clients: self.clients(),
+            peers: self.peers(),
+            caps: self.caps(),
+        }));
+
+        let status = self.status();
+        let previous = std::mem::replace(&mut *self.last_status.lock().unwrap(), status);
+        if previous != status {
+            Feed::get().publish(&self.node, previous, status);
+        }
+
+        if self.args.audit {
+            self.audit(std::panic::Location::caller());
+        }
+    }
+
+    /// Validate the invariants of the published state, logging
+    /// violations with the location of the update (`--audit`).
+    fn audit(&self, location: &std::panic::Location<'_>) {
+        let state = self.state();
+        let violations = state.violations();
+        if !violations.is_empty() {
+            let ts = timestamp();
+            log_warn!(
+                "Audit",
+                "[{ts}] {}: {} at {location} (v{} connected={} online={} synced={} clients={} caps={})",
+                self.node,
+                violations.join(", "),
+                state.version,
+                state.is_connected,
+                state.is_online,
+                state.is_synced,
+                state.clients,
+                state.caps.is_some()
+            );
+        }
     }
 
     #[inline] pub fn is_connected(&self) -> bool { self.is_connected.load(Ordering::Relaxed) }
@@ -148,33 +574,122 @@ impl Connection {
     #[inline] pub fn is_synced(&self) -> bool { self.is_synced.load(Ordering::Relaxed) }
     #[inline] pub fn clients(&self) -> u64 { self.clients.load(Ordering::Relaxed) }
     #[inline] pub fn peers(&self) -> u64 { self.peers.load(Ordering::Relaxed) }
-    #[inline] pub fn sockets(&self) -> u64 { self.clients() + self.peers() }
+    #[inline] pub fn is_peers_sampled(&self) -> bool { self.is_peers_sampled.load(Ordering::Relaxed) }
+    #[inline] pub fn retries(&self) -> u64 { self.retries.load(Ordering::Relaxed) }
+    #[inline] pub fn recoveries(&self) -> u64 { self.recoveries.load(Ordering::Relaxed) }
+    #[inline] pub fn reconnects(&self) -> u64 { self.connects.load(Ordering::Relaxed).saturating_sub(1) }
+    #[inline] pub fn rpc_failures(&self) -> u64 { self.rpc_failures.load(Ordering::Relaxed) }
+    #[inline] pub fn is_draining(&self) -> bool { self.is_draining.load(Ordering::Relaxed) || self.is_drilled.load(Ordering::Relaxed) }
+    #[inline] pub fn is_shed(&self) -> bool { self.is_shed.load(Ordering::Relaxed) }
+    #[inline] pub fn weight(&self) -> u64 { self.weight.load(Ordering::Relaxed) }
+    #[inline] pub fn is_pin_violation(&self) -> bool { self.is_pin_violation.load(Ordering::Relaxed) }
+    #[inline] pub fn cert_expiry(&self) -> Option<u64> { Some(self.cert_expiry.load(Ordering::Relaxed)).filter(|ts| *ts > 0) }
+    #[inline] pub fn is_cert_expiring(&self) -> bool { self.is_cert_expiring.load(Ordering::Relaxed) }
+    #[inline] pub fn is_probe_ok(&self) -> bool { self.is_probe_ok.load(Ordering::Relaxed) }
+    #[inline] pub fn is_slow(&self) -> bool { self.latency.is_slow() }
+    #[inline] pub fn latency(&self) -> Option<Duration> { self.latency.percentile() }
+    #[inline] pub fn is_suspect(&self) -> bool { self.feedback.is_suspect() }
+    #[inline] pub fn failure_reports(&self) -> usize { self.feedback.count() }
+    #[inline] pub fn daa_score(&self) -> u64 { self.daa_score.load(Ordering::Relaxed) }
+    #[inline] pub fn is_lagging(&self) -> bool { self.is_lagging.load(Ordering::Relaxed) }
+    #[inline] pub fn is_outdated(&self) -> bool { self.is_outdated.load(Ordering::Relaxed) }
+    #[inline] pub fn is_isolated(&self) -> bool { self.is_isolated.load(Ordering::Relaxed) }
+    #[inline] pub fn bandwidth(&self) -> Option<Bandwidth> { self.client.bandwidth() }
+    #[inline] pub fn probe_failures(&self) -> u64 { self.probe_failures.load(Ordering::Relaxed) }
+    #[inline] pub fn is_in_grace(&self) -> bool { unix_now_msec() < self.grace_until.load(Ordering::Relaxed) }
+    #[inline] pub fn is_custom_probe_ok(&self) -> bool { self.custom_probe_error.load().is_none() }
+    #[inline] pub fn custom_probe_error(&self) -> Option<String> { self.custom_probe_error.load_full().map(|err| (*err).clone()) }
+    #[inline] fn touch(&self) { self.last_activity.store(unix_now_msec(), Ordering::Relaxed) }
 
-    pub fn load(&self) -> Option<f64> {
-        self.caps.load().as_ref().map(|caps| self.clients() as f64 / caps.capacity as f64)
+    /// Connected but no RPC response received within [`KeepaliveSettings::silence`]
+    pub fn is_silent(&self) -> bool {
+        let last = self.last_activity.load(Ordering::Relaxed);
+        self.is_connected()
+            && last != 0
+            && unix_now_msec().saturating_sub(last) > KeepaliveSettings::silence().as_millis() as u64
     }
 
+    #[inline] pub fn faults(&self) -> &Faults { &self.faults }
+    #[inline] pub fn is_archived(&self) -> bool { self.monitor.archive().is_archived(self.node.uid_as_str()) }
     #[inline] pub fn caps(&self) -> Option<Arc<Caps>> { self.caps.load().clone() }
-    #[inline] pub fn system_id(&self) -> u64 {
-        self.caps.load().as_ref().map(|c| c.system_id).unwrap_or_default()
-    }
+    #[inline] pub fn system_id(&self) -> u64 { self.state().system_id() }
     #[inline] pub fn address(&self) -> &str { self.node.address.as_str() }
     #[inline] pub fn node(&self) -> &Arc<Node> { &self.node }
     #[inline] pub fn params(&self) -> PathParams { self.params }
     #[inline] pub fn network_id(&self) -> NetworkId { self.node.network }
     #[inline] pub fn is_delegate(&self) -> bool { self.delegate.load().is_none() }
 
+    /// Connection at the end of the delegate chain. A chain looping back
+    /// to this connection or longer than `MAX_DELEGATE_DEPTH` (refused
+    /// by [`Connection::bind_delegate`]) is broken up by unbinding this
+    /// connection, which becomes its own delegate.
     #[inline]
     pub fn delegate(self: &Arc<Self>) -> Arc<Connection> {
-        match (**self.delegate.load()).clone() {
-            Some(d) => d.delegate(),
-            None => self.clone(),
+        let mut current = self.clone();
+        for _ in 0..MAX_DELEGATE_DEPTH {
+            let Some(next) = (**current.delegate.load()).clone() else {
+                return current;
+            };
+            if Arc::ptr_eq(&next, self) {
+                return self.break_delegate_chain("loops back to the connection");
+            }
+            current = next;
+        }
+        if current.is_delegate() {
+            current
+        } else {
+            self.break_delegate_chain(&format!("exceeds {MAX_DELEGATE_DEPTH} connections"))
         }
     }
 
-    #[inline]
-    pub fn bind_delegate(&self, delegate: Option<Arc<Connection>>) {
+    fn break_delegate_chain(self: &Arc<Self>, reason: &str) -> Arc<Connection> {
+        let ts = timestamp();
+        log_error!("Delegate", "[{ts}] invalid delegate chain of {}: {reason}, unbinding", self.node);
+        Journal::record(JournalKind::DelegateChainInvalid, &self.node, Some(reason.to_string()));
+        self.delegate.store(Arc::new(None));
+        self.clone()
+    }
+
+    /// Bind this connection to a delegate (or `None` to become a delegate).
+    /// A delegate must serve the same network: nodes serving several
+    /// networks from one machine share the system id, and cross-network
+    /// delegation would corrupt clients/peers attribution and selection.
+    /// The delegate chain must not lead back to this connection nor be
+    /// longer than `MAX_DELEGATE_DEPTH` (e.g. after a system id collision).
+    pub fn bind_delegate(&self, delegate: Option<Arc<Connection>>) -> Result<()> {
+        if let Some(delegate) = delegate.as_ref() {
+            let mut current = delegate.clone();
+            let mut depth = 1;
+            loop {
+                if std::ptr::eq(Arc::as_ptr(&current), self) {
+                    return Err(Error::DelegateCycle(self.node.to_string(), delegate.node().to_string()));
+                }
+                let Some(next) = (**current.delegate.load()).clone() else {
+                    break;
+                };
+                depth += 1;
+                if depth > MAX_DELEGATE_DEPTH {
+                    return Err(Error::DelegateDepth(
+                        self.node.to_string(),
+                        delegate.node().to_string(),
+                        MAX_DELEGATE_DEPTH,
+                    ));
+                }
+                current = next;
+            }
+
+            let network = delegate.delegate().network_id();
+            if network != self.network_id() {
+                return Err(Error::DelegateNetwork(
+                    self.node.to_string(),
+                    self.network_id(),
+                    delegate.node().to_string(),
+                    network,
+                ));
+            }
+        }
         self.delegate.store(Arc::new(delegate));
+        Ok(())
     }
 
     pub fn resolve_delegators(self: &Arc<Self>) -> Vec<Arc<Connection>> {
@@ -188,10 +703,17 @@ impl Connection {
     }
 
     pub fn status(&self) -> &'static str {
-        if self.is_connected() {
-            if !self.is_delegate() {
+        let state = self.state();
+        if self.is_archived() {
+            "archived"
+        } else if self.is_shed() {
+            "shed"
+        } else if state.is_connected {
+            if self.is_draining() {
+                "draining"
+            } else if !self.is_delegate() {
                 "delegator"
-            } else if self.is_synced() {
+            } else if state.is_synced {
                 "online"
             } else {
                 "syncing"
@@ -207,8 +729,11 @@ impl Connection {
     }
 
     /// Hybrid reset: graceful disconnect to trigger_abort fallback
+    #[tracing::instrument(level = "debug", skip_all)]
     async fn hard_reset(&self) -> Result<()> {
         if self.is_connected.load(Ordering::Relaxed) {
+            // resolver-initiated, not classified as an outage
+            self.is_resetting.store(true, Ordering::Relaxed);
             match self.client.disconnect().await {
                 Ok(()) => {
                     let ts = timestamp();
@@ -217,37 +742,118 @@ impl Connection {
                 Err(_) => {
                     let ts = timestamp();
                     log_warn!("Reset", "[{ts}] graceful failed to hard abort");
-                    let _ = self.client.trigger_abort();
+                    let _ = self.client.trigger_abort().await;
                 }
             }
         }
         self.caps.store(None);
+        self.publish_state();
         self.client.connect().await
     }
 
+    /// Close the connection without reconnecting (fd guard)
+    async fn shed(&self) {
+        if self.is_connected() {
+            self.is_resetting.store(true, Ordering::Relaxed);
+            let disconnect = deadline(self.client.disconnect(), TimeoutSettings::call(), "disconnect");
+            if disconnect.await.is_err() {
+                let _ = self.client.trigger_abort().await;
+            }
+        }
+        self.caps.store(None);
+        self.publish_state();
+    }
+
+    /// Delay of the next connection attempt, archived nodes are
+    /// rechecked every `[archive] recheck-hrs` instead of retried
+    fn reconnect_delay(&self, backoff: &mut Backoff) -> Duration {
+        if self.is_archived() {
+            ArchiveSettings::recheck()
+        } else {
+            backoff.next()
+        }
+    }
+
+    /// Delay before the next connection attempt after `err`
+    fn retry_delay(&self, backoff: &mut Backoff, err: &Error) -> Duration {
+        let delay = self.reconnect_delay(backoff);
+        if !self.is_archived() {
+            let ts = timestamp();
+            log_warn!(
+                "Connect",
+                "[{ts}] {} - {err} (attempt {}, retrying in {:.1}s)",
+                self.node,
+                backoff.attempts(),
+                delay.as_secs_f64()
+            );
+        }
+        delay
+    }
+
     pub async fn task(self: Arc<Self>) -> Result<()> {
-        self.connect().await?;
         let rpc_ctl_channel = self.client.multiplexer().channel();
         let shutdown_ctl_receiver = self.shutdown_ctl.request.receiver.clone();
         let shutdown_ctl_sender = self.shutdown_ctl.response.sender.clone();
+        let reset_ctl_receiver = self.reset_ctl.receiver.clone();
 
-        let mut ttl = TtlSettings::ttl();
-        let mut poll = if self.is_delegate() {
-            interval(SyncSettings::poll())
+        let mut ttl = self.node.ttl();
+        let poll_period = if self.is_delegate() {
+            self.node.poll_interval()
         } else {
-            interval(SyncSettings::ping())
+            self.node.ping_interval()
         };
+        let mut poll = interval(poll_period);
+
+        let mut keepalive = interval(KeepaliveSettings::ping());
+        let mut probe = interval(ProbeSettings::interval());
+        let mut cert = interval(CertExpirySettings::interval());
+
+        // restart the poll and probe intervals after a stable per-node
+        // phase offset, spreading connections across the interval
+        // instead of polling in lockstep after a restart
+        let poll_phase = workflow_core::task::sleep(self.node.phase(poll_period)).fuse();
+        let probe_phase =
+            workflow_core::task::sleep(self.node.phase(ProbeSettings::interval())).fuse();
+        futures::pin_mut!(poll_phase, probe_phase);
 
         let mut last_connect_time: Option<Instant> = None;
 
+        // connection attempts are retried on an exponential backoff
+        // schedule, restarted on a successful connect
+        let mut backoff = Backoff::default();
+        let retry = futures::future::Fuse::terminated();
+        futures::pin_mut!(retry);
+        if let Err(err) = self.connect().await {
+            retry.set(workflow_core::task::sleep(self.retry_delay(&mut backoff, &err)).fuse());
+        }
+
         loop {
             select! {
+                _ = retry => {
+                    if !self.is_connected() && !self.is_shed() {
+                        if let Err(err) = self.connect().await {
+                            retry.set(workflow_core::task::sleep(self.retry_delay(&mut backoff, &err)).fuse());
+                        }
+                    }
+                }
+
+                _ = poll_phase => {
+                    poll = interval(poll_period);
+                }
+
+                _ = probe_phase => {
+                    probe = interval(ProbeSettings::interval());
+                }
+
                 _ = poll.next().fuse() => {
                     if TtlSettings::enable() {
                         if let Some(t) = last_connect_time {
                             if t.elapsed() > ttl {
                                 last_connect_time = None;
-                                let _ = self.hard_reset().await;
+                                Journal::record(JournalKind::TtlReset, &self.node, None);
+                                if let Err(err) = self.hard_reset().await {
+                                    retry.set(workflow_core::task::sleep(self.retry_delay(&mut backoff, &err)).fuse());
+                                }
                                 continue;
                             }
                         }
@@ -255,60 +861,173 @@ impl Connection {
 
                     if self.is_connected.load(Ordering::Relaxed) {
                         let was_online = self.is_online.load(Ordering::Relaxed);
-                        let is_online = self.update_state().await.is_ok();
+                        let result = if self.faults.is_offline() {
+                            Err(Error::custom("injected fault: offline"))
+                        } else {
+                            let cycle = self.poll_cycle(ttl);
+                            let Some(result) = or_shutdown(cycle, &shutdown_ctl_receiver).await else {
+                                break;
+                            };
+                            result
+                        };
+                        let is_online = result.is_ok();
                         self.is_online.store(is_online, Ordering::Relaxed);
+                        self.publish_state();
+                        self.evaluate_slo();
+                        self.evaluate_version();
 
                         if is_online != was_online {
                             let ts = timestamp();
                             if is_online {
-                                log_success!("Online", "[{ts}] {}", self.node.address);
-                            } else {
-                                log_error!("Offline", "[{ts}] {}", self.node.address);
+                                log_success!("Online", "[{ts}] {}", self.node);
+                                Journal::record(JournalKind::Restored, &self.node, None);
+                            } else if !self.is_archived() {
+                                log_error!("Offline", "[{ts}] {}", self.node);
+                                let detail = result.err().map(|err| err.to_string());
+                                Journal::record(JournalKind::Demoted, &self.node, detail);
                             }
                             self.update();
                         }
                     }
                 }
 
+                _ = keepalive.next().fuse() => {
+                    if self.is_connected() {
+                        // the ping is awaited in the background, silence
+                        // is judged by the responses of earlier pings
+                        self.schedule_keepalive();
+                        if self.is_silent() {
+                            let ts = timestamp();
+                            log_warn!("Keepalive", "[{ts}] dead peer detected: {}", self.node);
+                            last_connect_time = None;
+                            self.last_activity.store(0, Ordering::Relaxed);
+                            Journal::record(JournalKind::DeadPeerReset, &self.node, None);
+                            if let Err(err) = self.hard_reset().await {
+                                retry.set(workflow_core::task::sleep(self.retry_delay(&mut backoff, &err)).fuse());
+                            }
+                            continue;
+                        }
+                    }
+                }
+
+                _ = probe.next().fuse() => {
+                    if self.is_connected() && !self.is_archived() {
+                        let probes = async {
+                            if ProbeSettings::enable() {
+                                self.probe().await;
+                            }
+                            if !self.node.probes.is_empty() {
+                                self.custom_probes().await;
+                            }
+                        };
+                        if or_shutdown(probes, &shutdown_ctl_receiver).await.is_none() {
+                            break;
+                        }
+                    }
+                }
+
+                _ = cert.next().fuse() => {
+                    // also observed while disconnected: an expired
+                    // certificate fails every connection attempt
+                    if CertExpirySettings::enable() && !self.is_archived() && !self.is_shed() {
+                        self.schedule_certificate_check();
+                    }
+                }
+
+                _ = reset_ctl_receiver.recv().fuse() => {
+                    last_connect_time = None;
+                    retry.set(futures::future::Fuse::terminated());
+                    if self.is_shed() {
+                        self.shed().await;
+                    } else if self.is_archived() {
+                        // archived nodes are only rechecked occasionally
+                        self.shed().await;
+                        retry.set(workflow_core::task::sleep(self.reconnect_delay(&mut backoff)).fuse());
+                    } else if let Err(err) = self.hard_reset().await {
+                        retry.set(workflow_core::task::sleep(self.retry_delay(&mut backoff, &err)).fuse());
+                    }
+                }
+
                 msg = rpc_ctl_channel.receiver.recv().fuse() => {
                     match msg {
                         Ok(Ctl::Connect) => {
+                            backoff.reset();
+                            retry.set(futures::future::Fuse::terminated());
                             last_connect_time = Some(Instant::now());
-                            ttl = TtlSettings::ttl();
+                            self.connects.fetch_add(1, Ordering::Relaxed);
+                            ttl = self.node.ttl();
                             let ts = timestamp();
 
                             if self.args.verbose {
                                 log_info!(
                                     "Connected",
                                     "[{ts}] {} - ttl: {:.2}h",
-                                    self.node.address,
+                                    self.node,
                                     ttl.as_secs_f64() / 3600.0
                                 );
                             } else {
-                                log_success!("Connected", "[{ts}] {}", self.node.address);
+                                log_success!("Connected", "[{ts}] {}", self.node);
                             }
 
                             self.is_connected.store(true, Ordering::Relaxed);
+                            self.touch();
 
-                            if self.caps().is_some() {
-                                let _ = self.update_caps().await;
+                            if CertExpirySettings::enable() {
+                                self.schedule_certificate_check();
                             }
 
-                            if self.update_state().await.is_ok() {
-                                self.is_online.store(true, Ordering::Relaxed);
-                            } else {
-                                self.is_online.store(false, Ordering::Relaxed);
+                            let handshake = async {
+                                if PinningSettings::enable() {
+                                    self.check_pin().await;
+                                }
+
+                                if self.caps().is_some() {
+                                    let _ = self.update_caps().await;
+                                }
+
+                                self.poll_cycle(ttl).await.is_ok()
+                            };
+                            let Some(is_online) = or_shutdown(handshake, &shutdown_ctl_receiver).await else {
+                                break;
+                            };
+                            self.is_online.store(is_online, Ordering::Relaxed);
+
+                            let outage = self.outage.lock().unwrap().take();
+                            if let Some(outage) = outage {
+                                if or_shutdown(self.reconnected(outage), &shutdown_ctl_receiver).await.is_none() {
+                                    break;
+                                }
                             }
+
                             self.update();
                         }
 
                         Ok(Ctl::Disconnect) => {
-                            self.is_connected.store(false, Ordering::Relaxed);
+                            let is_reset = self.is_resetting.swap(false, Ordering::Relaxed);
+                            if !is_reset && self.is_connected() && self.is_delegate() {
+                                self.outage.lock().unwrap().replace(Outage {
+                                    since: unix_now(),
+                                    clients: self.clients(),
+                                    system_id: self.caps().map(|caps| caps.system_id()),
+                                });
+                            }
+
+                            let was_connected = self.is_connected.swap(false, Ordering::Relaxed);
                             self.is_online.store(false, Ordering::Relaxed);
+                            self.is_peers_sampled.store(false, Ordering::Relaxed);
                             last_connect_time = None;
                             self.update();
-                            let ts = timestamp();
-                            log_error!("Disconnected", "[{ts}] {}", self.node.address);
+                            if !self.is_archived() {
+                                let ts = timestamp();
+                                log_error!("Disconnected", "[{ts}] {}", self.node);
+                            }
+
+                            // the node dropped the connection: release the
+                            // client and reconnect on the backoff schedule
+                            if !is_reset && was_connected {
+                                let _ = self.client.disconnect().await;
+                                retry.set(workflow_core::task::sleep(self.reconnect_delay(&mut backoff)).fuse());
+                            }
                         }
 
                         Err(err) => {
@@ -323,88 +1042,637 @@ impl Connection {
             }
         }
 
+        // close the node socket instead of leaving it to the node to time out
+        if self.is_connected() {
+            self.is_resetting.store(true, Ordering::Relaxed);
+            let disconnect = deadline(self.client.disconnect(), TimeoutSettings::call(), "disconnect");
+            if disconnect.await.is_err() {
+                let _ = self.client.trigger_abort().await;
+            }
+        }
+
         shutdown_ctl_sender.send(()).await.unwrap();
         Ok(())
     }
 
     pub fn start(self: &Arc<Self>) -> Result<()> {
         let this = self.clone();
-        spawn(async move {
-            if let Err(e) = this.task().await {
-                let ts = timestamp();
-                log_error!("Task", "[{ts}] NodeConnection error: {:?}", e);
+        let span = self.span.clone();
+        spawn(
+            async move {
+                if let Err(e) = this.task().await {
+                    let ts = timestamp();
+                    log_error!("Task", "[{ts}] NodeConnection error: {:?}", e);
+                }
             }
-        });
+            .instrument(span),
+        );
         Ok(())
     }
 
+    /// Check the resolved node addresses against the pinned addresses
+    async fn check_pin(&self) {
+        let uid = self.node.uid_as_str();
+        let resolved = match crate::pinning::resolve(self.address()).await {
+            Ok(Some(resolved)) => resolved,
+            Ok(None) => return,
+            Err(err) => {
+                let ts = timestamp();
+                log_warn!("Pinning", "[{ts}] {}: {err}", self.node);
+                return;
+            }
+        };
+
+        let addresses = resolved.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
+        match self.monitor.pins().check(uid, resolved) {
+            Ok(PinCheck::Pinned) => {
+                let ts = timestamp();
+                log_info!("Pinning", "[{ts}] {} pinned to {addresses}", self.node);
+                self.is_pin_violation.store(false, Ordering::Relaxed);
+            }
+            Ok(PinCheck::Match) => {
+                self.is_pin_violation.store(false, Ordering::Relaxed);
+            }
+            Ok(PinCheck::Violation) => {
+                if !self.is_pin_violation.swap(true, Ordering::Relaxed) {
+                    let ts = timestamp();
+                    log_error!("Pinning", "[{ts}] {} resolves to unexpected addresses: {addresses}", self.node);
+                    Journal::record(JournalKind::PinViolation, &self.node, Some(addresses));
+                }
+            }
+            Err(err) => {
+                log_error!("Pinning", "{err}");
+            }
+        }
+        self.update();
+    }
+
+    /// Check the endpoint certificate in the background, at most once
+    /// per `[cert-expiry] min-interval-min` regardless of reconnects
+    fn schedule_certificate_check(self: &Arc<Self>) {
+        let now = unix_now_msec();
+        let last = self.cert_checked.load(Ordering::Relaxed);
+        if last > 0 && now.saturating_sub(last) < CertExpirySettings::min_interval().as_millis() as u64 {
+            return;
+        }
+        // a concurrent caller claimed this check
+        if self
+            .cert_checked
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let this = self.clone();
+        spawn(async move { this.check_certificate().await }.instrument(self.span.clone()));
+    }
+
+    /// Observe the endpoint certificate (`wss://` only) and journal
+    /// transitions in and out of the `[cert-expiry]` window
+    async fn check_certificate(&self) {
+        let address = self.address().to_string();
+        let result = ProbePool::get()
+            .run(async move {
+                let observe = certs::observe(&address).fuse();
+                let timeout = workflow_core::task::sleep(ProbeSettings::timeout()).fuse();
+                futures::pin_mut!(observe, timeout);
+                select! {
+                    result = observe => result,
+                    _ = timeout => Err(Error::custom("TLS handshake timeout")),
+                }
+            })
+            .await
+            .and_then(|result| result);
+
+        let observation = match result {
+            Ok(Some(observation)) => observation,
+            Ok(None) => return,
+            Err(err) => {
+                let ts = timestamp();
+                log_warn!("Certs", "[{ts}] {}: {err}", self.node);
+                return;
+            }
+        };
+
+        self.cert_expiry.store(observation.not_after, Ordering::Relaxed);
+        let now = unix_now();
+        let remaining = observation.not_after.saturating_sub(now);
+        let expiring = remaining < CertExpirySettings::window().as_secs();
+        let ts = timestamp();
+        if expiring {
+            let detail = if remaining == 0 {
+                format!("expired {:.1} days ago", (now - observation.not_after) as f64 / 86400.0)
+            } else {
+                format!("expires in {:.1} days", remaining as f64 / 86400.0)
+            };
+            let detail = match observation.error {
+                Some(err) => format!("{detail} ({err})"),
+                None => detail,
+            };
+            if !self.is_cert_expiring.swap(true, Ordering::Relaxed) {
+                log_warn!("Certs", "[{ts}] {} certificate {detail}", self.node);
+                Journal::record(JournalKind::CertExpiring, &self.node, Some(detail));
+            }
+        } else if self.is_cert_expiring.swap(false, Ordering::Relaxed) {
+            let detail = format!("expires in {:.1} days", remaining as f64 / 86400.0);
+            log_success!("Certs", "[{ts}] {} certificate renewed, {detail}", self.node);
+            Journal::record(JournalKind::CertRenewed, &self.node, Some(detail));
+        }
+    }
+
+    /// Clear a pin violation after the operator confirmed the new addresses
+    pub fn clear_pin_violation(&self) {
+        self.is_pin_violation.store(false, Ordering::Relaxed);
+        self.update();
+    }
+
+    /// Exclude the node from elections while keeping it connected
+    pub fn set_draining(self: &Arc<Self>, draining: bool) {
+        self.is_draining.store(draining, Ordering::Relaxed);
+        self.update();
+    }
+
+    /// Drain the node for a recovery drill, leaving operator drains intact
+    pub fn set_drilled(self: &Arc<Self>, drilled: bool) {
+        self.is_drilled.store(drilled, Ordering::Relaxed);
+        self.update();
+    }
+
+    /// Shed (disconnect) or restore the connection, `true` if changed
+    pub fn set_shed(self: &Arc<Self>, shed: bool) -> bool {
+        if self.is_shed.swap(shed, Ordering::Relaxed) == shed {
+            return false;
+        }
+        let _ = self.reset();
+        self.update();
+        true
+    }
+
+    /// Scale the node's share of elections (percent, 100 is neutral)
+    pub fn set_weight(self: &Arc<Self>, weight: u64) {
+        self.weight.store(weight, Ordering::Relaxed);
+        self.update();
+    }
+
+    /// Request a connection reset from the connection task
+    pub fn reset(&self) -> Result<()> {
+        self.reset_ctl
+            .try_send(())
+            .map_err(|err| Error::custom(format!("unable to reset {}: {err}", self.node)))
+    }
+
     pub async fn stop(self: &Arc<Self>) -> Result<()> {
         self.shutdown_ctl.signal(()).await.expect("shutdown signal failed");
         Ok(())
     }
 
+    /// Ping the node in the background, bounded by the silence
+    /// threshold, so that a half-open socket does not stall the
+    /// connection task.
+    fn schedule_keepalive(self: &Arc<Self>) {
+        // a ping still pending is not repeated
+        if self.is_pinging.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let this = self.clone();
+        spawn(
+            async move {
+                let ping = deadline(this.client.ping(), KeepaliveSettings::silence(), "ping");
+                if ping.await.is_ok() {
+                    this.touch();
+                }
+                this.is_pinging.store(false, Ordering::Relaxed);
+            }
+            .instrument(self.span.clone()),
+        );
+    }
+
+    /// External probe through the client-equivalent path, catching
+    /// nodes that keep existing connections alive but reject new ones.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn probe(&self) {
+        let delay = self.faults.probe_delay();
+        let client = self.client.clone();
+        let result = ProbePool::get()
+            .run(async move {
+                let probe = async {
+                    if let Some(delay) = delay {
+                        workflow_core::task::sleep(delay).await;
+                    }
+                    client.probe().await
+                }
+                .fuse();
+                let timeout = workflow_core::task::sleep(ProbeSettings::timeout()).fuse();
+                futures::pin_mut!(probe, timeout);
+                select! {
+                    result = probe => result,
+                    _ = timeout => Err(Error::custom("probe timeout")),
+                }
+            }.in_current_span())
+            .await
+            .and_then(|result| result);
+
+        let was_ok = self.is_probe_ok();
+        let is_ok = result.is_ok();
+        self.is_probe_ok.store(is_ok, Ordering::Relaxed);
+
+        let ts = timestamp();
+        if let Err(err) = result {
+            self.probe_failures.fetch_add(1, Ordering::Relaxed);
+            if was_ok {
+                log_error!("Probe", "[{ts}] {}: {err}", self.node);
+                Journal::record(JournalKind::ProbeFailed, &self.node, Some(err.to_string()));
+            }
+        } else if !was_ok {
+            log_success!("Probe", "[{ts}] {}", self.node);
+        }
+
+        if is_ok != was_ok {
+            self.update();
+        }
+    }
+
+    /// Flag the node as lagging behind its network (see [`crate::divergence`])
+    pub fn set_lagging(self: &Arc<Self>, lagging: bool, detail: Option<String>) {
+        if self.is_lagging.swap(lagging, Ordering::Relaxed) == lagging {
+            return;
+        }
+
+        let ts = timestamp();
+        let detail_str = detail.as_deref().unwrap_or_default();
+        if lagging {
+            log_warn!("Divergence", "[{ts}] {} lagging: {detail_str}", self.node);
+            Journal::record(JournalKind::NodeLagging, &self.node, detail);
+        } else {
+            log_success!("Divergence", "[{ts}] {} caught up: {detail_str}", self.node);
+            Journal::record(JournalKind::NodeCaughtUp, &self.node, detail);
+        }
+        self.update();
+    }
+
+    /// Flag the node as isolated from its network (see [`crate::peers`])
+    pub fn set_isolated(self: &Arc<Self>, isolated: bool, detail: Option<String>) {
+        if self.is_isolated.swap(isolated, Ordering::Relaxed) == isolated {
+            return;
+        }
+
+        let ts = timestamp();
+        let detail_str = detail.as_deref().unwrap_or_default();
+        if isolated {
+            log_warn!("Peers", "[{ts}] {} isolated: {detail_str}", self.node);
+            Journal::record(JournalKind::NodeIsolated, &self.node, detail);
+        } else {
+            log_success!("Peers", "[{ts}] {} peers recovered: {detail_str}", self.node);
+            Journal::record(JournalKind::NodePeered, &self.node, detail);
+        }
+        self.update();
+    }
+
+    /// Re-rank the node on latency SLO transitions (see [`crate::slo`])
+    fn evaluate_slo(self: &Arc<Self>) {
+        let Some(is_slow) = self.latency.evaluate(&self.network_id()) else {
+            return;
+        };
+
+        let ts = timestamp();
+        let latency = self
+            .latency()
+            .map(|latency| format!("p{} latency {}ms", SloSettings::percentile(), latency.as_millis()))
+            .unwrap_or_default();
+        if is_slow {
+            log_warn!("SLO", "[{ts}] {} slow: {latency}", self.node);
+            Journal::record(JournalKind::SloViolated, &self.node, Some(latency));
+        } else {
+            log_success!("SLO", "[{ts}] {} recovered: {latency}", self.node);
+            Journal::record(JournalKind::SloRecovered, &self.node, Some(latency));
+        }
+        self.update();
+    }
+
+    /// Exclude the node while it reports a version below the minimum
+    /// node version of its network (`--min-node-version`, `[version-gate]`)
+    fn evaluate_version(self: &Arc<Self>) {
+        let default = self.args.min_node_version.as_deref();
+        let min = VersionGateSettings::min_version(&self.network_id(), default);
+        let Some(caps) = self.caps() else {
+            return;
+        };
+        let is_outdated = min
+            .as_deref()
+            .is_some_and(|min| !is_version_at_least(&caps.version, min));
+        if self.is_outdated.swap(is_outdated, Ordering::Relaxed) == is_outdated {
+            return;
+        }
+
+        let ts = timestamp();
+        let detail = format!("version {} (minimum {})", caps.version, min.as_deref().unwrap_or("none"));
+        if is_outdated {
+            log_error!("Version", "[{ts}] {} excluded: {detail}", self.node);
+            Journal::record(JournalKind::VersionRejected, &self.node, Some(detail));
+        } else {
+            log_success!("Version", "[{ts}] {} accepted: {detail}", self.node);
+            Journal::record(JournalKind::VersionAccepted, &self.node, Some(detail));
+        }
+        self.update();
+    }
+
+    /// Record a client failure report; a failure spike for a node
+    /// still considered available demotes it pending an immediate
+    /// out-of-band probe (see [`crate::feedback`]); the client-supplied
+    /// `error` is quoted in the log, journal and notifications
+    pub fn report_failure(self: &Arc<Self>, ip: IpAddr, error: Option<&str>) {
+        if !self.is_available() || !self.feedback.record(ip) {
+            return;
+        }
+
+        let ts = timestamp();
+        let reports = format!(
+            "{} client failure reports{}",
+            FeedbackSettings::min_reports(),
+            error.map(|err| format!(", last: {err:?}")).unwrap_or_default()
+        );
+        log_warn!("Feedback", "[{ts}] {} suspect: {reports}", self.node);
+        Journal::record(JournalKind::FeedbackSpike, &self.node, Some(reports));
+        self.update();
+
+        let this = self.clone();
+        tokio::spawn(async move {
+            this.probe().await;
+            if !this.node.probes.is_empty() {
+                this.custom_probes().await;
+            }
+            this.feedback.clear();
+
+            let ts = timestamp();
+            if this.is_probe_ok() && this.is_custom_probe_ok() {
+                log_info!("Feedback", "[{ts}] {} probe passed, reinstated", this.node);
+            }
+            this.update();
+        }.instrument(self.span.clone()));
+    }
+
+    /// Operator-defined probes (see [`crate::probes`]) issued on the
+    /// monitored connection; the first failing probe excludes the node
+    /// from elections until all probes pass again.
+    #[tracing::instrument(level = "debug", skip_all)]
+    async fn custom_probes(&self) {
+        let client = self.client.clone();
+        let node = self.node.clone();
+        let error = ProbePool::get()
+            .run(async move {
+                for probe in node.probes.iter() {
+                    let call = client.call(&probe.method).fuse();
+                    let timeout = workflow_core::task::sleep(ProbeSettings::timeout()).fuse();
+                    futures::pin_mut!(call, timeout);
+                    let result = select! {
+                        result = call => result.map_err(|err| format!("{probe}: {err}")),
+                        _ = timeout => Err(format!("{probe}: timeout")),
+                    };
+                    if let Err(err) = result.and_then(|response| probe.check(&response)) {
+                        return Some(err);
+                    }
+                }
+                None
+            }.in_current_span())
+            .await
+            .unwrap_or_else(|err| Some(err.to_string()));
+
+        let was_ok = self.is_custom_probe_ok();
+        let is_ok = error.is_none();
+
+        let ts = timestamp();
+        if let Some(err) = &error {
+            if was_ok {
+                log_error!("Probe", "[{ts}] {}: {err}", self.node);
+                Journal::record(JournalKind::ProbeFailed, &self.node, Some(err.clone()));
+            }
+        } else if !was_ok {
+            log_success!("Probe", "[{ts}] {}", self.node);
+        }
+
+        self.custom_probe_error.store(error.map(Arc::new));
+
+        if is_ok != was_ok {
+            self.update();
+        }
+    }
+
+    /// Classify a disconnect-reconnect cycle as a node restart or a
+    /// network partition. The node API does not report process uptime,
+    /// a restart is detected by a changed system id or by the node
+    /// having lost the clients it served before the disconnect. Restarts
+    /// of nodes serving fewer than `[reconnect] min-clients` clients are
+    /// classified as partitions.
+    async fn reconnected(self: &Arc<Self>, outage: Outage) {
+        let caps = deadline(self.client.get_caps(), TimeoutSettings::call(), "get_caps")
+            .await
+            .ok();
+        let system_id = caps.as_ref().map(|caps| caps.system_id());
+        let is_replaced = outage.system_id.is_some() && system_id.is_some() && system_id != outage.system_id;
+        let is_drained = outage.clients >= ReconnectSettings::min_clients()
+            && (self.clients() as f64) <= outage.clients as f64 * ReconnectSettings::restart_ratio();
+
+        let (kind, grace) = if is_replaced || is_drained {
+            (Reconnect::Restart, ReconnectSettings::restart_grace())
+        } else {
+            (Reconnect::Partition, ReconnectSettings::partition_grace())
+        };
+
+        if kind == Reconnect::Restart {
+            self.retries.store(0, Ordering::Relaxed);
+            self.recoveries.store(0, Ordering::Relaxed);
+            self.probe_failures.store(0, Ordering::Relaxed);
+            if is_replaced {
+                // re-bind the delegate on the next update
+                self.caps.store(None);
+            } else if let Some(caps) = caps {
+                self.caps.store(Some(Arc::new(caps)));
+            }
+        }
+
+        self.grace_until.store(
+            unix_now_msec() + grace.as_millis() as u64,
+            Ordering::Relaxed,
+        );
+
+        let down = unix_now().saturating_sub(outage.since);
+        let detail = format!(
+            "down {down}s, clients {} -> {}, grace {}s",
+            outage.clients,
+            self.clients(),
+            grace.as_secs()
+        );
+        let ts = timestamp();
+        log_info!("Reconnect", "[{ts}] {}: {kind} ({detail})", self.node);
+        let journal_kind = match kind {
+            Reconnect::Restart => JournalKind::NodeRestart,
+            Reconnect::Partition => JournalKind::NetworkPartition,
+        };
+        Journal::record(journal_kind, &self.node, Some(detail));
+
+        // re-sort once the grace period has elapsed
+        let this = self.clone();
+        spawn(async move {
+            workflow_core::task::sleep(grace).await;
+            this.update();
+        });
+    }
+
+    /// Retry an RPC call on transient failures (RPC errors while the socket
+    /// is still connected). A disconnected socket or a node reporting an
+    /// unsynced state is a real failure and is returned immediately.
+    async fn with_retry<T, F, Fut>(&self, method: &'static str, f: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let span = tracing::debug_span!("rpc", method, attempt);
+            let start = Instant::now();
+            match deadline(f().instrument(span), TimeoutSettings::call(), method).await {
+                Ok(value) => {
+                    self.latency.record(start.elapsed());
+                    self.touch();
+                    if attempt > 0 {
+                        self.recoveries.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return Ok(value);
+                }
+                Err(err)
+                    if attempt < RetrySettings::attempts() && err.is_rpc() && self.is_connected() =>
+                {
+                    attempt += 1;
+                    self.rpc_failures.fetch_add(1, Ordering::Relaxed);
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    if self.verbose() {
+                        let ts = timestamp();
+                        log_warn!("Retry", "[{ts}] {} (attempt {attempt}): {err}", self.node);
+                    }
+                    workflow_core::task::sleep(RetrySettings::delay()).await;
+                }
+                Err(err) => {
+                    if err.is_rpc() {
+                        self.rpc_failures.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    /// RPC call through the connection client, with the retry, timeout
+    /// and latency accounting of the monitoring calls (service health checks)
+    pub async fn rpc<'a, T, F, Fut>(&'a self, method: &'static str, f: F) -> Result<T>
+    where
+        F: Fn(&'a rpc::Client) -> Fut,
+        Fut: std::future::Future<Output = Result<T>> + 'a,
+    {
+        self.with_retry(method, || f(&self.client)).await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
     async fn update_caps(self: &Arc<Self>) -> Result<()> {
         if let Some(prev) = self.caps().as_ref() {
-            let new = self.client.get_caps().await?;
+            let new = self.with_retry("get_caps", || self.client.get_caps()).await?;
             let caps = Caps::with_version(prev, new.version);
             self.caps.store(Some(Arc::new(caps)));
+            self.publish_state();
         }
         Ok(())
     }
 
+    /// State update bounded by the poll cycle timeout
+    /// within the TTL window `ttl`
+    async fn poll_cycle(self: &Arc<Self>, ttl: Duration) -> Result<()> {
+        deadline(self.update_state(), TimeoutSettings::cycle(ttl), "poll cycle").await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
     async fn update_state(self: &Arc<Self>) -> Result<()> {
         if !self.is_delegate() {
-            let _ = self.client.ping().await;
+            let _ = self.with_retry("ping", || self.client.ping()).await;
             return Ok(());
         }
 
         if self.caps().is_none() {
             let last_id = self.caps().as_ref().map(|c| c.system_id());
-            let caps = self.client.get_caps().await?;
+            let caps = self.with_retry("get_caps", || self.client.get_caps()).await?;
             let sys_id = caps.system_id();
             self.caps.store(Some(Arc::new(caps)));
 
-            if last_id != Some(sys_id) {
+            // with `--no-delegation` every connection remains its own delegate
+            if last_id != Some(sys_id) && !self.args.no_delegation {
                 let key = Delegate::new(sys_id, self.network_id());
-                let mut map = self.monitor.delegates().write().unwrap();
-                if let Some(existing) = map.get(&key) {
-                    self.bind_delegate(Some(existing.clone()));
+                if let Some(existing) = self.monitor.register_delegate(key, self) {
+                    match self.bind_delegate(Some(existing.clone())) {
+                        Ok(()) => {
+                            Journal::record(
+                                JournalKind::DelegateBound,
+                                &self.node,
+                                Some(format!("delegate: {}", existing.node())),
+                            );
+                        }
+                        Err(err) => {
+                            let ts = timestamp();
+                            log_error!("Delegate", "[{ts}] {err}");
+                            self.bind_delegate(None)?;
+                        }
+                    }
                 } else {
-                    map.insert(key, self.clone());
-                    self.bind_delegate(None);
+                    self.bind_delegate(None)?;
                 }
             }
         }
 
-        match self.client.get_sync().await {
+        match self.node.service().health().is_ready(self).await {
             Ok(sync) => {
                 let was_sync = self.is_synced.load(Ordering::Relaxed);
+                if !sync || !was_sync {
+                    // the peer count of an earlier session is stale
+                    self.is_peers_sampled.store(false, Ordering::Relaxed);
+                }
                 self.is_synced.store(sync, Ordering::Relaxed);
 
                 if sync {
-                    match self.client.get_active_connections().await {
+                    match self.with_retry("get_active_connections", || self.client.get_active_connections()).await {
                         Ok(Connections { clients, peers }) => {
                             let pc = self.clients.load(Ordering::Relaxed);
                             let pp = self.peers.load(Ordering::Relaxed);
 
+                            if self.seasonality.record(clients) {
+                                self.monitor.stats().set_seasonality(self.node.uid_as_str(), self.seasonality.buckets());
+                            }
+                            let clients = self.faults.inflate(clients);
+
                             self.clients.store(clients, Ordering::Relaxed);
                             self.peers.store(peers, Ordering::Relaxed);
+                            self.is_peers_sampled.store(true, Ordering::Relaxed);
 
                             if self.verbose() && (clients != pc || peers != pp) {
                                 let ts = timestamp();
                                 log_success!("Clients", "[{ts}] {self}");
                             }
+
+                            if DivergenceSettings::enable() {
+                                if let Ok(score) = self.with_retry("get_daa_score", || self.client.get_daa_score()).await {
+                                    self.daa_score.store(score, Ordering::Relaxed);
+                                }
+                            }
                             Ok(())
                         }
                         Err(e) => {
-                            let ts = timestamp();
-                            log_error!("RPC", "[{ts}] {self}");
-                            log_error!("Error", "[{ts}] {e}");
+                            if !self.is_archived() {
+                                let ts = timestamp();
+                                log_error!("RPC", "[{ts}] {self}");
+                                log_error!("Error", "[{ts}] {e}");
+                            }
                             Err(Error::Metrics)
                         }
                     }
                 } else {
-                    if sync != was_sync {
+                    if sync != was_sync && !self.is_archived() {
                         let ts = timestamp();
                         log_error!("Sync", "[{ts}] {self}");
                     }
@@ -412,32 +1680,125 @@ impl Connection {
                 }
             }
             Err(e) => {
-                let ts = timestamp();
-                log_error!("RPC", "[{ts}] {self}");
-                log_error!("Error", "[{ts}] {e}");
+                if !self.is_archived() {
+                    let ts = timestamp();
+                    log_error!("RPC", "[{ts}] {self}");
+                    log_error!("Error", "[{ts}] {e}");
+                }
                 Err(Error::Status)
             }
         }
     }
 
     #[inline]
+    #[track_caller]
     pub fn update(&self) {
-        self.monitor.schedule_sort(&self.params);
+        self.publish_state();
+        self.monitor.schedule_sort(self.node.uid(), &self.params);
+    }
+}
+
+#[cfg(test)]
+impl Connection {
+    /// Mark the connection connected and run a poll cycle against its
+    /// client, as the connection task would (see [`rpc::mock`])
+    pub async fn poll(self: &Arc<Self>) -> Result<()> {
+        self.is_connected.store(true, Ordering::Relaxed);
+        let result = self.update_state().await;
+        self.is_online.store(result.is_ok(), Ordering::Relaxed);
+        self.update();
+        result
     }
 }
 
+/// Pre-serialized [`Output`] and the delegate state it was rendered from
+#[derive(Debug)]
+struct CachedOutput {
+    state: Arc<State>,
+    json: Bytes,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Output<'a> {
     pub uid: &'a str,
+    pub id: &'a str,
     pub url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<&'a str>,
+    /// Advisory interval (seconds) after which clients should re-resolve
+    pub ttl: u64,
+    /// Why the client was moved off its previous node (`?previous=<uid>`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub switch_reason: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caps: Option<CapsDigest>,
 }
 
 impl<'a> From<&'a Arc<Connection>> for Output<'a> {
     fn from(conn: &'a Arc<Connection>) -> Self {
         Self {
             uid: conn.node.uid_as_str(),
+            id: conn.node.short_id(),
             url: conn.node.address(),
+            region: conn.node.region.as_deref(),
+            ttl: HintSettings::ttl(&conn.node.network),
+            switch_reason: None,
+            caps: CapsDigest::try_from(conn).ok(),
         }
     }
+}
+
+/// Node capabilities validated by the resolver, allowing clients
+/// to skip their own capability handshake. The `digest` is a hash
+/// of all other fields and can be used to detect capability changes.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CapsDigest {
+    pub protocol: ProtocolKind,
+    pub encoding: EncodingKind,
+    pub network: NetworkId,
+    pub version: String,
+    pub features: Vec<&'static str>,
+    #[serde(with = "SerHex::<Strict>")]
+    pub digest: u64,
+}
+
+impl TryFrom<&Arc<Connection>> for CapsDigest {
+    type Error = Error;
+
+    fn try_from(conn: &Arc<Connection>) -> Result<Self> {
+        let state = conn.delegate().state();
+        let caps = state.caps.as_ref().ok_or(Error::Status)?;
+
+        let params = conn.params();
+        let mut features = Vec::new();
+        if params.tls() == TlsKind::Tls {
+            features.push("tls");
+        }
+        if state.is_synced {
+            features.push("synced");
+        }
+
+        let protocol = params.protocol();
+        let encoding = params.encoding();
+        let network = conn.network_id();
+        let version = caps.version.clone();
+        let digest = xxh3_64(
+            format!(
+                "{protocol}:{encoding}:{network}:{version}:{}",
+                features.join(",")
+            )
+            .as_bytes(),
+        );
+
+        Ok(Self {
+            protocol,
+            encoding,
+            network,
+            version,
+            features,
+            digest,
+        })
+    }
 }
\ No newline at end of file
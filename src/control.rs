@@ -0,0 +1,163 @@
+use crate::imports::*;
+use crate::status::Status;
+use std::fs;
+
+/// Control socket command (one JSON object per line)
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+enum Command {
+    /// List nodes and their status
+    List,
+    /// Exclude a node from elections (`"enable": false` to resume)
+    Drain {
+        uid: String,
+        #[serde(default = "enable_default")]
+        enable: bool,
+    },
+    /// Reset the node connection
+    Reset { uid: String },
+    /// Validate and atomically apply a batch of operations
+    Batch { operations: Vec<Operation> },
+    /// Reload the node configuration
+    Reload,
+}
+
+fn enable_default() -> bool {
+    true
+}
+
+/// Local control socket accepting newline-delimited JSON commands
+/// (`list`, `drain`, `reset`, `batch`, `reload`), for use by scripts on the
+/// resolver host. Access is governed by the socket file permissions.
+pub fn start(resolver: &Arc<Resolver>, path: PathBuf) -> Result<()> {
+    listen(resolver, path)
+}
+
+#[cfg(unix)]
+fn listen(resolver: &Arc<Resolver>, path: PathBuf) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::net::UnixListener;
+
+    if path.exists() {
+        // remove a stale socket from a previous run
+        fs::remove_file(&path).map_err(|err| Error::file(&path, err))?;
+    }
+
+    // the socket is created with the process umask, so it is bound inside
+    // an owner-only directory and only moved into place once restricted
+    let staging = path.with_extension("staging");
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|err| Error::file(&staging, err))?;
+    }
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|err| Error::file(&staging, err))?;
+    let staged = staging.join("control.sock");
+    let bound = UnixListener::bind(&staged)
+        .map_err(|err| Error::Bind(path.display().to_string(), err))
+        .and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))
+                .and_then(|_| fs::rename(&staged, &path))
+                .map_err(|err| Error::file(&path, err))?;
+            Ok(listener)
+        });
+    fs::remove_dir_all(&staging).map_err(|err| Error::file(&staging, err))?;
+    let listener = bound?;
+    log_success!("Control", "Listening on `{}`", path.display());
+
+    let resolver = resolver.clone();
+    spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let resolver = resolver.clone();
+                    spawn(async move {
+                        if let Err(err) = session(&resolver, stream).await {
+                            log_error!("Control", "{err}");
+                        }
+                    });
+                }
+                Err(err) => {
+                    log_error!("Control", "{err}");
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen(_resolver: &Arc<Resolver>, _path: PathBuf) -> Result<()> {
+    Err(Error::custom("control socket is only supported on unix platforms"))
+}
+
+#[cfg(unix)]
+async fn session(resolver: &Arc<Resolver>, stream: tokio::net::UnixStream) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Command>(&line) {
+            Ok(command) => match execute(resolver, command).await {
+                Ok(result) => serde_json::json!({ "ok" : true, "result" : result }),
+                Err(err) => serde_json::json!({ "ok" : false, "error" : err.to_string() }),
+            },
+            Err(err) => serde_json::json!({ "ok" : false, "error" : err.to_string() }),
+        };
+
+        writer.write_all(format!("{response}\n").as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+async fn execute(resolver: &Arc<Resolver>, command: Command) -> Result<serde_json::Value> {
+    match command {
+        Command::List => {
+            let connections = resolver.connections();
+            let status = connections.iter().map(Status::from).collect::<Vec<_>>();
+            Ok(serde_json::to_value(status)?)
+        }
+        Command::Drain { uid, enable } => {
+            let connection = find(resolver, &uid)?;
+            connection.set_draining(enable);
+            if enable {
+                log_warn!("Control", "Draining {}", connection.node());
+            } else {
+                log_success!("Control", "Resuming {}", connection.node());
+            }
+            Ok(serde_json::Value::Null)
+        }
+        Command::Reset { uid } => {
+            let connection = find(resolver, &uid)?;
+            log_warn!("Control", "Resetting {}", connection.node());
+            connection.reset()?;
+            Ok(serde_json::Value::Null)
+        }
+        Command::Batch { operations } => {
+            let batch = Batch { operations };
+            resolver.apply_batch(&batch)?;
+            log_warn!("Control", "Batch applied: {batch}");
+            Journal::record(JournalKind::BatchApplied, &batch, None);
+            Ok(serde_json::Value::Null)
+        }
+        Command::Reload => {
+            resolver.reload().await?;
+            Ok(serde_json::Value::Null)
+        }
+    }
+}
+
+fn find(resolver: &Arc<Resolver>, uid: &str) -> Result<Arc<Connection>> {
+    resolver
+        .find(uid)
+        .ok_or_else(|| Error::custom(format!("node `{uid}` not found")))
+}
@@ -1,5 +1,7 @@
 use crate::imports::*;
 
+/// Delegate map key: connections are delegated by system id
+/// within the same network only.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
 pub struct Delegate {
     #[serde(with = "SerHex::<Strict>")]
@@ -0,0 +1,130 @@
+//!
+//! Transport discovery of nodes configured without `transport-type`.
+//!
+//! Such nodes are probed with each transport of `[discovery] order`
+//! (borsh, then JSON by default) when the node list is updated; the
+//! first transport answering the probe is used and persisted in
+//! `~/.vecno-resolver/discovery.json`, so subsequent restarts connect
+//! using the discovered transport without probing.
+//!
+
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+pub struct Discovery {
+    // node key -> discovered transport
+    transports: RwLock<BTreeMap<String, TransportKind>>,
+    path: PathBuf,
+}
+
+impl Discovery {
+    pub fn get() -> &'static Self {
+        static DISCOVERY: OnceLock<Discovery> = OnceLock::new();
+        DISCOVERY.get_or_init(Discovery::load)
+    }
+
+    fn load() -> Self {
+        let path = global_config_folder().join("discovery.json");
+        let transports = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str(&json)?))
+            {
+                Ok(transports) => transports,
+                Err(err) => {
+                    log_error!("Discovery", "Unable to load `{}`: {err}", path.display());
+                    BTreeMap::default()
+                }
+            }
+        } else {
+            BTreeMap::default()
+        };
+
+        Self {
+            transports: RwLock::new(transports),
+            path,
+        }
+    }
+
+    /// Key identifying the endpoint of a node config independently of the transport
+    pub fn key(config: &NodeConfig) -> String {
+        match config.address.as_ref() {
+            Some(address) => address.clone(),
+            None => format!(
+                "{}://{}/{}/{}",
+                if config.tls { "wss" } else { "ws" },
+                config.fqdn,
+                config.service,
+                config.network
+            ),
+        }
+    }
+
+    /// Previously discovered transport of a node config
+    pub fn lookup(&self, config: &NodeConfig) -> Option<TransportKind> {
+        self.transports
+            .read()
+            .unwrap()
+            .get(&Self::key(config))
+            .copied()
+    }
+
+    fn store(&self, config: &NodeConfig, kind: TransportKind) {
+        let mut transports = self.transports.write().unwrap();
+        transports.insert(Self::key(config), kind);
+        if let Err(err) = serde_json::to_string_pretty(&*transports)
+            .map_err(Error::from)
+            .and_then(|json| std::fs::write(&self.path, json).map_err(|err| Error::file(&self.path, err)))
+        {
+            log_error!("Discovery", "{err}");
+        }
+    }
+
+    /// Probe the transports of `config` in `[discovery] order`,
+    /// returning the node of the first transport answering the probe
+    async fn discover(&self, config: &NodeConfig) -> Option<Arc<Node>> {
+        for kind in DiscoverySettings::order() {
+            let node = Arc::new(Node::from(NodeConfig {
+                transport_kind: Some(*kind),
+                ..config.clone()
+            }));
+            let Ok(client) = rpc::try_new(&node) else {
+                continue;
+            };
+            let result = ProbePool::get()
+                .run(async move { client.probe().await })
+                .await
+                .and_then(|result| result);
+            match result {
+                Ok(()) => {
+                    log_success!("Discovery", "{node} uses `{kind}`");
+                    self.store(config, *kind);
+                    return Some(node);
+                }
+                Err(err) => {
+                    log_info!("Discovery", "{node} `{kind}` probe failed: {err}");
+                }
+            }
+        }
+        None
+    }
+
+    /// Resolve the transport of nodes pending discovery; nodes none
+    /// of the transports answered for retain the default transport
+    /// and are probed again on the next node list update.
+    pub async fn resolve(&self, nodes: Vec<Arc<Node>>) -> Vec<Arc<Node>> {
+        futures::future::join_all(nodes.into_iter().map(|node| async move {
+            let Some(config) = node.discovery.as_ref() else {
+                return node;
+            };
+            match self.discover(config).await {
+                Some(discovered) => discovered,
+                None => {
+                    log_warn!("Discovery", "Unable to discover the transport of {node}");
+                    node
+                }
+            }
+        }))
+        .await
+    }
+}
@@ -0,0 +1,122 @@
+//!
+//! DAA score divergence: the virtual DAA score reported by each synced
+//! node is compared to the median score of the synced nodes of its
+//! network. A node reporting `synced` while lagging more than
+//! `[divergence] max-lag` behind the median is flagged `lagging` and
+//! excluded from elections until it catches up (within `recover-lag`).
+//! The median (rather than the highest score) keeps a single node
+//! reporting an inflated score from flagging the rest of the network.
+//! Networks with fewer than `min-nodes` synced nodes are not evaluated
+//! and the flags of their nodes are cleared.
+//!
+
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Evaluate the DAA scores of the delegate `connections`
+pub fn update(connections: &[Arc<Connection>]) {
+    if !DivergenceSettings::enable() {
+        for connection in connections {
+            connection.set_lagging(false, None);
+        }
+        return;
+    }
+
+    let mut networks = BTreeMap::<String, Vec<&Arc<Connection>>>::new();
+    for connection in connections.iter().filter(|connection| {
+        let state = connection.state();
+        connection.is_delegate()
+            && state.is_connected
+            && state.is_synced
+            && connection.daa_score() > 0
+    }) {
+        networks
+            .entry(connection.network_id().to_string())
+            .or_default()
+            .push(connection);
+    }
+
+    for connections in networks.values() {
+        // the median is only meaningful with enough synced nodes,
+        // flags raised while the network was larger no longer apply
+        if connections.len() < DivergenceSettings::min_nodes() {
+            for connection in connections {
+                connection.set_lagging(false, Some("too few synced nodes to compare".to_string()));
+            }
+            continue;
+        }
+
+        let mut scores = connections
+            .iter()
+            .map(|connection| connection.daa_score())
+            .collect::<Vec<_>>();
+        scores.sort_unstable();
+        let median = scores[scores.len() / 2];
+
+        for connection in connections {
+            let lag = median.saturating_sub(connection.daa_score());
+            if lag > DivergenceSettings::max_lag() {
+                connection.set_lagging(
+                    true,
+                    Some(format!("{lag} DAA behind the network median {median}")),
+                );
+            } else if lag <= DivergenceSettings::recover_lag() {
+                connection.set_lagging(
+                    false,
+                    Some(format!("{lag} DAA behind the network median {median}")),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock;
+
+    #[tokio::test]
+    async fn flags_nodes_behind_the_network_median() {
+        let lag = DivergenceSettings::max_lag() + 1;
+        let connections = mock::polled(
+            "divergence",
+            &[100_000, 100_000, 100_000 - lag],
+            |client, score| client.set_daa_score(*score),
+        )
+        .await;
+        update(&connections);
+        assert!(!connections[0].delegate().is_lagging());
+        assert!(!connections[1].delegate().is_lagging());
+        assert!(connections[2].delegate().is_lagging());
+    }
+
+    #[tokio::test]
+    async fn tolerates_lag_up_to_the_threshold() {
+        let lag = DivergenceSettings::max_lag();
+        let connections = mock::polled(
+            "divergence",
+            &[100_000, 100_000, 100_000 - lag],
+            |client, score| client.set_daa_score(*score),
+        )
+        .await;
+        update(&connections);
+        assert!(connections.iter().all(|connection| !connection.delegate().is_lagging()));
+    }
+
+    #[tokio::test]
+    async fn clears_flags_below_the_minimum_network_size() {
+        let lag = DivergenceSettings::max_lag() + 1;
+        let connections = mock::polled(
+            "divergence",
+            &[100_000, 100_000, 100_000 - lag],
+            |client, score| client.set_daa_score(*score),
+        )
+        .await;
+        update(&connections);
+        assert!(connections[2].delegate().is_lagging());
+
+        let remaining = [connections[0].clone(), connections[2].clone()];
+        update(&remaining);
+        assert!(!connections[2].delegate().is_lagging());
+    }
+}
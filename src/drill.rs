@@ -0,0 +1,165 @@
+//!
+//! Recovery drills: a randomly selected non-critical node is drained
+//! for a short window to verify that failover works, i.e. the drained
+//! node stops receiving new clients while the remaining nodes of its
+//! group keep serving (and absorb the new clients).
+//!
+
+use crate::imports::*;
+use std::collections::VecDeque;
+
+const REPORTS: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DrillReport {
+    pub ts: u64,
+    pub uid: String,
+    pub id: String,
+    pub url: String,
+    pub network: NetworkId,
+    pub duration_sec: u64,
+    /// clients of the drained node before and after the window
+    pub clients_before: u64,
+    pub clients_after: u64,
+    /// clients of the remaining nodes before and after the window
+    pub others_before: u64,
+    pub others_after: u64,
+    /// remaining available nodes at the end of the window
+    pub available: usize,
+    pub passed: bool,
+}
+
+impl Display for DrillReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} clients {} -> {}, others {} -> {}, {} available",
+            if self.passed { "passed" } else { "failed" },
+            self.clients_before,
+            self.clients_after,
+            self.others_before,
+            self.others_after,
+            self.available
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct Drills {
+    is_running: AtomicBool,
+    reports: Mutex<VecDeque<DrillReport>>,
+}
+
+impl Drills {
+    /// Most recent drill reports, newest last
+    pub fn reports(&self) -> Vec<DrillReport> {
+        self.reports.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Drain a randomly selected non-critical node for the drill
+    /// window and report whether its clients moved to other nodes.
+    pub async fn run(&self, monitor: &Arc<Monitor>) {
+        if self.is_running.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        if let Some(report) = drill(monitor).await {
+            let ts = timestamp();
+            let subject = format!("[{}] {}", report.id, report.url);
+            if report.passed {
+                log_success!("Drill", "[{ts}] {subject}: {report}");
+            } else {
+                log_error!("Drill", "[{ts}] {subject}: {report}");
+            }
+            Journal::record(JournalKind::Drill, subject, Some(report.to_string()));
+            let mut reports = self.reports.lock().unwrap();
+            if reports.len() == REPORTS {
+                reports.pop_front();
+            }
+            reports.push_back(report);
+        }
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+}
+
+fn clients(connections: &[Arc<Connection>]) -> u64 {
+    connections
+        .iter()
+        .map(|connection| connection.state().clients)
+        .sum()
+}
+
+async fn drill(monitor: &Arc<Monitor>) -> Option<DrillReport> {
+    // nodes whose group keeps at least `min-available` other
+    // available nodes while the node is drained
+    let groups = monitor
+        .connections()
+        .into_iter()
+        .filter(|(params, _)| params.is_tls_strict())
+        .map(|(_, connections)| {
+            connections
+                .into_iter()
+                .filter(|connection| connection.is_delegate() && connection.is_available())
+                .collect::<Vec<_>>()
+        })
+        .filter(|connections| connections.len() > DrillSettings::min_available())
+        .collect::<Vec<_>>();
+
+    let candidates = groups
+        .iter()
+        .flat_map(|group| {
+            group
+                .iter()
+                .filter(|connection| connection.state().clients > 0)
+                .map(move |connection| (connection, group))
+        })
+        .collect::<Vec<_>>();
+
+    if candidates.is_empty() {
+        log_warn!("Drill", "No eligible node");
+        return None;
+    }
+
+    let (target, group) = candidates[rand::thread_rng().gen_range(0..candidates.len())];
+    let others = group
+        .iter()
+        .filter(|connection| !Arc::ptr_eq(connection, target))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let clients_before = target.state().clients;
+    let others_before = clients(&others);
+    let window = DrillSettings::window();
+
+    let ts = timestamp();
+    log_warn!("Drill", "[{ts}] draining {} for {}s", target.node(), window.as_secs());
+    target.set_drilled(true);
+    workflow_core::task::sleep(window).await;
+    target.set_drilled(false);
+
+    let clients_after = target.state().clients;
+    let others_after = clients(&others);
+    let available = others
+        .iter()
+        .filter(|connection| connection.is_available())
+        .count();
+
+    // the drained node must not gain clients while the others keep serving
+    let passed = available > 0 && clients_after <= clients_before;
+
+    let node = target.node();
+    Some(DrillReport {
+        ts: unix_now(),
+        uid: node.uid_as_str().to_string(),
+        id: node.short_id().to_string(),
+        url: node.address().to_string(),
+        network: node.network,
+        duration_sec: window.as_secs(),
+        clients_before,
+        clients_after,
+        others_before,
+        others_after,
+        available,
+        passed,
+    })
+}
@@ -0,0 +1,213 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Hypothetical node list change: a [`Change`] with the client
+/// capacity assumed for added nodes
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Hypothetical {
+    #[serde(flatten)]
+    pub change: Change,
+    /// Client capacity of an added node (required for `add`)
+    #[serde(default)]
+    pub capacity: Option<u64>,
+}
+
+/// Node entry of a dry run fleet
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Entry {
+    pub uid: String,
+    pub id: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub added: bool,
+    pub available: bool,
+    pub clients: u64,
+    pub capacity: u64,
+    /// Expected share of elections under the active policy (percent)
+    pub share: f64,
+    #[serde(skip)]
+    score: u64,
+}
+
+/// Selection and capacity of the nodes of a path
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Fleet {
+    pub nodes: usize,
+    pub available: usize,
+    pub clients: u64,
+    pub capacity: u64,
+    /// Free client capacity of the available nodes
+    pub headroom: u64,
+    /// Available nodes in ranking order
+    pub selection: Vec<Entry>,
+}
+
+impl Fleet {
+    fn new(mut entries: Vec<Entry>) -> Self {
+        entries.sort_by_key(|entry| entry.score);
+        let mut selection = entries
+            .iter()
+            .filter(|entry| entry.available)
+            .cloned()
+            .collect::<Vec<_>>();
+        for (entry, share) in selection
+            .iter_mut()
+            .zip(shares(PolicySettings::active(), selection.len()))
+        {
+            entry.share = share;
+        }
+
+        Self {
+            nodes: entries.len(),
+            available: selection.len(),
+            clients: entries.iter().map(|entry| entry.clients).sum(),
+            capacity: entries.iter().map(|entry| entry.capacity).sum(),
+            headroom: selection
+                .iter()
+                .map(|entry| entry.capacity.saturating_sub(entry.clients))
+                .sum(),
+            selection,
+        }
+    }
+}
+
+/// Current and hypothetical fleet of a path
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DryRunReport {
+    pub before: Fleet,
+    pub after: Fleet,
+}
+
+/// Expected election share (percent) of each position
+/// in a list of `len` available nodes sorted by score
+fn shares(policy: Policy, len: usize) -> Vec<f64> {
+    match policy {
+        // position `i` is weighted `len - i` (see `Policy::select_index`)
+        Policy::Weighted => {
+            let total = (len * (len + 1) / 2).max(1) as f64;
+            (0..len).map(|i| (len - i) as f64 / total * 100.0).collect()
+        }
+        Policy::LeastLoaded => (0..len).map(|i| if i == 0 { 100.0 } else { 0.0 }).collect(),
+        Policy::Random => vec![100.0 / len.max(1) as f64; len],
+    }
+}
+
+/// Path key of `node` (`service:tls:protocol:encoding:network`)
+fn path(node: &Node) -> String {
+    format!("{}:{}", node.service(), node.params())
+}
+
+/// Evaluate how node selection and capacity headroom would change
+/// with `changes` applied to the current fleet, per affected path.
+/// Added nodes are assumed available without clients, nodes are
+/// ranked by their current score (the election path is not altered).
+/// `connections` are the node connections of all services.
+pub fn run(
+    connections: &[Arc<Connection>],
+    changes: &[Hypothetical],
+) -> Result<BTreeMap<String, DryRunReport>> {
+    let connections = connections
+        .iter()
+        .filter(|connection| connection.params().is_tls_strict() && connection.is_delegate())
+        .collect::<Vec<_>>();
+
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut weights = HashMap::new();
+    for hypothetical in changes {
+        match &hypothetical.change {
+            Change::Remove { uid } => {
+                let connection = connections
+                    .iter()
+                    .find(|connection| connection.node().is_id(uid))
+                    .ok_or_else(|| Error::custom(format!("Unknown node `{uid}`")))?;
+                removed.push(connection.node().uid());
+            }
+            Change::Weight { uid, weight } => {
+                let connection = connections
+                    .iter()
+                    .find(|connection| connection.node().is_id(uid))
+                    .ok_or_else(|| Error::custom(format!("Unknown node `{uid}`")))?;
+                if !WEIGHT_RANGE.contains(weight) {
+                    return Err(Error::custom(format!("weight {weight} of `{uid}` is out of range")));
+                }
+                weights.insert(connection.node().uid(), *weight);
+            }
+            Change::Add { node } => {
+                if node.address.is_none() && node.fqdn.is_empty() {
+                    return Err(Error::custom("node requires `fqdn` or `address`"));
+                }
+                node.validate()?;
+                let capacity = hypothetical.capacity.ok_or_else(|| {
+                    Error::custom(format!("`capacity` is required to add {}", node.fqdn))
+                })?;
+                let enable = node.enable.unwrap_or(true);
+                added.push((Node::from(node.clone()), enable, capacity));
+            }
+        }
+    }
+
+    // fleets (before, after) of the paths affected by the changes
+    let mut fleets = BTreeMap::<String, (Vec<Entry>, Vec<Entry>)>::new();
+    let mut affected = HashSet::new();
+    for connection in connections {
+        let state = connection.state();
+        let entry = Entry {
+            uid: connection.node().uid_as_str().to_string(),
+            id: connection.node().short_id().to_string(),
+            added: false,
+            available: connection.is_available_for(false),
+            clients: state.clients,
+            capacity: state
+                .caps
+                .as_ref()
+                .map(|caps| caps.clients_limit)
+                .unwrap_or_default(),
+            share: 0.0,
+            score: connection.score(),
+        };
+        let params = path(connection.node());
+        let (before, after) = fleets.entry(params.clone()).or_default();
+        if removed.contains(&connection.node().uid()) {
+            affected.insert(params);
+        } else if let Some(weight) = weights.get(&connection.node().uid()) {
+            // the score is scaled by the inverse of the node weight
+            let score = entry.score.saturating_mul(connection.weight()) / weight;
+            after.push(Entry { score, ..entry.clone() });
+            affected.insert(params);
+        } else {
+            after.push(entry.clone());
+        }
+        before.push(entry);
+    }
+    for (node, enable, capacity) in added {
+        let params = path(&node);
+        let (_, after) = fleets.entry(params.clone()).or_default();
+        after.push(Entry {
+            uid: node.uid_as_str().to_string(),
+            id: node.short_id().to_string(),
+            added: true,
+            available: enable,
+            clients: 0,
+            capacity,
+            share: 0.0,
+            score: 0,
+        });
+        affected.insert(params);
+    }
+
+    Ok(fleets
+        .into_iter()
+        .filter(|(params, _)| affected.contains(params))
+        .map(|(params, (before, after))| {
+            let report = DryRunReport {
+                before: Fleet::new(before),
+                after: Fleet::new(after),
+            };
+            (params, report)
+        })
+        .collect())
+}
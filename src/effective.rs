@@ -0,0 +1,164 @@
+//!
+//! Effective configuration: command line flags (with their source),
+//! environment, node configuration source and the `Resolver.toml`
+//! settings, logged at startup and served at `/admin/config`.
+//! Secrets (webhook urls, credentials, keys) are redacted.
+//!
+
+use crate::imports::*;
+use serde_json::{json, Map, Value};
+
+const REDACTED: &str = "<redacted>";
+
+// settings holding secrets (`section.field`)
+const SECRET_SETTINGS: &[&str] = &["notify.url"];
+
+// environment variables read by the resolver, (name, is secret)
+const ENV: &[(&str, bool)] = &[
+    ("RESOLVER_S3_ACCESS_KEY", true),
+    ("RESOLVER_S3_SECRET_KEY", true),
+    ("RESOLVER_S3_REGION", false),
+    ("RESOLVER_SNAPSHOT_KEY", true),
+    ("RESOLVER_FEDERATION_KEY", true),
+];
+
+/// Url without credentials, path and query
+fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => format!(
+            "{}://{}{}",
+            url.scheme(),
+            url.host_str().unwrap_or_default(),
+            url.port()
+                .map(|port| format!(":{port}"))
+                .unwrap_or_default()
+        ),
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+fn flags(args: &Args) -> Value {
+    let flag = |id: &str, value: Value| {
+        let source = if args.explicit.iter().any(|explicit| explicit == id) {
+            "command-line"
+        } else {
+            "default"
+        };
+        (
+            id.to_string(),
+            json!({ "value" : value, "source" : source }),
+        )
+    };
+
+    let rate_limit = args
+        .rate_limit
+        .as_ref()
+        .map(|limit| format!("{}:{}", limit.requests, limit.period));
+    let flags = [
+        flag("listen", json!(args.listen)),
+        flag("rate-limit", json!(rate_limit)),
+        flag("verbose", json!(args.verbose)),
+        flag("trace", json!(args.trace)),
+        flag("debug", json!(args.debug)),
+        flag("audit", json!(args.audit)),
+        flag("no-delegation", json!(args.no_delegation)),
+        flag("auto-update", json!(args.auto_update)),
+        flag("config-file", json!(args.user_config)),
+        flag("profile", json!(args.profile)),
+        flag(
+            "snapshot-url",
+            json!(args.snapshot_url.as_deref().map(redact_url)),
+        ),
+        flag("registry", json!(args.registry)),
+        flag("registry-key", json!(args.registry_key)),
+        flag("control", json!(args.control)),
+        flag("tls-ca", json!(args.tls_ca)),
+        flag("tls-cert", json!(args.tls_cert)),
+        flag("tls-key", json!(args.tls_key)),
+        flag("otlp", json!(args.otlp)),
+        flag("peer", json!(args.peers)),
+        flag("public", json!(args.public)),
+        flag("strict", json!(args.strict)),
+        flag("strict-startup", json!(args.strict_startup)),
+    ];
+    Value::Object(flags.into_iter().collect())
+}
+
+fn env() -> Value {
+    let env = ENV.iter().map(|(name, is_secret)| {
+        let value = match std::env::var(name) {
+            Ok(_) if *is_secret => json!(REDACTED),
+            Ok(value) => json!(value),
+            Err(_) => Value::Null,
+        };
+        (name.to_string(), value)
+    });
+    Value::Object(env.collect())
+}
+
+/// Where the node list is loaded from (in order of precedence)
+fn nodes(args: &Args) -> Value {
+    let source = if let Some(registry) = args.registry.as_ref() {
+        json!({ "source" : "registry", "url" : registry })
+    } else if let Some(path) = args.user_config.as_ref() {
+        json!({ "source" : "config-file", "path" : path })
+    } else if args.auto_update {
+        json!({ "source" : "auto-update", "url" : Updates::url() })
+    } else {
+        json!({ "source" : "global", "folder" : global_config_folder() })
+    };
+    json!({ "nodes" : source, "profile" : profile() })
+}
+
+fn settings() -> Value {
+    let mut settings = serde_json::to_value(Settings::get()).unwrap_or_default();
+    for path in SECRET_SETTINGS {
+        let Some((section, field)) = path.split_once('.') else {
+            continue;
+        };
+        if let Some(value) = settings
+            .get_mut(section)
+            .and_then(|section| section.get_mut(field))
+        {
+            if value.as_str().is_some_and(|value| !value.is_empty()) {
+                *value = json!(REDACTED);
+            }
+        }
+    }
+    settings
+}
+
+/// Effective configuration as a JSON object
+pub fn to_json(args: &Args) -> Value {
+    json!({
+        "version" : crate::VERSION,
+        "flags" : flags(args),
+        "env" : env(),
+        "config" : nodes(args),
+        // built into the binary (`Resolver.toml`), not overridable at runtime
+        "settings" : settings(),
+    })
+}
+
+/// Log the effective configuration, one line per section
+pub fn banner(args: &Args) {
+    let effective = to_json(args);
+    let section = |value: &Value| value.as_object().cloned().unwrap_or_default();
+
+    let flags = section(&effective["flags"])
+        .into_iter()
+        .filter(|(_, flag)| flag["source"] == "command-line")
+        .map(|(id, flag)| format!("--{id}={}", flag["value"]))
+        .collect::<Vec<_>>();
+    if flags.is_empty() {
+        log_info!("Effective", "flags: defaults");
+    } else {
+        log_info!("Effective", "flags: {}", flags.join(" "));
+    }
+    log_info!("Effective", "env: {}", effective["env"]);
+    log_info!("Effective", "config: {}", effective["config"]);
+    let settings: Map<String, Value> = section(&effective["settings"]);
+    for (name, value) in settings {
+        log_info!("Effective", "[{name}] {value}");
+    }
+}
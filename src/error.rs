@@ -13,6 +13,9 @@ pub enum Error {
     #[error(transparent)]
     VecnoRpcCore(#[from] vecno_rpc_core::RpcError),
 
+    #[error("gRPC error: {0}")]
+    Grpc(String),
+
     // #[error(transparent)]
     #[error("TOML error: {0}")]
     Toml(#[from] toml::de::Error),
@@ -35,6 +38,9 @@ pub enum Error {
     #[error("Status")]
     Status,
 
+    #[error("`{0}` timed out")]
+    Timeout(&'static str),
+
     #[error("Channel send error")]
     ChannelSend,
 
@@ -70,6 +76,24 @@ pub enum Error {
 
     #[error("Unauthorized")]
     Unauthorized,
+
+    #[error("Unable to bind `{0}`: {1}")]
+    Bind(String, std::io::Error),
+
+    #[error("No node connections available after {0} seconds")]
+    NoConnections(u64),
+
+    #[error("Startup quorum not reached: {0}/{1} nodes available after {2} seconds")]
+    StartupQuorum(usize, usize, u64),
+
+    #[error("Refusing to bind {0} ({1}) to delegate {2} serving a different network ({3})")]
+    DelegateNetwork(String, vecno_consensus_core::network::NetworkId, String, vecno_consensus_core::network::NetworkId),
+
+    #[error("Refusing to bind {0} to delegate {1}: the delegate chain leads back to {0}")]
+    DelegateCycle(String, String),
+
+    #[error("Refusing to bind {0} to delegate {1}: the delegate chain exceeds {2} connections")]
+    DelegateDepth(String, String, usize),
 }
 
 impl Error {
@@ -84,6 +108,48 @@ impl Error {
     }
 }
 
+impl Error {
+    /// Errors produced by the RPC layer (request failures, timeouts),
+    /// as opposed to the node reporting an unusable state.
+    pub fn is_rpc(&self) -> bool {
+        matches!(
+            self,
+            Error::VecnoRpc(_) | Error::VecnoRpcCore(_) | Error::Grpc(_) | Error::Timeout(_)
+        )
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        Error::Grpc(status.message().to_string())
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::transport::Error> for Error {
+    fn from(err: tonic::transport::Error) -> Self {
+        Error::Grpc(err.to_string())
+    }
+}
+
+impl Error {
+    /// Process exit code for this error class
+    pub fn exit_code(&self) -> crate::exit::ExitCode {
+        use crate::exit::ExitCode;
+        match self {
+            Error::Config(_)
+            | Error::Toml(_)
+            | Error::LocalConfigNotFound
+            | Error::KeyNotFound => ExitCode::Config,
+            Error::Bind(..) => ExitCode::Bind,
+            Error::NoConnections(_) => ExitCode::NoConnections,
+            Error::StartupQuorum(..) => ExitCode::StartupQuorum,
+            _ => ExitCode::Error,
+        }
+    }
+}
+
 impl Error {
     pub fn config<T: std::fmt::Display>(msg: T) -> Self {
         Error::Config(msg.to_string())
@@ -1,4 +1,6 @@
 pub enum Events {
     Start,
     Update,
+    /// Re-read the node configuration (SIGHUP, `/admin/reload`)
+    Reload,
 }
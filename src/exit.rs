@@ -0,0 +1,24 @@
+/// Process exit codes, allowing orchestration systems
+/// to branch restart behavior on the failure class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ExitCode {
+    /// Unclassified runtime error
+    Error = 1,
+    /// Invalid arguments or configuration
+    Config = 2,
+    /// Unable to bind the HTTP server or control socket
+    Bind = 3,
+    /// No node connection became available at startup (`--strict`)
+    NoConnections = 4,
+    /// Thread panics exceeded `[limits] panics-per-hour`
+    PanicThreshold = 5,
+    /// Startup quorum not reached (`--strict-startup`)
+    StartupQuorum = 6,
+}
+
+impl ExitCode {
+    pub fn exit(self) -> ! {
+        std::process::exit(self as i32)
+    }
+}
@@ -0,0 +1,248 @@
+//!
+//! Controlled policy experiments: a configurable percentage of clients
+//! is assigned (by a stable hash of the client address) to a treatment
+//! selection policy, the remaining clients to the control policy. Each
+//! arm records its elections, the client failure reports of its clients
+//! and the load of the nodes it elected, so that policy changes can be
+//! evaluated against measured outcomes.
+//!
+
+use crate::imports::*;
+use std::net::IpAddr;
+
+/// Experiment definition (`PUT /admin/experiment`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExperimentConfig {
+    pub name: String,
+    pub control: Policy,
+    pub treatment: Policy,
+    /// Percentage of clients assigned to the treatment arm (0..=100)
+    pub percent: f64,
+}
+
+impl ExperimentConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(Error::custom("experiment name is empty"));
+        }
+        if !(0.0..=100.0).contains(&self.percent) {
+            return Err(Error::custom(format!(
+                "percent {} is out of range (0..=100)",
+                self.percent
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Arm {
+    Control,
+    Treatment,
+}
+
+/// Running mean and variance (Welford) of the elected node load
+#[derive(Debug, Default)]
+struct Load {
+    samples: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Load {
+    fn add(&mut self, load: f64) {
+        self.samples += 1;
+        let delta = load - self.mean;
+        self.mean += delta / self.samples as f64;
+        self.m2 += delta * (load - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        if self.samples < 2 {
+            0.0
+        } else {
+            self.m2 / (self.samples - 1) as f64
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Outcome {
+    policy: Policy,
+    elections: AtomicU64,
+    failures: AtomicU64,
+    load: Mutex<Load>,
+}
+
+impl Outcome {
+    fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            elections: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            load: Default::default(),
+        }
+    }
+
+    fn report(&self, arm: Arm) -> ArmReport {
+        let elections = self.elections.load(Ordering::Relaxed);
+        let failures = self.failures.load(Ordering::Relaxed);
+        let load = self.load.lock().unwrap();
+        ArmReport {
+            arm,
+            policy: self.policy,
+            elections,
+            failures,
+            success_rate: (elections > 0)
+                .then(|| 1.0 - (failures as f64 / elections as f64).min(1.0)),
+            load_mean: load.mean,
+            load_variance: load.variance(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Running {
+    config: ExperimentConfig,
+    started: u64,
+    seed: u64,
+    control: Outcome,
+    treatment: Outcome,
+}
+
+impl Running {
+    fn outcome(&self, arm: Arm) -> &Outcome {
+        match arm {
+            Arm::Control => &self.control,
+            Arm::Treatment => &self.treatment,
+        }
+    }
+
+    fn assign(&self, client: IpAddr) -> Arm {
+        let bucket = xxh3_64_with_seed(client.to_string().as_bytes(), self.seed) % 10_000;
+        if (bucket as f64) < self.config.percent * 100.0 {
+            Arm::Treatment
+        } else {
+            Arm::Control
+        }
+    }
+
+    fn report(&self) -> ExperimentReport {
+        ExperimentReport {
+            config: self.config.clone(),
+            started: self.started,
+            arms: vec![
+                self.control.report(Arm::Control),
+                self.treatment.report(Arm::Treatment),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ArmReport {
+    pub arm: Arm,
+    pub policy: Policy,
+    pub elections: u64,
+    /// Client failure reports by clients assigned to the arm
+    pub failures: u64,
+    /// `1 - failures / elections`
+    pub success_rate: Option<f64>,
+    /// Mean and variance of the load (clients / capacity) of elected nodes
+    pub load_mean: f64,
+    pub load_variance: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExperimentReport {
+    #[serde(flatten)]
+    pub config: ExperimentConfig,
+    pub started: u64,
+    pub arms: Vec<ArmReport>,
+}
+
+/// Runtime-configured policy experiment (not persisted across restarts),
+/// shared by the monitors of all services
+#[derive(Debug, Default)]
+pub struct Experiment {
+    running: Mutex<Option<Arc<Running>>>,
+}
+
+impl Experiment {
+    pub fn get() -> &'static Self {
+        static EXPERIMENT: OnceLock<Experiment> = OnceLock::new();
+        EXPERIMENT.get_or_init(Experiment::default)
+    }
+
+    /// Start `config`, replacing (and returning the report of) a running experiment
+    pub fn start(&self, config: ExperimentConfig) -> Result<Option<ExperimentReport>> {
+        config.validate()?;
+        let started = unix_now();
+        let detail = format!(
+            "{}: {} vs {} ({}%)",
+            config.name, config.control, config.treatment, config.percent
+        );
+        let running = Running {
+            seed: xxh3_64_with_seed(config.name.as_bytes(), started),
+            control: Outcome::new(config.control),
+            treatment: Outcome::new(config.treatment),
+            config,
+            started,
+        };
+        let previous = self.running.lock().unwrap().replace(Arc::new(running));
+        log_info!("Experiment", "started {detail}");
+        Journal::record(JournalKind::Experiment, "policy", Some(format!("started {detail}")));
+        Ok(previous.map(|previous| previous.report()))
+    }
+
+    /// Stop the running experiment, returning its final report
+    pub fn stop(&self) -> Option<ExperimentReport> {
+        let running = self.running.lock().unwrap().take()?;
+        let report = running.report();
+        log_info!("Experiment", "stopped {}", report.config.name);
+        Journal::record(
+            JournalKind::Experiment,
+            "policy",
+            Some(format!("stopped {}", report.config.name)),
+        );
+        Some(report)
+    }
+
+    pub fn report(&self) -> Option<ExperimentReport> {
+        self.running.lock().unwrap().as_ref().map(|running| running.report())
+    }
+
+    /// Arm and selection policy of `client` if an experiment is running
+    pub fn assign(&self, client: IpAddr) -> Option<(Arm, Policy)> {
+        let running = self.running.lock().unwrap();
+        running.as_ref().map(|running| {
+            let arm = running.assign(client);
+            (arm, running.outcome(arm).policy)
+        })
+    }
+
+    /// Record an election of `connection` by `arm`
+    pub fn elected(&self, arm: Arm, connection: &Arc<Connection>) {
+        let running = self.running.lock().unwrap().clone();
+        if let Some(running) = running {
+            let outcome = running.outcome(arm);
+            outcome.elections.fetch_add(1, Ordering::Relaxed);
+            if let Some(load) = connection.delegate().state().load() {
+                outcome.load.lock().unwrap().add(load);
+            }
+        }
+    }
+
+    /// Record a client failure report against the arm of `client`
+    pub fn failure(&self, client: IpAddr) {
+        let running = self.running.lock().unwrap().clone();
+        if let Some(running) = running {
+            let arm = running.assign(client);
+            running.outcome(arm).failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
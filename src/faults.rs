@@ -0,0 +1,114 @@
+use crate::imports::*;
+
+/// Upper bound for the duration of an injected fault
+const MAX_DURATION_SEC: u64 = 60 * 60;
+
+/// Simulated failure mode of a connection
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum Fault {
+    /// Report the node as offline
+    Offline,
+    /// Multiply the reported number of clients
+    Load { factor: f64 },
+    /// Delay external probes
+    ProbeDelay {
+        #[serde(rename = "delay-msec")]
+        delay_msec: u64,
+    },
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fault::Offline => write!(f, "offline"),
+            Fault::Load { factor } => write!(f, "load x{factor}"),
+            Fault::ProbeDelay { delay_msec } => write!(f, "probe delay {delay_msec}ms"),
+        }
+    }
+}
+
+/// Admin request injecting a fault for a bounded duration
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FaultRequest {
+    #[serde(flatten)]
+    pub fault: Fault,
+    pub duration_sec: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ActiveFault {
+    #[serde(flatten)]
+    pub fault: Fault,
+    pub expires_at: u64,
+}
+
+/// Faults injected into a connection via the admin API, used to
+/// validate alerting and client failover. Faults expire on their own.
+#[derive(Debug, Default)]
+pub struct Faults {
+    active: Mutex<Vec<ActiveFault>>,
+}
+
+impl Faults {
+    /// Inject a fault, replacing an active fault of the same mode
+    pub fn inject(&self, request: FaultRequest) -> Result<()> {
+        let FaultRequest { fault, duration_sec } = request;
+        if duration_sec == 0 || duration_sec > MAX_DURATION_SEC {
+            return Err(Error::custom(format!(
+                "fault duration must be between 1 and {MAX_DURATION_SEC} seconds"
+            )));
+        }
+        if let Fault::Load { factor } = fault {
+            if !factor.is_finite() || factor < 0.0 {
+                return Err(Error::custom("invalid load factor"));
+            }
+        }
+
+        let mut active = self.active.lock().unwrap();
+        active.retain(|f| std::mem::discriminant(&f.fault) != std::mem::discriminant(&fault));
+        active.push(ActiveFault {
+            fault,
+            expires_at: unix_now() + duration_sec,
+        });
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.active.lock().unwrap().clear();
+    }
+
+    /// Currently active (non-expired) faults
+    pub fn active(&self) -> Vec<ActiveFault> {
+        let now = unix_now();
+        let mut active = self.active.lock().unwrap();
+        active.retain(|f| f.expires_at > now);
+        active.clone()
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.active()
+            .iter()
+            .any(|f| matches!(f.fault, Fault::Offline))
+    }
+
+    /// Reported client count with an active load fault applied
+    pub fn inflate(&self, clients: u64) -> u64 {
+        self.active()
+            .iter()
+            .find_map(|f| match f.fault {
+                Fault::Load { factor } => Some((clients as f64 * factor) as u64),
+                _ => None,
+            })
+            .unwrap_or(clients)
+    }
+
+    pub fn probe_delay(&self) -> Option<Duration> {
+        self.active().iter().find_map(|f| match f.fault {
+            Fault::ProbeDelay { delay_msec } => Some(Duration::from_millis(delay_msec)),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,179 @@
+//!
+//! Socket exhaustion self-protection: the open file descriptors of the
+//! process are compared with its limit every `[fd-guard] interval-sec`.
+//! At `high-percent` of the limit the monitoring connections of the
+//! lowest-priority nodes (archived, delegators, then unavailable, by
+//! weight and client count) are shed, at most `max-shed-per-tick` per
+//! sample, until usage is back below `low-percent`, and new status and
+//! fleet feed subscribers are refused. Available delegates serve the
+//! elections and are never shed. Shed connections are restored, highest
+//! priority first, once usage stays below `low-percent`. Usage is only
+//! observed on Linux.
+//!
+
+use crate::imports::*;
+use crate::status::json_response;
+use axum::{body::Body, http::StatusCode, response::Response};
+use std::cmp::Reverse;
+use std::sync::atomic::AtomicUsize;
+
+pub struct FdGuard {
+    open: AtomicU64,
+    limit: AtomicU64,
+    shed: AtomicUsize,
+    is_exhausted: AtomicBool,
+}
+
+impl FdGuard {
+    pub fn get() -> &'static Self {
+        static GUARD: OnceLock<FdGuard> = OnceLock::new();
+        GUARD.get_or_init(|| Self {
+            open: AtomicU64::new(0),
+            limit: AtomicU64::new(0),
+            shed: AtomicUsize::new(0),
+            is_exhausted: AtomicBool::new(false),
+        })
+    }
+
+    /// Usage crossed `high-percent` and has not yet dropped below `low-percent`
+    pub fn is_exhausted(&self) -> bool {
+        self.is_exhausted.load(Ordering::Relaxed)
+    }
+
+    /// Open descriptors and the descriptor limit (0 if not observed)
+    pub fn usage(&self) -> (u64, u64) {
+        (self.open.load(Ordering::Relaxed), self.limit.load(Ordering::Relaxed))
+    }
+
+    /// Number of currently shed node connections
+    pub fn shed(&self) -> usize {
+        self.shed.load(Ordering::Relaxed)
+    }
+
+    /// Sample the descriptor usage, shedding or restoring `connections`
+    pub fn update(&self, connections: &[Arc<Connection>]) {
+        let Some((open, limit)) = usage() else {
+            return;
+        };
+        self.open.store(open, Ordering::Relaxed);
+        self.limit.store(limit, Ordering::Relaxed);
+
+        // lowest priority first
+        let mut connections = connections.to_vec();
+        connections.sort_by_key(|connection| priority(connection));
+
+        let high = FdGuardSettings::high(limit);
+        let low = FdGuardSettings::low(limit).min(high);
+        if open >= high {
+            if !self.is_exhausted.swap(true, Ordering::Relaxed) {
+                log_error!("FdGuard", "{open} of {limit} file descriptors open, shedding node connections");
+            }
+            // descriptors are also held by clients, subscribers and files,
+            // so the excess is only an upper bound of what shedding frees
+            let mut excess = open - low;
+            let sheddable = connections
+                .iter()
+                .filter(|connection| !connection.is_shed() && !is_protected(connection))
+                .take(FdGuardSettings::max_shed_per_tick());
+            for connection in sheddable {
+                if excess == 0 {
+                    break;
+                }
+                if connection.set_shed(true) {
+                    log_warn!("FdGuard", "shedding {}", connection.node());
+                    let detail = format!("{open} of {limit} file descriptors open");
+                    Journal::record(JournalKind::FdShed, connection.node(), Some(detail));
+                }
+                excess = excess.saturating_sub(descriptors(connection));
+            }
+        } else if open < low {
+            if self.is_exhausted.swap(false, Ordering::Relaxed) {
+                log_success!("FdGuard", "{open} of {limit} file descriptors open, accepting subscribers");
+            }
+            let mut headroom = low - open;
+            let restorable = connections
+                .iter()
+                .rev()
+                .filter(|connection| connection.is_shed())
+                .take(FdGuardSettings::max_shed_per_tick());
+            for connection in restorable {
+                let descriptors = descriptors(connection);
+                if headroom < descriptors {
+                    break;
+                }
+                if connection.set_shed(false) {
+                    log_info!("FdGuard", "restoring {}", connection.node());
+                    Journal::record(JournalKind::FdRestored, connection.node(), None);
+                }
+                headroom -= descriptors;
+            }
+        }
+
+        let shed = connections.iter().filter(|connection| connection.is_shed()).count();
+        self.shed.store(shed, Ordering::Relaxed);
+    }
+}
+
+/// Available delegates are never shed
+fn is_protected(connection: &Arc<Connection>) -> bool {
+    !connection.is_archived() && connection.is_delegate() && connection.is_available()
+}
+
+/// Descriptors released by shedding a connection: for tunneled wRPC
+/// connections the client, tunnel and node sockets (the tunnel listener,
+/// a fourth descriptor, stays open), the node socket otherwise
+fn descriptors(connection: &Arc<Connection>) -> u64 {
+    match connection.node().transport_kind {
+        TransportKind::WrpcBorsh | TransportKind::WrpcJson if Tunnel::is_tunneled(connection.node().address()) => 3,
+        _ => 1,
+    }
+}
+
+/// Shedding priority of a connection, lowest shed first
+fn priority(connection: &Arc<Connection>) -> (u8, u64, u64, Reverse<String>) {
+    let tier = if connection.is_archived() {
+        0
+    } else if !connection.is_delegate() {
+        1
+    } else if !connection.is_available() {
+        2
+    } else {
+        3
+    };
+    (
+        tier,
+        connection.weight(),
+        connection.delegate().clients(),
+        // stable order between otherwise equal nodes
+        Reverse(connection.node().uid_as_str().to_string()),
+    )
+}
+
+/// Open file descriptors and the soft descriptor limit of the process
+#[cfg(target_os = "linux")]
+fn usage() -> Option<(u64, u64)> {
+    let open = std::fs::read_dir("/proc/self/fd").ok()?.count() as u64;
+    let limits = std::fs::read_to_string("/proc/self/limits").ok()?;
+    let limit = limits
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))?
+        .split_whitespace()
+        .next()?
+        .parse::<u64>()
+        .ok()?;
+    Some((open, limit))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn usage() -> Option<(u64, u64)> {
+    None
+}
+
+/// Response refusing a new subscriber while descriptors are exhausted
+pub fn refuse() -> Response<Body> {
+    let (open, limit) = FdGuard::get().usage();
+    let body = serde_json::json!({
+        "error" : format!("resolver is near its file descriptor limit ({open} of {limit} open), retry later"),
+    });
+    json_response(StatusCode::SERVICE_UNAVAILABLE, body.to_string())
+}
@@ -0,0 +1,89 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Evaluate the shadow selection policy on each election
+pub const SHADOW_POLICY: &str = "shadow-policy";
+/// Retry transient RPC failures before marking a node offline
+pub const TRANSIENT_RETRY: &str = "transient-retry";
+/// Send anonymized fleet statistics to `[telemetry] url` (opt-in)
+pub const TELEMETRY: &str = "telemetry";
+
+/// Runtime feature toggles. Defaults are taken from the `[features]`
+/// section of `Resolver.toml`; overrides set via the admin API are
+/// persisted to `~/.vecno-resolver/features.json`.
+pub struct Features {
+    overrides: RwLock<BTreeMap<String, bool>>,
+    path: PathBuf,
+}
+
+impl Features {
+    pub fn get() -> &'static Self {
+        static FEATURES: OnceLock<Features> = OnceLock::new();
+        FEATURES.get_or_init(Features::load)
+    }
+
+    fn load() -> Self {
+        let path = global_config_folder().join("features.json");
+        let overrides = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str::<BTreeMap<String, bool>>(&json)?))
+            {
+                Ok(overrides) => overrides,
+                Err(err) => {
+                    log_error!("Features", "Unable to load `{}`: {err}", path.display());
+                    BTreeMap::new()
+                }
+            }
+        } else {
+            BTreeMap::new()
+        };
+
+        Self {
+            overrides: RwLock::new(overrides),
+            path,
+        }
+    }
+
+    /// Check if the feature is enabled (unknown features are disabled)
+    pub fn is_enabled(name: &str) -> bool {
+        let features = Self::get();
+        let enabled = features.overrides.read().unwrap().get(name).copied();
+        enabled
+            .or_else(|| FeatureSettings::default_for(name))
+            .unwrap_or(false)
+    }
+
+    /// Effective state of all known and overridden features
+    pub fn to_map(&self) -> BTreeMap<String, bool> {
+        let mut map = FeatureSettings::defaults()
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        map.extend(
+            self.overrides
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(k, v)| (k.clone(), *v)),
+        );
+        map
+    }
+
+    /// Override feature state, `None` restores the configured default.
+    pub fn set(&self, name: &str, enabled: Option<bool>) -> Result<()> {
+        let json = {
+            let mut overrides = self.overrides.write().unwrap();
+            match enabled {
+                Some(enabled) => {
+                    overrides.insert(name.to_string(), enabled);
+                }
+                None => {
+                    overrides.remove(name);
+                }
+            }
+            serde_json::to_string_pretty(&*overrides)?
+        };
+        log_info!("Features", "`{name}`: {}", Self::is_enabled(name));
+        std::fs::write(&self.path, json).map_err(|err| Error::file(&self.path, err))
+    }
+}
@@ -0,0 +1,313 @@
+//!
+//! Resolver federation: resolver instances configured as peers
+//! (`--peer=<url>`) exchange their node status tables over HTTP
+//! (`/v1/federation/nodes`), authenticated with a shared secret
+//! (`RESOLVER_FEDERATION_KEY`, HMAC-SHA256 of the request timestamp,
+//! and of the request timestamp followed by the response body, so
+//! that a response cannot be replayed to a later request). When no
+//! local node is available for an election, the resolver elects among
+//! the available nodes reported by its peers, providing geographic
+//! redundancy without connecting to every node. Peers only export their
+//! own (local) nodes, so tables are never relayed between peers.
+//!
+
+use crate::imports::*;
+use crate::status::json_response;
+use crate::utils::to_hex;
+use axum::{
+    body::Body,
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Request header carrying the request timestamp (UNIX seconds)
+const TS_HEADER: &str = "x-federation-ts";
+/// Request and response header carrying the hex HMAC-SHA256 signature
+const SIGNATURE_HEADER: &str = "x-federation-signature";
+/// Accepted clock skew of signed requests (seconds)
+const MAX_SKEW: u64 = 60;
+
+/// Node of a resolver status table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PeerNode {
+    pub uid: String,
+    pub id: String,
+    pub url: String,
+    // tables of older peers list `vecno` nodes only
+    #[serde(default)]
+    pub service: Service,
+    pub params: PathParams,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    pub roles: Vec<Role>,
+    pub available: bool,
+    pub score: u64,
+}
+
+/// Node status table of a resolver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Table {
+    pub ts: u64,
+    pub nodes: Vec<PeerNode>,
+}
+
+impl Table {
+    /// Table of the local node `connections` (all services)
+    pub fn new(connections: &[Arc<Connection>]) -> Self {
+        let nodes = connections
+            .iter()
+            .filter(|connection| connection.is_delegate())
+            .map(|connection| {
+                let node = connection.node();
+                PeerNode {
+                    uid: node.uid_as_str().to_string(),
+                    id: node.short_id().to_string(),
+                    url: node.address().to_string(),
+                    service: node.service(),
+                    params: connection.params(),
+                    region: node.region.clone(),
+                    roles: node.roles.clone(),
+                    available: connection.is_available_for(false),
+                    score: connection.score(),
+                }
+            })
+            .collect();
+        Self {
+            ts: unix_now(),
+            nodes,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Peer {
+    table: Option<Table>,
+    // time (UNIX seconds) of the last successful fetch
+    fetched_at: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PeerReport {
+    pub url: String,
+    pub fetched_at: Option<u64>,
+    pub stale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub nodes: Vec<PeerNode>,
+}
+
+pub struct Federation {
+    key: Vec<u8>,
+    peers: Mutex<BTreeMap<String, Peer>>,
+}
+
+impl Federation {
+    /// Federation with the `--peer` resolvers, `None` unless the
+    /// `RESOLVER_FEDERATION_KEY` shared secret is set
+    pub fn try_new(args: &Args) -> Result<Option<Self>> {
+        let key = std::env::var("RESOLVER_FEDERATION_KEY")
+            .ok()
+            .filter(|key| !key.is_empty());
+        let Some(key) = key else {
+            if !args.peers.is_empty() {
+                return Err(Error::config(
+                    "`--peer` requires the `RESOLVER_FEDERATION_KEY` env var",
+                ));
+            }
+            return Ok(None);
+        };
+
+        let peers = args
+            .peers
+            .iter()
+            .map(|url| {
+                reqwest::Url::parse(url)
+                    .map_err(|err| Error::config(format!("Invalid peer url `{url}`: {err}")))?;
+                Ok((url.trim_end_matches('/').to_string(), Peer::default()))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        Ok(Some(Self {
+            key: key.into_bytes(),
+            peers: Mutex::new(peers),
+        }))
+    }
+
+    fn mac(&self, parts: &[&[u8]]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        for part in parts {
+            mac.update(part);
+        }
+        mac
+    }
+
+    fn sign(&self, parts: &[&[u8]]) -> String {
+        to_hex(&self.mac(parts).finalize().into_bytes())
+    }
+
+    fn verify(&self, parts: &[&[u8]], signature: Option<&str>) -> bool {
+        let Some(signature) = signature.and_then(from_hex) else {
+            return false;
+        };
+        self.mac(parts).verify_slice(&signature).is_ok()
+    }
+
+    /// Serve the local node table to an authenticated peer
+    pub fn serve(&self, connections: &[Arc<Connection>], headers: &HeaderMap) -> Response<Body> {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let ts = header(TS_HEADER).and_then(|ts| ts.parse::<u64>().ok());
+        let ts = ts.filter(|ts| {
+            unix_now().abs_diff(*ts) <= MAX_SKEW
+                && self.verify(&[ts.to_string().as_bytes()], header(SIGNATURE_HEADER))
+        });
+        let Some(ts) = ts else {
+            return json_response(StatusCode::UNAUTHORIZED, "");
+        };
+
+        // the response is bound to the request timestamp
+        let body = serde_json::to_string(&Table::new(connections)).unwrap();
+        let mut response = json_response(StatusCode::OK, body.clone());
+        let ts = format!("{ts}\n");
+        if let Ok(signature) = self.sign(&[ts.as_bytes(), body.as_bytes()]).parse() {
+            response.headers_mut().insert(SIGNATURE_HEADER, signature);
+        }
+        response
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Table> {
+        let ts = unix_now().to_string();
+        let response = reqwest::Client::new()
+            .get(format!("{url}/v1/federation/nodes"))
+            .header(TS_HEADER, &ts)
+            .header(SIGNATURE_HEADER, self.sign(&[ts.as_bytes()]))
+            .timeout(FederationSettings::timeout())
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Error::custom(format!("peer responded with {}", response.status())));
+        }
+
+        let signature = response
+            .headers()
+            .get(SIGNATURE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let body = response.bytes().await?;
+        let ts = format!("{ts}\n");
+        if !self.verify(&[ts.as_bytes(), &body], signature.as_deref()) {
+            return Err(Error::custom("invalid response signature"));
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetch the node tables of all peers
+    pub async fn update(&self) {
+        let urls = self.peers.lock().unwrap().keys().cloned().collect::<Vec<_>>();
+        let results =
+            futures::future::join_all(urls.iter().map(|url| self.fetch(url.as_str()))).await;
+
+        let mut peers = self.peers.lock().unwrap();
+        for (url, result) in urls.into_iter().zip(results) {
+            let peer = peers.entry(url.clone()).or_default();
+            match result {
+                Ok(table) => {
+                    if peer.error.take().is_some() || peer.fetched_at.is_none() {
+                        log_success!("Federation", "`{url}`: {} nodes", table.nodes.len());
+                    }
+                    peer.table = Some(table);
+                    peer.fetched_at = Some(unix_now());
+                }
+                Err(err) => {
+                    let err = err.to_string();
+                    if peer.error.as_ref() != Some(&err) {
+                        log_warn!("Federation", "`{url}`: {err}");
+                    }
+                    peer.error = Some(err);
+                }
+            }
+        }
+    }
+
+    fn is_stale(peer: &Peer) -> bool {
+        peer.fetched_at.is_none_or(|ts| {
+            unix_now().saturating_sub(ts) > FederationSettings::stale().as_secs()
+        })
+    }
+
+    /// Elect an available node of `service` serving `role` for `params` among
+    /// the nodes of peers with a recent table
+    pub fn elect(&self, service: Service, params: &PathParams, role: Role) -> Option<String> {
+        let peers = self.peers.lock().unwrap();
+        let mut nodes = peers
+            .iter()
+            .filter(|(_, peer)| !Self::is_stale(peer))
+            .filter_map(|(url, peer)| peer.table.as_ref().map(|table| (url, table)))
+            .flat_map(|(url, table)| table.nodes.iter().map(move |node| (url, node)))
+            .filter(|(_, node)| {
+                node.available
+                    && node.service == service
+                    && node.roles.contains(&role)
+                    && matches_params(&node.params, params)
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|(_, node)| node.score);
+
+        let index = PolicySettings::active().select_index(nodes.len())?;
+        let (peer, node) = nodes[index];
+        let output = serde_json::json!({
+            "uid" : node.uid,
+            "id" : node.id,
+            "url" : node.url,
+            "region" : node.region,
+            "ttl" : HintSettings::ttl(&params.network),
+            "peer" : peer,
+        });
+        Some(output.to_string())
+    }
+
+    pub fn report(&self) -> Vec<PeerReport> {
+        self.peers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(url, peer)| PeerReport {
+                url: url.clone(),
+                fetched_at: peer.fetched_at,
+                stale: Self::is_stale(peer),
+                error: peer.error.clone(),
+                nodes: peer
+                    .table
+                    .as_ref()
+                    .map(|table| table.nodes.clone())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// Peer node `node` (tls strict) serves election path `params` (tls strict or any)
+fn matches_params(node: &PathParams, params: &PathParams) -> bool {
+    node.network == params.network
+        && node.protocol == params.protocol
+        && node.encoding == params.encoding
+        && (params.tls == TlsKind::Any || node.tls == params.tls)
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,52 @@
+//!
+//! Feed of node status transitions (`online`, `offline`, `syncing`,
+//! `delegator`, ...) streamed to `/v1/events` subscribers as
+//! Server-Sent Events.
+//!
+
+use crate::imports::*;
+use tokio::sync::broadcast;
+
+const CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StatusEvent {
+    pub ts: u64,
+    pub uid: String,
+    pub id: String,
+    pub network: String,
+    pub status: &'static str,
+    pub previous: &'static str,
+}
+
+pub struct Feed {
+    sender: broadcast::Sender<StatusEvent>,
+}
+
+impl Feed {
+    pub fn get() -> &'static Self {
+        static FEED: OnceLock<Feed> = OnceLock::new();
+        FEED.get_or_init(|| Feed {
+            sender: broadcast::channel(CAPACITY).0,
+        })
+    }
+
+    /// Publish the status transition of `node`
+    pub fn publish(&self, node: &Node, previous: &'static str, status: &'static str) {
+        let event = StatusEvent {
+            ts: unix_now_msec(),
+            uid: node.uid_as_str().to_string(),
+            id: node.short_id().to_string(),
+            network: node.network.to_string(),
+            status,
+            previous,
+        };
+        // no receivers is not an error
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StatusEvent> {
+        self.sender.subscribe()
+    }
+}
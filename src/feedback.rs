@@ -0,0 +1,133 @@
+//!
+//! Client feedback: clients report RPC failures of the node they were
+//! elected to (`POST /v1/feedback`). When failures reported by at least
+//! `[feedback] min-clients` distinct clients reach `min-reports` within
+//! `window-sec` for a node the resolver still considers available, the
+//! node is flagged `suspect` (ranked after all other nodes) and an
+//! immediate out-of-band probe is issued. The flag is cleared once
+//! the probe completes; a failing probe excludes the node as usual.
+//! A node is probed at most once per `cooldown-sec`.
+//!
+
+use crate::imports::*;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+
+const BODY_LIMIT: usize = 4 * 1024;
+// characters of a reported error retained for logs and notifications
+const ERROR_LIMIT: usize = 200;
+
+/// Failure report posted by a client
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Report {
+    /// Node uid (hex) or short id, as returned by the election
+    pub uid: String,
+    /// Optional error description (logged)
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl Report {
+    /// Reported error with control characters replaced and truncated
+    /// to `ERROR_LIMIT` characters, as it is logged and journaled
+    pub fn error(&self) -> Option<String> {
+        self.error
+            .as_deref()
+            .map(|error| {
+                error
+                    .chars()
+                    .take(ERROR_LIMIT)
+                    .map(|c| if c.is_control() { ' ' } else { c })
+                    .collect::<String>()
+            })
+            .filter(|error| !error.trim().is_empty())
+    }
+}
+
+/// Client failure reports and suspect state of a connection
+#[derive(Default)]
+pub struct Reports {
+    // (report time msec, client address), within the feedback window
+    reports: Mutex<VecDeque<(u64, IpAddr)>>,
+    is_suspect: AtomicBool,
+    // time (msec) of the last feedback-triggered probe
+    last_probe: AtomicU64,
+}
+
+impl Reports {
+    /// Record a failure reported by `ip`, returning `true` if the
+    /// reports amount to a failure spike and the node should be probed
+    pub fn record(&self, ip: IpAddr) -> bool {
+        let now = unix_now_msec();
+        let window = FeedbackSettings::window().as_millis() as u64;
+        let mut reports = self.reports.lock().unwrap();
+        reports.push_back((now, ip));
+        while reports
+            .front()
+            .is_some_and(|(ts, _)| now.saturating_sub(*ts) > window)
+        {
+            reports.pop_front();
+        }
+
+        let clients = reports.iter().map(|(_, ip)| ip).collect::<HashSet<_>>();
+        let is_spike = reports.len() >= FeedbackSettings::min_reports()
+            && clients.len() >= FeedbackSettings::min_clients();
+        let cooldown = FeedbackSettings::cooldown().as_millis() as u64;
+        let last_probe = self.last_probe.load(Ordering::Relaxed);
+        if !is_spike || self.is_suspect() || now.saturating_sub(last_probe) < cooldown {
+            return false;
+        }
+
+        reports.clear();
+        self.last_probe.store(now, Ordering::Relaxed);
+        self.is_suspect.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Number of reports within the feedback window
+    pub fn count(&self) -> usize {
+        self.reports.lock().unwrap().len()
+    }
+
+    #[inline]
+    pub fn is_suspect(&self) -> bool {
+        self.is_suspect.load(Ordering::Relaxed)
+    }
+
+    /// Clear the suspect flag once the probe has completed
+    pub fn clear(&self) {
+        self.is_suspect.store(false, Ordering::Relaxed);
+    }
+}
+
+/// `POST /v1/feedback`: record a client failure report
+pub async fn feedback_handler(
+    resolver: &Arc<Resolver>,
+    addr: SocketAddr,
+    req: Request<Body>,
+) -> Response {
+    if !FeedbackSettings::enable() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let ip = blocklist::client_ip(addr, req.headers());
+    let Ok(body) = axum::body::to_bytes(req.into_body(), BODY_LIMIT).await else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Ok(report) = serde_json::from_slice::<Report>(&body) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let Some(connection) = resolver.find(&report.uid) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    connection.report_failure(ip, report.error().as_deref());
+    Experiment::get().failure(ip);
+    StatusCode::ACCEPTED.into_response()
+}
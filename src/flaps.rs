@@ -0,0 +1,248 @@
+//!
+//! Flap correlation: node down transitions recorded in the journal are
+//! clustered in time (transitions less than `[flaps] window-sec` apart
+//! form one event) to tell fleet-wide events, where several nodes went
+//! down together, from isolated node issues. Fleet-wide events are
+//! classified by what the affected nodes share: the whole fleet, a
+//! provider (the last two labels of the node hostname, or the /24
+//! network of its address) or a declared region.
+//!
+
+use crate::imports::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+/// Journal kinds recording a node going down
+const DOWN: &[JournalKind] = &[
+    JournalKind::Demoted,
+    JournalKind::NetworkPartition,
+    JournalKind::DeadPeerReset,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    /// A single node (or fewer than `min-nodes`)
+    Isolated,
+    /// At least `fleet-ratio` of all nodes
+    Fleet,
+    /// Nodes of one provider
+    Provider,
+    /// Nodes of one region
+    Region,
+    /// Several nodes without a shared provider or region
+    Correlated,
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Scope::Isolated => "isolated",
+            Scope::Fleet => "fleet",
+            Scope::Provider => "provider",
+            Scope::Region => "region",
+            Scope::Correlated => "correlated",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Down transition of a node
+#[derive(Debug, Clone)]
+struct Flap {
+    ts: u64,
+    id: String,
+    provider: Option<String>,
+    region: Option<String>,
+}
+
+impl Flap {
+    fn try_new(connections: &[Arc<Connection>], entry: &JournalEntry) -> Option<Self> {
+        if !DOWN.contains(&entry.kind) {
+            return None;
+        }
+        // node subjects are rendered as `[id] address`
+        let (id, address) = entry.subject.strip_prefix('[')?.split_once("] ")?;
+        let connection = connections
+            .iter()
+            .find(|connection| connection.node().is_id(id));
+        let host = match connection.as_ref() {
+            Some(connection) => Some(connection.node().fqdn.clone()),
+            None => reqwest::Url::parse(address)
+                .ok()
+                .and_then(|url| url.host_str().map(String::from)),
+        };
+        Some(Self {
+            ts: entry.ts,
+            id: id.to_string(),
+            provider: host.as_deref().map(provider),
+            region: connection.and_then(|connection| connection.node().region.clone()),
+        })
+    }
+}
+
+/// Provider of a node host: the /24 (IPv4) or /48 (IPv6) network of
+/// an address, or the last two labels of a hostname
+fn provider(host: &str) -> String {
+    let host = host
+        .rsplit_once(':')
+        .filter(|(host, port)| port.parse::<u16>().is_ok() && !host.contains(':'))
+        .map(|(host, _)| host)
+        .unwrap_or(host)
+        .trim_matches(['[', ']']);
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{a}.{b}.{c}.0/24")
+        }
+        Ok(IpAddr::V6(ip)) => {
+            let segments = ip.segments();
+            format!("{:x}:{:x}:{:x}::/48", segments[0], segments[1], segments[2])
+        }
+        Err(_) => {
+            let labels = host.rsplit('.').take(2).collect::<Vec<_>>();
+            labels.into_iter().rev().collect::<Vec<_>>().join(".")
+        }
+    }
+}
+
+/// Correlated event (a cluster of down transitions)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Event {
+    pub start: u64,
+    pub end: u64,
+    pub scope: Scope,
+    /// Shared provider or region (`provider` and `region` scopes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Short ids of the affected nodes
+    pub nodes: Vec<String>,
+    pub transitions: usize,
+}
+
+impl Event {
+    fn new(flaps: &[Flap], fleet: usize) -> Self {
+        let nodes = flaps.iter().map(|flap| flap.id.clone()).collect::<BTreeSet<_>>();
+        let shared = |key: fn(&Flap) -> Option<&String>| {
+            let values = flaps.iter().map(key).collect::<BTreeSet<_>>();
+            match values.into_iter().collect::<Vec<_>>().as_slice() {
+                [Some(value)] => Some((*value).clone()),
+                _ => None,
+            }
+        };
+
+        let (scope, key) = if nodes.len() < FlapsSettings::min_nodes() {
+            (Scope::Isolated, None)
+        } else if fleet > 0 && nodes.len() as f64 >= fleet as f64 * FlapsSettings::fleet_ratio() {
+            (Scope::Fleet, None)
+        } else if let Some(provider) = shared(|flap| flap.provider.as_ref()) {
+            (Scope::Provider, Some(provider))
+        } else if let Some(region) = shared(|flap| flap.region.as_ref()) {
+            (Scope::Region, Some(region))
+        } else {
+            (Scope::Correlated, None)
+        };
+
+        Self {
+            start: flaps.first().map(|flap| flap.ts).unwrap_or_default(),
+            end: flaps.last().map(|flap| flap.ts).unwrap_or_default(),
+            scope,
+            key,
+            nodes: nodes.into_iter().collect(),
+            transitions: flaps.len(),
+        }
+    }
+
+    fn is_fleet_wide(&self) -> bool {
+        self.scope != Scope::Isolated
+    }
+
+    /// Classification rendered in notifications
+    pub fn summary(&self) -> String {
+        match (&self.scope, &self.key) {
+            (Scope::Isolated, _) => "isolated".to_string(),
+            (scope, Some(key)) => {
+                format!("fleet-wide ({scope} {key}, {} nodes)", self.nodes.len())
+            }
+            (scope, None) => format!("fleet-wide ({scope}, {} nodes)", self.nodes.len()),
+        }
+    }
+}
+
+/// Cluster the down transitions of `entries` (ordered by time)
+/// of the nodes of `connections` (all services)
+fn cluster(connections: &[Arc<Connection>], entries: &[JournalEntry]) -> Vec<Event> {
+    let mut flaps = entries
+        .iter()
+        .filter_map(|entry| Flap::try_new(connections, entry))
+        .collect::<Vec<_>>();
+    flaps.sort_by_key(|flap| flap.ts);
+
+    let fleet = connections.iter().filter(|connection| connection.is_delegate()).count();
+    let window = FlapsSettings::window().as_secs();
+    let mut events = Vec::new();
+    let mut start = 0;
+    for index in 1..=flaps.len() {
+        if index == flaps.len() || flaps[index].ts - flaps[index - 1].ts > window {
+            events.push(Event::new(&flaps[start..index], fleet));
+            start = index;
+        }
+    }
+    events
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FlapsQuery {
+    /// start of the analyzed period (UNIX seconds)
+    pub since: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Report {
+    pub since: u64,
+    pub window_sec: u64,
+    pub transitions: usize,
+    pub isolated: usize,
+    pub fleet_wide: usize,
+    /// Fleet-wide events by affected scope
+    pub scopes: BTreeMap<String, usize>,
+    pub events: Vec<Event>,
+}
+
+/// Correlate the down transitions of the persisted journal since `since`
+pub fn analyze(connections: &[Arc<Connection>], since: u64) -> Result<Report> {
+    let entries = Journal::read_since(since)?;
+
+    let events = cluster(connections, &entries);
+    let mut scopes = BTreeMap::new();
+    for event in events.iter().filter(|event| event.is_fleet_wide()) {
+        *scopes.entry(event.scope.to_string()).or_default() += 1;
+    }
+    Ok(Report {
+        since,
+        window_sec: FlapsSettings::window().as_secs(),
+        transitions: events.iter().map(|event| event.transitions).sum(),
+        isolated: events.iter().filter(|event| !event.is_fleet_wide()).count(),
+        fleet_wide: events.iter().filter(|event| event.is_fleet_wide()).count(),
+        scopes,
+        events,
+    })
+}
+
+/// Classify the down transition `entry` against the recent journal
+/// entries, `None` if `entry` is not a down transition
+pub fn classify(connections: &[Arc<Connection>], entry: &JournalEntry) -> Option<Event> {
+    if !DOWN.contains(&entry.kind) {
+        return None;
+    }
+    let since = entry.ts.saturating_sub(FlapsSettings::window().as_secs());
+    let mut entries = Journal::get()
+        .recent()
+        .into_iter()
+        .filter(|recent| recent.ts >= since && recent.ts <= entry.ts)
+        .collect::<Vec<_>>();
+    entries.push(entry.clone());
+    cluster(connections, &entries).pop()
+}
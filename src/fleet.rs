@@ -0,0 +1,269 @@
+//!
+//! Public fleet event feed: nodes joining and leaving the available
+//! set of each network, without node addresses or other sensitive
+//! details, for ecosystem status pages. Events are signed with the
+//! resolver key (`RESOLVER_SNAPSHOT_KEY`) and served as Server-Sent
+//! Events (`/v1/fleet/events`) or for polling (`/v1/fleet/events.json`),
+//! rate limited per client address.
+//!
+
+use crate::imports::*;
+use crate::status::json_response;
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{Request, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures::StreamExt;
+use secp256k1::{Keypair, Message, SECP256K1};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+const CAPACITY: usize = 256;
+const MINUTE_MSEC: u64 = 60 * 1000;
+
+/// Change of the available set of a network
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FleetEvent {
+    /// Sequence number (restarts with the resolver)
+    pub seq: u64,
+    pub ts: u64,
+    pub network: String,
+    /// Node short id
+    pub id: String,
+    /// `joined` or `left`
+    pub event: &'static str,
+    /// Available nodes of the network after the event
+    pub available: usize,
+}
+
+/// [`FleetEvent`] serialized as a JSON string with the hex Schnorr
+/// signature of its SHA256 hash (as with the node registry)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SignedEvent {
+    pub event: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Return events with a sequence number above `since`
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+#[derive(Default)]
+struct State {
+    // network -> short ids of the available nodes
+    available: BTreeMap<String, HashSet<String>>,
+    recent: VecDeque<(u64, SignedEvent)>,
+    seq: u64,
+}
+
+pub struct Fleet {
+    keypair: Option<Keypair>,
+    state: Mutex<State>,
+    sender: broadcast::Sender<SignedEvent>,
+    // client address -> (window start msec, requests)
+    requests: Mutex<AHashMap<IpAddr, (u64, u64)>>,
+    streams: Arc<AtomicU64>,
+}
+
+/// Open SSE stream, released when the client disconnects
+struct StreamGuard(Arc<AtomicU64>);
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Fleet {
+    pub fn get() -> &'static Self {
+        static FLEET: OnceLock<Fleet> = OnceLock::new();
+        FLEET.get_or_init(|| {
+            let keypair = std::env::var("RESOLVER_SNAPSHOT_KEY").ok().and_then(|key| {
+                match Keypair::from_seckey_str(SECP256K1, key.trim()) {
+                    Ok(keypair) => Some(keypair),
+                    Err(err) => {
+                        log_error!("Fleet", "Invalid `RESOLVER_SNAPSHOT_KEY`: {err}");
+                        None
+                    }
+                }
+            });
+            if keypair.is_none() && FleetFeedSettings::enable() {
+                log_warn!(
+                    "Fleet",
+                    "`RESOLVER_SNAPSHOT_KEY` is not set, fleet events will not be signed"
+                );
+            }
+
+            Fleet {
+                keypair,
+                state: Mutex::default(),
+                sender: broadcast::channel(CAPACITY).0,
+                requests: Mutex::default(),
+                streams: Arc::new(AtomicU64::new(0)),
+            }
+        })
+    }
+
+    /// x-only public key verifying event signatures (hex)
+    pub fn public_key(&self) -> Option<String> {
+        self.keypair
+            .as_ref()
+            .map(|keypair| keypair.x_only_public_key().0.to_string())
+    }
+
+    fn sign(&self, event: &FleetEvent) -> SignedEvent {
+        let event = serde_json::to_string(event).unwrap_or_default();
+        let signature = self.keypair.as_ref().map(|keypair| {
+            let digest: [u8; 32] = Sha256::digest(event.as_bytes()).into();
+            let signature =
+                SECP256K1.sign_schnorr_no_aux_rand(&Message::from_digest(digest), keypair);
+            signature.to_string()
+        });
+        SignedEvent { event, signature }
+    }
+
+    /// Publish changes of the available set of each network
+    pub fn update(&self, connections: &[Arc<Connection>]) {
+        if !FleetFeedSettings::enable() {
+            return;
+        }
+
+        let mut networks = BTreeMap::<String, HashSet<String>>::new();
+        for connection in connections
+            .iter()
+            .filter(|connection| connection.is_delegate())
+        {
+            let available = networks
+                .entry(connection.network_id().to_string())
+                .or_default();
+            if connection.is_available() {
+                available.insert(connection.node().short_id().to_string());
+            }
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let ts = unix_now_msec();
+        let mut events = Vec::new();
+        for (network, available) in networks.iter() {
+            let previous = state.available.get(network).cloned().unwrap_or_default();
+            let joined = available.difference(&previous).map(|id| (id, "joined"));
+            let left = previous.difference(available).map(|id| (id, "left"));
+            for (id, event) in joined.chain(left) {
+                events.push(FleetEvent {
+                    seq: 0,
+                    ts,
+                    network: network.clone(),
+                    id: id.clone(),
+                    event,
+                    available: available.len(),
+                });
+            }
+        }
+        state.available = networks;
+
+        for mut event in events {
+            state.seq += 1;
+            event.seq = state.seq;
+            let signed = self.sign(&event);
+            if state.recent.len() == FleetFeedSettings::history() {
+                state.recent.pop_front();
+            }
+            state.recent.push_back((event.seq, signed.clone()));
+            // no receivers is not an error
+            let _ = self.sender.send(signed);
+        }
+    }
+
+    /// Count a request of `ip`, `false` if over `requests-per-min`
+    fn allow(&self, ip: IpAddr) -> bool {
+        let now = unix_now_msec();
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, (start, _)| now.saturating_sub(*start) < MINUTE_MSEC);
+        let (_, count) = requests.entry(ip).or_insert((now, 0));
+        *count += 1;
+        *count <= FleetFeedSettings::requests_per_min()
+    }
+
+    fn recent(&self, since: u64) -> Vec<SignedEvent> {
+        let state = self.state.lock().unwrap();
+        state
+            .recent
+            .iter()
+            .filter(|(seq, _)| *seq > since)
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+}
+
+fn client_ip(addr: SocketAddr, req: &Request<Body>) -> IpAddr {
+    blocklist::client_ip(addr, req.headers())
+}
+
+/// `GET /v1/fleet/events.json?since=<seq>`: recent fleet events
+pub async fn poll_handler(
+    addr: SocketAddr,
+    Query(query): Query<EventsQuery>,
+    req: Request<Body>,
+) -> Response {
+    if !FleetFeedSettings::enable() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let fleet = Fleet::get();
+    if !fleet.allow(client_ip(addr, &req)) {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    let body = serde_json::json!({
+        "public-key" : fleet.public_key(),
+        "events" : fleet.recent(query.since.unwrap_or_default()),
+    });
+    json_response(StatusCode::OK, body.to_string())
+}
+
+/// `GET /v1/fleet/events`: fleet events as Server-Sent Events
+pub async fn events_handler(addr: SocketAddr, req: Request<Body>) -> Response {
+    if !FleetFeedSettings::enable() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let fleet = Fleet::get();
+    if !fleet.allow(client_ip(addr, &req)) {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+    if FdGuard::get().is_exhausted() {
+        return fdguard::refuse();
+    }
+    if fleet.streams.fetch_add(1, Ordering::Relaxed) >= FleetFeedSettings::max_streams() {
+        fleet.streams.fetch_sub(1, Ordering::Relaxed);
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+
+    let guard = StreamGuard(fleet.streams.clone());
+    let receiver = fleet.sender.subscribe();
+    let stream = futures::stream::unfold((receiver, guard), |(mut receiver, guard)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, (receiver, guard))),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .map(|event| Event::default().event("fleet").json_data(&event));
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
@@ -0,0 +1,99 @@
+//!
+//! Geo-aware routing: with a MaxMind GeoIP (GeoLite2 Country or City)
+//! database (`--geoip=<path>`), elections prefer available nodes whose
+//! declared region serves the country or continent of the client,
+//! falling back to global selection when no regional node is available.
+//! Regions serve the ISO country and continent codes listed for them in
+//! `[geo.regions]`, or the code matching the region label itself (a
+//! node in region `eu` serves clients located in Europe).
+//!
+
+use crate::imports::*;
+use maxminddb::{geoip2, Reader};
+use std::net::IpAddr;
+
+pub struct Geo {
+    reader: Reader<Vec<u8>>,
+}
+
+static GEO: OnceLock<Option<Geo>> = OnceLock::new();
+
+impl Geo {
+    /// Load the `--geoip` database, geo-aware routing is disabled without it
+    pub fn init(args: &Args) -> Result<()> {
+        let geo = match args.geoip.as_ref() {
+            Some(path) => {
+                let reader = Reader::open_readfile(path).map_err(|err| {
+                    Error::config(format!("Unable to load GeoIP database `{}`: {err}", path.display()))
+                })?;
+                log_success!(
+                    "GeoIP",
+                    "Loaded `{}` ({})",
+                    path.display(),
+                    reader.metadata.database_type
+                );
+                Some(Geo { reader })
+            }
+            None => None,
+        };
+        let _ = GEO.set(geo);
+        Ok(())
+    }
+
+    pub fn get() -> Option<&'static Self> {
+        GEO.get().and_then(Option::as_ref)
+    }
+
+    /// ISO country and continent codes of `ip` (empty if not located)
+    fn locate(&self, ip: IpAddr) -> Vec<String> {
+        let Ok(country) = self.reader.lookup::<geoip2::Country>(ip) else {
+            return Vec::new();
+        };
+        let country_code = country.country.and_then(|country| country.iso_code);
+        let continent_code = country.continent.and_then(|continent| continent.code);
+        country_code
+            .into_iter()
+            .chain(continent_code)
+            .map(|code| code.to_uppercase())
+            .collect()
+    }
+}
+
+/// Region `region` serves a client located by `codes`
+fn serves(region: &str, codes: &[String]) -> bool {
+    let listed = GeoSettings::codes(region);
+    codes.iter().any(|code| {
+        code.eq_ignore_ascii_case(region)
+            || listed.iter().any(|listed| listed.eq_ignore_ascii_case(code))
+    })
+}
+
+/// Available `nodes` in a region serving `client`, or all `nodes` if
+/// geo-aware routing is disabled, the client cannot be located or no
+/// regional node is available
+pub fn prefer_local<'a>(client: IpAddr, nodes: Vec<&'a Arc<Connection>>) -> Vec<&'a Arc<Connection>> {
+    let Some(geo) = Geo::get() else {
+        return nodes;
+    };
+    let codes = geo.locate(client);
+    if codes.is_empty() {
+        return nodes;
+    }
+
+    let local = nodes
+        .iter()
+        .copied()
+        .filter(|connection| {
+            connection
+                .node()
+                .region
+                .as_deref()
+                .is_some_and(|region| serves(region, &codes))
+        })
+        .collect::<Vec<_>>();
+    if local.is_empty() {
+        nodes
+    } else {
+        local
+    }
+}
@@ -7,6 +7,11 @@ pub struct Group {
     pub transports: Vec<String>,
     pub services: Vec<Service>,
     pub network: HashMap<NetworkId, Vec<String>>,
+    #[serde(default, rename = "probe")]
+    pub probes: Vec<CustomProbe>,
+    pub region: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
 }
 
 impl std::fmt::Display for Group {
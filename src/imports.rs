@@ -1,28 +1,78 @@
+pub(crate) use crate::admin;
+pub use crate::annotations::*;
+pub use crate::api_keys::ApiKeys;
+pub use crate::archive::Archive;
+pub(crate) use crate::blocklist::{self, Blocklist};
 pub use crate::args::Args;
+pub use crate::backoff::Backoff;
+pub use crate::batch::{Batch, Operation, WEIGHT_RANGE};
+pub use crate::budget::*;
 pub use crate::cache::NoCacheHtml;
+pub(crate) use crate::certs;
+pub use crate::changes::*;
 pub use crate::config::*;
 pub use crate::connection::{Connection, Output};
+pub(crate) use crate::control;
 pub use crate::delegate::*;
+pub use crate::discovery::Discovery;
+pub(crate) use crate::divergence;
+pub use crate::drill::{DrillReport, Drills};
+pub(crate) use crate::dryrun::{self, Hypothetical};
+pub(crate) use crate::effective;
 pub use crate::error::Error;
 pub use crate::events::Events;
+pub use crate::experiment::{Experiment, ExperimentConfig};
+pub use crate::faults::*;
+pub(crate) use crate::fdguard::{self, FdGuard};
+pub use crate::features::Features;
+pub use crate::feed::Feed;
+pub(crate) use crate::federation::{self, Federation};
+pub(crate) use crate::feedback::{self, Reports};
+pub(crate) use crate::flaps;
+pub(crate) use crate::fleet::{self, Fleet};
+pub(crate) use crate::geo::{self, Geo};
 pub use crate::group::*;
+pub use crate::journal::*;
 pub use crate::log::*;
-pub use crate::monitor::Monitor;
+pub(crate) use crate::metrics;
+pub use crate::monitor::{Elected, Monitor};
 pub use crate::node::*;
+pub use crate::notify::Notifier;
+pub(crate) use crate::otel;
 pub use crate::params::PathParams;
 pub use crate::path::*;
+pub(crate) use crate::peers;
+pub use crate::pinning::{Pin, PinCheck, Pins};
+pub use crate::policy::*;
+pub use crate::pool::ProbePool;
+pub use crate::probes::CustomProbe;
 pub(crate) use crate::public;
+pub(crate) use crate::ratelimit;
+pub use crate::readiness::{Readiness, ReadinessReport, Stage};
+pub use crate::registry::Registry;
 pub use crate::resolver::Resolver;
 pub use crate::result::Result;
+pub use crate::rollout::{Rollout, RolloutReport};
 pub(crate) use crate::rpc;
 pub use crate::rpc::ClientT;
-pub use crate::rpc::{Caps, Connections};
+pub use crate::rpc::{Bandwidth, Caps, Connections};
+pub use crate::seasonality::Seasonality;
 pub use crate::services::Service;
+pub use crate::slo::Latency;
+pub use crate::stats::Stats;
 pub(crate) use crate::session::*;
+pub(crate) use crate::shard::{self, Shard};
+pub use crate::snapshot::Publisher;
+pub use crate::telemetry::Telemetry;
+pub(crate) use crate::tls;
 pub(crate) use crate::status;
+pub(crate) use crate::subscribe;
 pub use crate::tpl::Tpl;
+pub(crate) use crate::topology;
 pub use crate::transport::*;
+pub use crate::tunnel::Tunnel;
 pub use crate::utils::*;
+pub(crate) use crate::zone;
 
 pub use workflow_core::channel::*;
 pub use workflow_core::enums::Describe;
@@ -33,8 +83,8 @@ pub use workflow_rpc::encoding::Encoding as WrpcEncoding;
 
 pub use ahash::AHashMap;
 pub use arc_swap::{ArcSwap, ArcSwapOption};
+pub use async_trait::async_trait;
 pub use cliclack::{log, outro};
-pub use enum_dispatch::enum_dispatch;
 pub use futures::{select, FutureExt, StreamExt};
 pub use vecno_consensus_core::network::{NetworkId, NetworkType};
 pub use vecno_utils::hex::*;
@@ -51,4 +101,4 @@ pub use std::sync::atomic::{AtomicU64, Ordering};
 pub use std::sync::OnceLock;
 pub use std::sync::{Arc, Mutex, RwLock};
 pub use std::time::Duration;
-pub use xxhash_rust::xxh3::xxh3_64;
+pub use xxhash_rust::xxh3::{xxh3_64, xxh3_64_with_seed};
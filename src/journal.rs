@@ -0,0 +1,239 @@
+use crate::imports::*;
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::mpsc;
+
+const JOURNAL_CAPACITY: usize = 1024;
+// tail of the journal file read at startup
+const JOURNAL_TAIL: u64 = 1024 * 1024;
+
+/// Automatic decisions made by the resolver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JournalKind {
+    /// Connection reset after TTL expiration
+    TtlReset,
+    /// Connection reset after a half-open socket was detected
+    DeadPeerReset,
+    /// Connection bound to a delegate with the same system id
+    DelegateBound,
+    /// Invalid (looping or too deep) delegate chain broken up
+    DelegateChainInvalid,
+    /// Node excluded from elections (RPC failure or unsynced)
+    Demoted,
+    /// Node returned to elections
+    Restored,
+    /// Node failed the external probe
+    ProbeFailed,
+    /// Scheduled admin change activated
+    ChangeApplied,
+    /// Error budget severity changed
+    BudgetSeverity,
+    /// Version rollout gate opened or closed
+    RolloutGate,
+    /// Fault injected via the admin API
+    FaultInjected,
+    /// Node archived after a long offline period
+    Archived,
+    /// Archived node returned online
+    Unarchived,
+    /// Weekly summary of archived nodes
+    ArchiveSummary,
+    /// Node hostname resolved to addresses other than the pinned ones
+    PinViolation,
+    /// Recovery drill result
+    Drill,
+    /// Batch of admin operations applied
+    BatchApplied,
+    /// Reconnect classified as a node restart
+    NodeRestart,
+    /// Reconnect classified as a network partition
+    NetworkPartition,
+    /// Node configuration reloaded (SIGHUP, `/admin/reload`)
+    ConfigReloaded,
+    /// Node ranked last for violating the latency SLO
+    SloViolated,
+    /// Node meeting the latency SLO again
+    SloRecovered,
+    /// Spike of client failure reports for an available node
+    FeedbackSpike,
+    /// Synced node lagging behind the DAA score of its network
+    NodeLagging,
+    /// Lagging node caught up with its network
+    NodeCaughtUp,
+    /// Synced node whose peer count collapsed below its network
+    NodeIsolated,
+    /// Isolated node peered with its network again
+    NodePeered,
+    /// Policy experiment started or stopped
+    Experiment,
+    /// Node endpoint TLS certificate expiring within `[cert-expiry] window-days`
+    CertExpiring,
+    /// Expiring node endpoint TLS certificate renewed
+    CertRenewed,
+    /// Node reporting a version below the minimum node version
+    VersionRejected,
+    /// Node reporting the minimum node version (or newer) again
+    VersionAccepted,
+    /// Node connection shed near the file descriptor limit
+    FdShed,
+    /// Shed node connection restored
+    FdRestored,
+    /// Client address blocked by the blocklist
+    Blocked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct JournalEntry {
+    pub ts: u64,
+    pub kind: JournalKind,
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Append-only log of automatic actions, kept in memory (most recent
+/// entries) and appended to `~/.vecno-resolver/journal.jsonl` by a
+/// background writer thread. The file is rotated to `journal.1.jsonl`
+/// once it exceeds `[journal] max-mb`.
+pub struct Journal {
+    recent: Mutex<VecDeque<JournalEntry>>,
+    writer: mpsc::Sender<JournalEntry>,
+}
+
+impl Journal {
+    pub fn get() -> &'static Self {
+        static JOURNAL: OnceLock<Journal> = OnceLock::new();
+        JOURNAL.get_or_init(Journal::load)
+    }
+
+    /// Location of the persisted journal
+    pub fn path() -> PathBuf {
+        global_config_folder().join("journal.jsonl")
+    }
+
+    /// Location of the previous (rotated) journal
+    fn rotated_path() -> PathBuf {
+        global_config_folder().join("journal.1.jsonl")
+    }
+
+    fn load() -> Self {
+        let path = Self::path();
+        let mut recent = VecDeque::with_capacity(JOURNAL_CAPACITY);
+        match read_tail(&path, JOURNAL_TAIL) {
+            Ok(text) => {
+                for entry in text
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+                {
+                    if recent.len() == JOURNAL_CAPACITY {
+                        recent.pop_front();
+                    }
+                    recent.push_back(entry);
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => log_error!("Journal", "{}", Error::file(&path, err)),
+        }
+
+        let (writer, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("journal".to_string())
+            .spawn(move || {
+                for entry in receiver {
+                    if let Err(err) = append(&path, &entry) {
+                        log_error!("Journal", "{err}");
+                    }
+                }
+            })
+            .expect("unable to start the journal writer");
+
+        Self {
+            recent: Mutex::new(recent),
+            writer,
+        }
+    }
+
+    /// Persisted entries recorded at or after `since` (UNIX seconds),
+    /// from the rotated and the current journal, oldest first
+    pub fn read_since(since: u64) -> Result<Vec<JournalEntry>> {
+        let mut entries = Vec::new();
+        for path in [Self::rotated_path(), Self::path()] {
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(Error::file(&path, err)),
+            };
+            entries.extend(
+                text.lines()
+                    .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+                    .filter(|entry| entry.ts >= since),
+            );
+        }
+        Ok(entries)
+    }
+
+    /// Record an automatic action
+    pub fn record(kind: JournalKind, subject: impl Display, detail: Option<String>) {
+        let entry = JournalEntry {
+            ts: unix_now(),
+            kind,
+            subject: subject.to_string(),
+            detail,
+        };
+
+        let journal = Self::get();
+        if journal.writer.send(entry.clone()).is_err() {
+            log_error!("Journal", "the journal writer has stopped");
+        }
+
+        Notifier::dispatch(&entry);
+
+        let mut recent = journal.recent.lock().unwrap();
+        if recent.len() == JOURNAL_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(entry);
+    }
+
+    /// Most recent entries, newest last
+    pub fn recent(&self) -> Vec<JournalEntry> {
+        self.recent.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// Append `entry` to the journal at `path`, rotating the journal
+/// once it exceeds `[journal] max-mb` (run by the writer thread)
+fn append(path: &Path, entry: &JournalEntry) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| Error::file(path, err))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+
+    let len = file.metadata().map(|metadata| metadata.len()).unwrap_or_default();
+    if len > JournalSettings::max_bytes() {
+        drop(file);
+        let rotated = Journal::rotated_path();
+        std::fs::rename(path, &rotated).map_err(|err| Error::file(&rotated, err))?;
+    }
+    Ok(())
+}
+
+/// Last `len` bytes of the file at `path`, from the first complete line
+fn read_tail(path: &Path, len: u64) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let start = size.saturating_sub(len);
+    file.seek(SeekFrom::Start(start))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    if start > 0 {
+        // skip the partial line at the cut
+        let skip = data.iter().position(|&b| b == b'\n').map_or(data.len(), |i| i + 1);
+        data.drain(..skip);
+    }
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
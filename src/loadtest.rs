@@ -0,0 +1,242 @@
+//!
+//! Synthetic load testing (`vecno-resolver loadtest`): `concurrency`
+//! workers issue resolution requests against a target resolver for
+//! `duration-sec`, cycling through the configured resolution `paths`,
+//! and the latency percentiles, throughput and error rates are reported
+//! once the run completes. Defaults are taken from `[loadtest]`, the
+//! target defaults to the local `--listen` address.
+//!
+
+use crate::imports::*;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// `loadtest` subcommand options overriding `[loadtest]`
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub target: Option<String>,
+    pub concurrency: Option<usize>,
+    pub duration: Option<u64>,
+    pub paths: Vec<String>,
+    pub json: bool,
+}
+
+/// Outcome of a single request
+enum Outcome {
+    /// HTTP status of the response
+    Status(u16),
+    /// Request timed out
+    Timeout,
+    /// Connection or protocol error
+    Error,
+}
+
+/// Samples recorded by a worker
+#[derive(Default)]
+struct Samples {
+    latencies: Vec<Duration>,
+    statuses: BTreeMap<u16, u64>,
+    timeouts: u64,
+    errors: u64,
+}
+
+impl Samples {
+    fn record(&mut self, latency: Duration, outcome: Outcome) {
+        self.latencies.push(latency);
+        match outcome {
+            Outcome::Status(status) => *self.statuses.entry(status).or_default() += 1,
+            Outcome::Timeout => self.timeouts += 1,
+            Outcome::Error => self.errors += 1,
+        }
+    }
+
+    fn merge(&mut self, other: Samples) {
+        self.latencies.extend(other.latencies);
+        for (status, count) in other.statuses {
+            *self.statuses.entry(status).or_default() += count;
+        }
+        self.timeouts += other.timeouts;
+        self.errors += other.errors;
+    }
+}
+
+/// Load test results
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Report {
+    pub target: String,
+    pub concurrency: usize,
+    pub duration_sec: f64,
+    pub requests: u64,
+    pub requests_per_sec: f64,
+    /// responses by HTTP status
+    pub statuses: BTreeMap<u16, u64>,
+    pub timeouts: u64,
+    pub errors: u64,
+    /// requests not answered with a `2xx` status
+    pub error_rate: f64,
+    /// latency percentiles (milliseconds)
+    pub latency_ms: BTreeMap<&'static str, f64>,
+}
+
+impl Report {
+    fn new(target: String, concurrency: usize, elapsed: Duration, mut samples: Samples) -> Self {
+        samples.latencies.sort_unstable();
+        let requests = samples.latencies.len() as u64;
+        let successes = samples
+            .statuses
+            .iter()
+            .filter(|(status, _)| (200..300).contains(*status))
+            .map(|(_, count)| count)
+            .sum::<u64>();
+        let percentile = |percentile: f64| {
+            let rank = (percentile / 100.0 * samples.latencies.len() as f64).ceil() as usize;
+            samples
+                .latencies
+                .get(rank.saturating_sub(1))
+                .map(|latency| latency.as_secs_f64() * 1000.0)
+                .unwrap_or_default()
+        };
+        let latency_ms = BTreeMap::from([
+            ("p50", percentile(50.0)),
+            ("p90", percentile(90.0)),
+            ("p99", percentile(99.0)),
+            ("p99.9", percentile(99.9)),
+            ("max", percentile(100.0)),
+        ]);
+
+        Self {
+            target,
+            concurrency,
+            duration_sec: elapsed.as_secs_f64(),
+            requests,
+            requests_per_sec: requests as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            statuses: samples.statuses,
+            timeouts: samples.timeouts,
+            errors: samples.errors,
+            error_rate: if requests == 0 {
+                0.0
+            } else {
+                (requests - successes) as f64 / requests as f64
+            },
+            latency_ms,
+        }
+    }
+
+    fn print(&self) {
+        println!();
+        println!("Target:      {}", self.target);
+        println!("Concurrency: {}", self.concurrency);
+        println!("Duration:    {:.1}s", self.duration_sec);
+        println!("Requests:    {} ({:.1}/s)", self.requests, self.requests_per_sec);
+        for (status, count) in self.statuses.iter() {
+            println!("  HTTP {status}:  {count}");
+        }
+        if self.timeouts > 0 {
+            println!("  timeouts:  {}", self.timeouts);
+        }
+        if self.errors > 0 {
+            println!("  errors:    {}", self.errors);
+        }
+        println!("Error rate:  {:.2}%", self.error_rate * 100.0);
+        println!("Latency:");
+        for (name, latency) in self.latency_ms.iter() {
+            println!("  {name:<6}    {latency:.2}ms");
+        }
+        println!();
+    }
+}
+
+async fn worker(client: reqwest::Client, urls: Arc<Vec<String>>, offset: usize, until: Instant) -> Samples {
+    let mut samples = Samples::default();
+    // workers start on different paths to spread the initial requests
+    let mut index = offset;
+    while Instant::now() < until {
+        let url = &urls[index % urls.len()];
+        index += 1;
+
+        let start = Instant::now();
+        let outcome = match client.get(url).send().await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                // the response is complete once its body is read
+                match response.bytes().await {
+                    Ok(_) => Outcome::Status(status),
+                    Err(err) if err.is_timeout() => Outcome::Timeout,
+                    Err(_) => Outcome::Error,
+                }
+            }
+            Err(err) if err.is_timeout() => Outcome::Timeout,
+            Err(_) => Outcome::Error,
+        };
+        samples.record(start.elapsed(), outcome);
+    }
+    samples
+}
+
+/// Run a load test against the target resolver and report the results
+pub async fn run(args: &Args, options: Options) -> Result<()> {
+    let target = options
+        .target
+        .or_else(LoadTestSettings::target)
+        .unwrap_or_else(|| format!("http://{}", args.listen));
+    let target = target.trim_end_matches('/').to_string();
+    reqwest::Url::parse(&target)
+        .map_err(|err| Error::config(format!("Invalid load test target `{target}`: {err}")))?;
+
+    let concurrency = options
+        .concurrency
+        .unwrap_or_else(LoadTestSettings::concurrency)
+        .max(1);
+    let duration = options
+        .duration
+        .map(Duration::from_secs)
+        .unwrap_or_else(LoadTestSettings::duration);
+    let paths = if options.paths.is_empty() {
+        LoadTestSettings::paths()
+    } else {
+        options.paths
+    };
+    if paths.is_empty() {
+        return Err(Error::config("No load test paths configured"));
+    }
+    let urls = Arc::new(
+        paths
+            .iter()
+            .map(|path| format!("{target}/{}", path.trim_start_matches('/')))
+            .collect::<Vec<_>>(),
+    );
+
+    let client = reqwest::Client::builder()
+        .timeout(LoadTestSettings::timeout())
+        .pool_max_idle_per_host(concurrency)
+        .build()?;
+
+    log_info!(
+        "LoadTest",
+        "{concurrency} workers against `{target}` for {}s ({} paths)",
+        duration.as_secs(),
+        urls.len()
+    );
+
+    let start = Instant::now();
+    let until = start + duration;
+    let workers = (0..concurrency)
+        .map(|offset| tokio::spawn(worker(client.clone(), urls.clone(), offset, until)))
+        .collect::<Vec<_>>();
+    let mut samples = Samples::default();
+    for worker in futures::future::join_all(workers).await {
+        match worker {
+            Ok(worker) => samples.merge(worker),
+            Err(err) => log_error!("LoadTest", "worker failed: {err}"),
+        }
+    }
+
+    let report = Report::new(target, concurrency, start.elapsed(), samples);
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.print();
+    }
+    Ok(())
+}
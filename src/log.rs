@@ -1,21 +1,95 @@
 pub mod impls {
+    use super::buffer::{self, Level};
     use console::style;
     use std::fmt;
 
     pub fn log_info(source: &str, args: &fmt::Arguments<'_>) {
         println!("{:>12} {}", style(source).cyan().bold(), args);
+        buffer::publish(Level::Info, source, args);
     }
 
     pub fn log_success(source: &str, args: &fmt::Arguments<'_>) {
         println!("{:>12} {}", style(source).green().bold(), args);
+        buffer::publish(Level::Success, source, args);
     }
 
     pub fn log_warn(source: &str, args: &fmt::Arguments<'_>) {
         println!("{:>12} {}", style(source).yellow().bold(), args);
+        buffer::publish(Level::Warn, source, args);
     }
 
     pub fn log_error(source: &str, args: &fmt::Arguments<'_>) {
         println!("{:>12} {}", style(source).red().bold(), args);
+        buffer::publish(Level::Error, source, args);
+    }
+}
+
+/// In-memory buffer of recent log events with a broadcast channel
+/// for live subscribers (`/admin/logs/tail`).
+pub mod buffer {
+    use serde::{Deserialize, Serialize};
+    use std::collections::VecDeque;
+    use std::fmt;
+    use std::sync::{Mutex, OnceLock};
+    use tokio::sync::broadcast;
+
+    const CAPACITY: usize = 1024;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Level {
+        Info,
+        Success,
+        Warn,
+        Error,
+    }
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct LogEvent {
+        pub ts: u64,
+        pub level: Level,
+        pub source: String,
+        pub message: String,
+    }
+
+    struct Buffer {
+        recent: Mutex<VecDeque<LogEvent>>,
+        sender: broadcast::Sender<LogEvent>,
+    }
+
+    fn buffer() -> &'static Buffer {
+        static BUFFER: OnceLock<Buffer> = OnceLock::new();
+        BUFFER.get_or_init(|| Buffer {
+            recent: Mutex::new(VecDeque::with_capacity(CAPACITY)),
+            sender: broadcast::channel(CAPACITY).0,
+        })
+    }
+
+    pub fn publish(level: Level, source: &str, args: &fmt::Arguments<'_>) {
+        let event = LogEvent {
+            ts: crate::utils::unix_now_msec(),
+            level,
+            source: source.to_string(),
+            message: args.to_string(),
+        };
+
+        let buffer = buffer();
+        let mut recent = buffer.recent.lock().unwrap();
+        if recent.len() == CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(event.clone());
+        // no receivers is not an error
+        let _ = buffer.sender.send(event);
+    }
+
+    /// Most recent events (oldest first) and a receiver of the events
+    /// published after them. Both are taken under the buffer lock (which
+    /// `publish` holds while sending), so no event is missed or repeated.
+    pub fn subscribe() -> (Vec<LogEvent>, broadcast::Receiver<LogEvent>) {
+        let buffer = buffer();
+        let recent = buffer.recent.lock().unwrap();
+        (recent.iter().cloned().collect(), buffer.sender.subscribe())
     }
 }
 
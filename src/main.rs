@@ -1,28 +1,83 @@
+mod admin;
+mod annotations;
+mod api_keys;
+mod archive;
 mod args;
+mod backoff;
+mod batch;
+mod blocklist;
+mod blueprint;
+mod budget;
 mod cache;
+mod certs;
+mod changes;
 mod config;
 mod connection;
+mod control;
 mod delegate;
+mod discovery;
+mod divergence;
+mod drill;
+mod dryrun;
+mod effective;
 mod error;
 mod events;
+mod exit;
+mod experiment;
+mod faults;
+mod fdguard;
+mod features;
+mod feedback;
+mod flaps;
+mod fleet;
+mod feed;
+mod federation;
+mod geo;
 mod group;
 mod imports;
+mod journal;
+mod loadtest;
 mod log;
+mod metrics;
 mod monitor;
 mod node;
+mod notify;
+mod otel;
 mod panic;
 mod params;
 mod path;
+mod peers;
+mod pinning;
+mod policy;
+mod pool;
+mod probes;
 mod public;
+mod ratelimit;
+mod readiness;
+mod registry;
 mod resolver;
 mod result;
+mod rollout;
 mod rpc;
+mod schema;
+mod seasonality;
 mod services;
 mod session;
+mod shard;
+mod simulate;
+mod slo;
+mod stats;
+mod snapshot;
 mod status;
+mod subscribe;
+mod telemetry;
+mod tls;
 mod tpl;
+mod topology;
 mod transport;
+mod tunnel;
 mod utils;
+mod zone;
 
 use crate::config::*;
 use args::*;
@@ -37,7 +92,7 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 #[tokio::main]
 async fn main() {
     if let Err(error) = run().await {
-        match error {
+        match &error {
             Error::Config(s) => {
                 log_error!("Config", "{s}");
             }
@@ -45,14 +100,14 @@ async fn main() {
                 eprintln!("Error: {}", error);
             }
         }
-        std::process::exit(1);
+        error.exit_code().exit();
     }
 }
 
 async fn run() -> Result<()> {
     let args = Arc::new(Args::parse());
 
-    config::init(&args.user_config)?;
+    config::init(&args.user_config, &args.profile)?;
 
     match args.action {
         Action::Test => {
@@ -83,6 +138,25 @@ async fn run() -> Result<()> {
             config::update_global_config().await?;
             println!();
         }
+        Action::Init(ref options) => {
+            println!();
+            blueprint::run(options.clone())?;
+            println!();
+        }
+        Action::Schema { ref dir } => {
+            schema::run(dir.clone())?;
+        }
+        Action::LoadTest(ref options) => {
+            loadtest::run(&args, options.clone()).await?;
+        }
+        Action::Simulate { policy, journal } => {
+            let nodes = match user_config() {
+                Some(nodes) => nodes,
+                None => load_config()?,
+            };
+            let journal = journal.unwrap_or_else(journal::Journal::path);
+            simulate::run(nodes, &journal, policy)?;
+        }
         Action::Run => {
             if let Err(err) = try_set_fd_limit(Limits::fd()) {
                 log_error!("FD Limit", "{err}");
@@ -98,13 +172,58 @@ async fn run() -> Result<()> {
             println!();
             println!("Vecno RPC resolver v{}", env!("CARGO_PKG_VERSION"));
 
-            tracing_subscriber::fmt::init();
+            // connection and RPC spans are recorded at the debug level
+            let level = if args.trace {
+                tracing::Level::TRACE
+            } else {
+                tracing::Level::INFO
+            };
+            otel::init(&args, level)?;
+
+            effective::banner(&args);
 
             let resolver = Arc::new(Resolver::try_new(&args)?);
             resolver.init_http_server().await?;
             resolver.start().await?;
+
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+                let mut hangup = signal(SignalKind::hangup())?;
+                let resolver = resolver.clone();
+                tokio::spawn(async move {
+                    while hangup.recv().await.is_some() {
+                        log_info!("Config", "SIGHUP received, reloading node configuration");
+                        if let Err(err) = resolver.reload().await {
+                            log_error!("Config", "{err}");
+                        }
+                    }
+                });
+            }
+
+            if args.strict {
+                let resolver = resolver.clone();
+                tokio::spawn(async move {
+                    if let Err(err) = resolver.wait_for_available(StartupSettings::timeout()).await {
+                        log_error!("Startup", "{err}");
+                        err.exit_code().exit();
+                    }
+                });
+            }
+
+            if args.strict_startup {
+                resolver
+                    .wait_for_quorum(
+                        StartupSettings::timeout(),
+                        StartupSettings::min_nodes(),
+                        StartupSettings::min_percent(),
+                    )
+                    .await?;
+            }
+
             resolver.listen().await?;
             resolver.stop().await?;
+            otel::shutdown();
         }
     }
 
@@ -0,0 +1,479 @@
+//!
+//! HTTP request metrics (request counts by status class and latency
+//! histograms, labeled by endpoint and network) and node topology
+//! info metrics, exported in the Prometheus text format via `/metrics`
+//! and as plain JSON via `/stats.json` (nested, or with flat dotted keys
+//! using `?flat=true`).
+//!
+
+use crate::imports::*;
+use axum::{
+    body::Body,
+    extract::MatchedPath,
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use workflow_core::time::Instant;
+
+/// Latency histogram bucket bounds (seconds)
+const BUCKETS: [f64; 11] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Labels {
+    endpoint: String,
+    network: String,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(index) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Default)]
+struct Series {
+    // request count per status class (`2xx`, `4xx`, ...)
+    requests: BTreeMap<&'static str, u64>,
+    latency: Histogram,
+}
+
+#[derive(Default)]
+pub struct HttpMetrics {
+    series: Mutex<BTreeMap<Labels, Series>>,
+    // elected node responses per network
+    resolutions: Mutex<BTreeMap<String, u64>>,
+    // resolution requests rejected by the rate limiter
+    rate_limited: AtomicU64,
+}
+
+impl HttpMetrics {
+    pub fn get() -> &'static Self {
+        static METRICS: OnceLock<HttpMetrics> = OnceLock::new();
+        METRICS.get_or_init(HttpMetrics::default)
+    }
+
+    fn record(&self, labels: Labels, status: StatusCode, elapsed: Duration) {
+        let class = match status.as_u16() {
+            100..=199 => "1xx",
+            200..=299 => "2xx",
+            300..=399 => "3xx",
+            400..=499 => "4xx",
+            _ => "5xx",
+        };
+
+        let mut series = self.series.lock().unwrap();
+        let series = series.entry(labels).or_default();
+        *series.requests.entry(class).or_default() += 1;
+        series.latency.observe(elapsed.as_secs_f64());
+    }
+
+    /// Count a resolution served for `network`
+    pub fn resolution(&self, network: &NetworkId) {
+        *self
+            .resolutions
+            .lock()
+            .unwrap()
+            .entry(network.to_string())
+            .or_default() += 1;
+    }
+
+    /// Count a resolution request rejected by the rate limiter
+    pub fn rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut text = String::new();
+
+        let _ = writeln!(text, "# HELP resolver_resolutions_total Elected node responses served by network");
+        let _ = writeln!(text, "# TYPE resolver_resolutions_total counter");
+        for (network, count) in self.resolutions.lock().unwrap().iter() {
+            let _ = writeln!(text, "resolver_resolutions_total{{network=\"{network}\"}} {count}");
+        }
+
+        let _ = writeln!(text, "# HELP resolver_rate_limited_total Resolution requests rejected by the per-address rate limit");
+        let _ = writeln!(text, "# TYPE resolver_rate_limited_total counter");
+        let _ = writeln!(text, "resolver_rate_limited_total {}", self.rate_limited.load(Ordering::Relaxed));
+
+        let fd_guard = FdGuard::get();
+        let (open, limit) = fd_guard.usage();
+        let _ = writeln!(text, "# HELP resolver_open_fds Open file descriptors of the process");
+        let _ = writeln!(text, "# TYPE resolver_open_fds gauge");
+        let _ = writeln!(text, "resolver_open_fds {open}");
+        let _ = writeln!(text, "# HELP resolver_fd_limit File descriptor limit of the process");
+        let _ = writeln!(text, "# TYPE resolver_fd_limit gauge");
+        let _ = writeln!(text, "resolver_fd_limit {limit}");
+        let _ = writeln!(text, "# HELP resolver_shed_connections Node connections shed near the file descriptor limit");
+        let _ = writeln!(text, "# TYPE resolver_shed_connections gauge");
+        let _ = writeln!(text, "resolver_shed_connections {}", fd_guard.shed());
+
+        let _ = writeln!(text, "# HELP resolver_http_requests_total HTTP requests by endpoint, network and status class");
+        let _ = writeln!(text, "# TYPE resolver_http_requests_total counter");
+        for (labels, series) in series.iter() {
+            for (class, count) in series.requests.iter() {
+                let _ = writeln!(
+                    text,
+                    "resolver_http_requests_total{{endpoint=\"{}\",network=\"{}\",class=\"{class}\"}} {count}",
+                    labels.endpoint, labels.network
+                );
+            }
+        }
+
+        let _ = writeln!(text, "# HELP resolver_http_request_duration_seconds HTTP request latency by endpoint and network");
+        let _ = writeln!(text, "# TYPE resolver_http_request_duration_seconds histogram");
+        for (labels, series) in series.iter() {
+            let Labels { endpoint, network } = labels;
+            let histogram = &series.latency;
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    text,
+                    "resolver_http_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",network=\"{network}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                text,
+                "resolver_http_request_duration_seconds_bucket{{endpoint=\"{endpoint}\",network=\"{network}\",le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                text,
+                "resolver_http_request_duration_seconds_sum{{endpoint=\"{endpoint}\",network=\"{network}\"}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                text,
+                "resolver_http_request_duration_seconds_count{{endpoint=\"{endpoint}\",network=\"{network}\"}} {}",
+                histogram.count
+            );
+        }
+
+        text
+    }
+
+    fn resolutions_to_json(&self) -> Value {
+        json!(*self.resolutions.lock().unwrap())
+    }
+
+    /// Request counts and latency summary as `endpoint -> network -> series`
+    fn to_json(&self) -> Value {
+        let series = self.series.lock().unwrap();
+        let mut endpoints = serde_json::Map::new();
+        for (Labels { endpoint, network }, series) in series.iter() {
+            let endpoint = endpoints
+                .entry(endpoint.clone())
+                .or_insert_with(|| Value::Object(Default::default()));
+            endpoint[network.as_str()] = json!({
+                "requests": series.requests,
+                "latency": {
+                    "count": series.latency.count,
+                    "sum": series.latency.sum,
+                },
+            });
+        }
+        Value::Object(endpoints)
+    }
+}
+
+/// Middleware recording every routed request
+pub async fn track(request: Request<Body>, next: Next) -> Response {
+    let endpoint = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let network = network(&endpoint, request.uri().path());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    HttpMetrics::get().record(
+        Labels { endpoint, network },
+        response.status(),
+        start.elapsed(),
+    );
+    response
+}
+
+/// Value of the `:network` segment of the matched route, if any
+fn network(endpoint: &str, path: &str) -> String {
+    endpoint
+        .split('/')
+        .zip(path.split('/'))
+        .find(|(segment, _)| *segment == ":network")
+        .and_then(|(_, value)| NetworkId::from_str(value).ok())
+        .map(|network| network.to_string())
+        .unwrap_or_else(|| "none".to_string())
+}
+
+/// Info-style topology metrics (constant `1` carrying the node labels)
+/// and the client count per node, allowing dashboards to join numeric
+/// series with topology labels by `uid`.
+fn render_topology(connections: &[Arc<Connection>]) -> String {
+    let mut text = String::new();
+
+    let _ = writeln!(text, "# HELP resolver_node_info Node topology (system id, delegate, network, version, transport)");
+    let _ = writeln!(text, "# TYPE resolver_node_info gauge");
+    for connection in connections.iter() {
+        let node = connection.node();
+        let delegate = connection.delegate();
+        let version = connection
+            .caps()
+            .map(|caps| caps.version.clone())
+            .unwrap_or_default();
+        let _ = writeln!(
+            text,
+            "resolver_node_info{{uid=\"{}\",id=\"{}\",system_id=\"{:016x}\",delegate=\"{}\",network=\"{}\",version=\"{}\",transport=\"{}\"}} 1",
+            node.uid_as_str(),
+            node.short_id(),
+            connection.system_id(),
+            delegate.node().uid_as_str(),
+            node.network,
+            version.replace('"', ""),
+            node.transport_kind,
+        );
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_monitoring_bytes_total Monitoring traffic per node (JSON transports only)");
+    let _ = writeln!(text, "# TYPE resolver_node_monitoring_bytes_total counter");
+    let mut total = Bandwidth::default();
+    for connection in connections.iter() {
+        if let Some(Bandwidth { sent, received }) = connection.bandwidth() {
+            let uid = connection.node().uid_as_str();
+            let _ = writeln!(text, "resolver_node_monitoring_bytes_total{{uid=\"{uid}\",direction=\"sent\"}} {sent}");
+            let _ = writeln!(text, "resolver_node_monitoring_bytes_total{{uid=\"{uid}\",direction=\"received\"}} {received}");
+            total.sent += sent;
+            total.received += received;
+        }
+    }
+
+    let _ = writeln!(text, "# HELP resolver_monitoring_bytes_total Monitoring traffic of all nodes (JSON transports only)");
+    let _ = writeln!(text, "# TYPE resolver_monitoring_bytes_total counter");
+    let _ = writeln!(text, "resolver_monitoring_bytes_total{{direction=\"sent\"}} {}", total.sent);
+    let _ = writeln!(text, "resolver_monitoring_bytes_total{{direction=\"received\"}} {}", total.received);
+
+    let _ = writeln!(text, "# HELP resolver_node_clients Clients reported by the node's delegate");
+    let _ = writeln!(text, "# TYPE resolver_node_clients gauge");
+    for connection in connections.iter().filter(|connection| connection.is_delegate()) {
+        let _ = writeln!(
+            text,
+            "resolver_node_clients{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.state().clients
+        );
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_peers Peers reported by the node's delegate");
+    let _ = writeln!(text, "# TYPE resolver_node_peers gauge");
+    for connection in connections.iter().filter(|connection| connection.is_delegate()) {
+        let _ = writeln!(
+            text,
+            "resolver_node_peers{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.state().peers
+        );
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_isolated Node peer count collapsed below its network (1) or not (0)");
+    let _ = writeln!(text, "# TYPE resolver_node_isolated gauge");
+    for connection in connections.iter().filter(|connection| connection.is_delegate()) {
+        let _ = writeln!(
+            text,
+            "resolver_node_isolated{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.is_isolated() as u8
+        );
+    }
+
+    let health = peers::health(connections);
+    let _ = writeln!(text, "# HELP resolver_network_median_peers Median peer count of the synced nodes of the network");
+    let _ = writeln!(text, "# TYPE resolver_network_median_peers gauge");
+    for (network, health) in health.iter() {
+        let _ = writeln!(text, "resolver_network_median_peers{{network=\"{network}\"}} {}", health.median_peers);
+    }
+    let _ = writeln!(text, "# HELP resolver_network_isolated_nodes Synced nodes of the network flagged isolated");
+    let _ = writeln!(text, "# TYPE resolver_network_isolated_nodes gauge");
+    for (network, health) in health.iter() {
+        let _ = writeln!(text, "resolver_network_isolated_nodes{{network=\"{network}\"}} {}", health.isolated);
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_load Client load (clients / capacity) of the node's delegate");
+    let _ = writeln!(text, "# TYPE resolver_node_load gauge");
+    for connection in connections.iter().filter(|connection| connection.is_delegate()) {
+        if let Some(load) = connection.state().load() {
+            let _ = writeln!(
+                text,
+                "resolver_node_load{{uid=\"{}\"}} {load}",
+                connection.node().uid_as_str(),
+            );
+        }
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_online Node is connected and online (1) or not (0)");
+    let _ = writeln!(text, "# TYPE resolver_node_online gauge");
+    for connection in connections.iter() {
+        let _ = writeln!(
+            text,
+            "resolver_node_online{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.is_online() as u8
+        );
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_synced Node reports being synced (1) or not (0)");
+    let _ = writeln!(text, "# TYPE resolver_node_synced gauge");
+    for connection in connections.iter() {
+        let _ = writeln!(
+            text,
+            "resolver_node_synced{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.is_synced() as u8
+        );
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_reconnects_total Reconnects of the node connection");
+    let _ = writeln!(text, "# TYPE resolver_node_reconnects_total counter");
+    for connection in connections.iter() {
+        let _ = writeln!(
+            text,
+            "resolver_node_reconnects_total{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.reconnects()
+        );
+    }
+
+    let _ = writeln!(text, "# HELP resolver_node_rpc_failures_total Failed RPC calls (including retried attempts)");
+    let _ = writeln!(text, "# TYPE resolver_node_rpc_failures_total counter");
+    for connection in connections.iter() {
+        let _ = writeln!(
+            text,
+            "resolver_node_rpc_failures_total{{uid=\"{}\"}} {}",
+            connection.node().uid_as_str(),
+            connection.rpc_failures()
+        );
+    }
+
+    text
+}
+
+/// Topology counterpart of [`render_topology`] keyed by node uid
+fn topology_to_json(connections: &[Arc<Connection>]) -> Value {
+    let mut nodes = serde_json::Map::new();
+    let mut total = Bandwidth::default();
+    for connection in connections.iter() {
+        let node = connection.node();
+        let bandwidth = connection.bandwidth();
+        if let Some(Bandwidth { sent, received }) = bandwidth.as_ref() {
+            total.sent += sent;
+            total.received += received;
+        }
+        nodes.insert(
+            node.uid_as_str().to_string(),
+            json!({
+                "id": node.short_id(),
+                "system-id": format!("{:016x}", connection.system_id()),
+                "delegate": connection.delegate().node().uid_as_str(),
+                "network": node.network.to_string(),
+                "version": connection.caps().map(|caps| caps.version.clone()),
+                "transport": node.transport_kind.to_string(),
+                "clients": connection.is_delegate().then(|| connection.state().clients),
+                "peers": connection.is_delegate().then(|| connection.state().peers),
+                "load": connection.is_delegate().then(|| connection.state().load()).flatten(),
+                "online": connection.is_online(),
+                "synced": connection.is_synced(),
+                "isolated": connection.is_isolated(),
+                "reconnects": connection.reconnects(),
+                "rpc-failures": connection.rpc_failures(),
+                "monitoring-bytes": bandwidth,
+            }),
+        );
+    }
+
+    json!({
+        "nodes": nodes,
+        "monitoring-bytes": total,
+    })
+}
+
+/// Flatten nested objects into `a.b.c` keys
+fn flatten(prefix: &str, value: Value, flat: &mut serde_json::Map<String, Value>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&key, value, flat);
+            }
+        }
+        value => {
+            flat.insert(prefix.to_string(), value);
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsQuery {
+    #[serde(default)]
+    flat: bool,
+}
+
+pub async fn stats_handler(resolver: &Arc<Resolver>, query: StatsQuery) -> Response {
+    let mut stats = topology_to_json(&resolver.connections());
+    stats["http"] = HttpMetrics::get().to_json();
+    stats["resolutions"] = HttpMetrics::get().resolutions_to_json();
+    stats["rate-limited"] = json!(HttpMetrics::get().rate_limited.load(Ordering::Relaxed));
+    stats["peer-health"] = json!(peers::health(&resolver.connections()));
+    let (open, limit) = FdGuard::get().usage();
+    stats["fd"] = json!({
+        "open" : open,
+        "limit" : limit,
+        "shed" : FdGuard::get().shed(),
+        "exhausted" : FdGuard::get().is_exhausted(),
+    });
+
+    let stats = if query.flat {
+        let mut flat = serde_json::Map::new();
+        flatten("", stats, &mut flat);
+        Value::Object(flat)
+    } else {
+        stats
+    };
+
+    (
+        [(header::CONTENT_TYPE, "application/json")],
+        stats.to_string(),
+    )
+        .into_response()
+}
+
+pub async fn metrics_handler(resolver: &Arc<Resolver>) -> Response {
+    let mut text = HttpMetrics::get().render();
+    text.push_str(&render_topology(&resolver.connections()));
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        text,
+    )
+        .into_response()
+}
@@ -1,13 +1,50 @@
 use crate::imports::*;
+use axum::body::Bytes;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::sync::{PoisonError, RwLockReadGuard, RwLockWriteGuard};
+
+/// Election result: JSON response and the
+/// (uid, url) of the shadow policy pick if any.
+pub struct Elected {
+    pub json: Bytes,
+    pub shadow: Option<(String, String)>,
+}
+
+/// Node entry of the election dry run (see [`Monitor::candidates`])
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Candidate {
+    pub rank: usize,
+    pub uid: String,
+    pub id: String,
+    pub url: String,
+    pub score: u64,
+    pub weight: u64,
+    pub clients: u64,
+    pub excluded: Vec<&'static str>,
+}
 
 /// Monitor receives updates from [Connection] monitoring tasks
 /// and updates the descriptors for each [Params] based on the
 /// connection store (number of connections * bias).
 pub struct Monitor {
     args: Arc<Args>,
-    connections: RwLock<AHashMap<PathParams, Vec<Arc<Connection>>>>,
-    delegates: RwLock<AHashMap<Delegate, Arc<Connection>>>,
-    sorts: AHashMap<PathParams, AtomicBool>,
+    shards: Vec<Arc<Shard>>,
+    // serializes delegate registration across shards
+    delegate_lock: Mutex<()>,
+    nodes: Mutex<Vec<Arc<Node>>>,
+    changes: Mutex<Changes>,
+    sync_lock: tokio::sync::Mutex<()>,
+    shadow: Option<Shadow>,
+    budget: ErrorBudget,
+    // process-wide, shared by the monitors of all services
+    archive: &'static Archive,
+    stats: &'static Stats,
+    pins: &'static Pins,
+    experiment: &'static Experiment,
+    drills: Drills,
+    rollout: Rollout,
     channel: Channel<PathParams>,
     shutdown_ctl: DuplexChannel<()>,
     service: Service,
@@ -24,15 +61,25 @@ impl fmt::Debug for Monitor {
 
 impl Monitor {
     pub fn new(args: &Arc<Args>, service: Service) -> Self {
-        let sorts = PathParams::iter_tls_any()
-            .map(|params| (params, AtomicBool::new(false)))
+        let shards = (0..SortSettings::shards())
+            .map(|_| Arc::new(Shard::default()))
             .collect();
 
         Self {
             args: args.clone(),
-            connections: Default::default(),
-            delegates: Default::default(),
-            sorts,
+            shards,
+            delegate_lock: Default::default(),
+            nodes: Default::default(),
+            changes: Mutex::new(Changes::load(service)),
+            sync_lock: Default::default(),
+            shadow: PolicySettings::shadow().map(Shadow::new),
+            budget: Default::default(),
+            archive: Archive::get(),
+            stats: Stats::get(),
+            pins: Pins::get(),
+            experiment: Experiment::get(),
+            drills: Default::default(),
+            rollout: Default::default(),
             channel: Channel::unbounded(),
             shutdown_ctl: DuplexChannel::oneshot(),
             service,
@@ -43,18 +90,74 @@ impl Monitor {
         self.args.verbose
     }
 
-    pub fn delegates(&self) -> &RwLock<AHashMap<Delegate, Arc<Connection>>> {
-        &self.delegates
+    fn shard(&self, uid: u64) -> &Arc<Shard> {
+        &self.shards[Shard::index(uid, self.shards.len())]
     }
 
+    /// Register `connection` as the delegate of `key` unless a delegate
+    /// is already registered (in any shard), returning the existing delegate
+    pub fn register_delegate(&self, key: Delegate, connection: &Arc<Connection>) -> Option<Arc<Connection>> {
+        let _lock = self.delegate_lock.lock().unwrap_or_else(|poisoned| {
+            self.delegate_lock.clear_poison();
+            self.recover_delegation("registration", poisoned)
+        });
+        let existing = self.shards.iter().find_map(|shard| {
+            let delegates = shard.delegates().read().unwrap_or_else(|poisoned| {
+                shard.delegates().clear_poison();
+                self.recover_delegation("delegates", poisoned)
+            });
+            delegates.get(&key).cloned()
+        });
+        if existing.is_none() {
+            let shard = self.shard(connection.node().uid());
+            let mut delegates = shard.delegates().write().unwrap_or_else(|poisoned| {
+                shard.delegates().clear_poison();
+                self.recover_delegation("delegates", poisoned)
+            });
+            delegates.insert(key, connection.clone());
+        }
+        existing
+    }
+
+    /// Recover a delegation lock poisoned by a panic while it was held.
+    /// Delegates are only ever inserted whole, so the guarded map stays
+    /// consistent and delegation keeps working instead of failing every
+    /// subsequent registration.
+    fn recover_delegation<G>(&self, lock: &str, poisoned: PoisonError<G>) -> G {
+        log_error!("Delegation", "recovered `{lock}` lock poisoned by a panic");
+        Readiness::get().delegation_recovered();
+        poisoned.into_inner()
+    }
+
+    /// Node lists of all shards
     pub fn connections(&self) -> AHashMap<PathParams, Vec<Arc<Connection>>> {
-        self.connections.read().unwrap().clone()
+        let mut connections = AHashMap::<PathParams, Vec<Arc<Connection>>>::new();
+        for shard in self.shards.iter() {
+            for (params, list) in shard.connections().read().unwrap().iter() {
+                connections.entry(*params).or_default().extend(list.iter().cloned());
+            }
+        }
+        connections
     }
 
     pub fn to_vec(&self) -> Vec<Arc<Connection>> {
-        PathParams::iter_tls_strict()
-            .filter_map(|params| self.connections.read().unwrap().get(&params).cloned())
-            .flatten()
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                let connections = shard.connections().read().unwrap();
+                PathParams::iter_tls_strict()
+                    .filter_map(|params| connections.get(&params).cloned())
+                    .flatten()
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Read guards of the node lists of all shards
+    fn read_shards(&self) -> Vec<RwLockReadGuard<'_, AHashMap<PathParams, Vec<Arc<Connection>>>>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.connections().read().unwrap())
             .collect()
     }
 
@@ -73,6 +176,72 @@ impl Monitor {
             }
         });
 
+        *self.nodes.lock().unwrap() = nodes;
+        self.sync_nodes().await
+    }
+
+    /// Schedule an admin change to the node list, returns the change id.
+    pub fn schedule_change(&self, change: ScheduledChange) -> Result<String> {
+        self.changes.lock().unwrap().schedule(change)
+    }
+
+    /// Apply a node list change immediately, starting or
+    /// stopping the corresponding connections.
+    pub async fn apply_change(self: &Arc<Self>, change: Change) -> Result<()> {
+        let activated = {
+            let mut changes = self.changes.lock().unwrap();
+            changes.schedule(ScheduledChange {
+                id: String::new(),
+                activate_at: None,
+                change,
+            })?;
+            changes.activate(unix_now())
+        };
+        self.apply_weights(&activated);
+        self.sync_nodes().await
+    }
+
+    /// Apply activated weight changes to the running connections
+    /// (connections created later take the weight from the node list)
+    fn apply_weights(&self, activated: &[Change]) {
+        for change in activated {
+            if let Change::Weight { uid, weight } = change {
+                let uid = parse_uid(uid).ok();
+                self.to_vec()
+                    .iter()
+                    .filter(|connection| Some(connection.node().uid()) == uid)
+                    .for_each(|connection| connection.set_weight(*weight));
+            }
+        }
+    }
+
+    pub fn cancel_change(&self, id: &str) -> bool {
+        self.changes.lock().unwrap().cancel(id)
+    }
+
+    pub fn pending_changes(&self) -> Vec<ScheduledChange> {
+        self.changes.lock().unwrap().pending()
+    }
+
+    /// Reconcile node connections against the configured node
+    /// list with activated admin changes applied on top.
+    async fn sync_nodes(self: &Arc<Self>) -> Result<()> {
+        let _lock = self.sync_lock.lock().await;
+
+        let nodes = {
+            let config = self.nodes.lock().unwrap();
+            self.changes.lock().unwrap().apply(&config)
+        };
+
+        // gRPC node lists only exist with the `grpc` feature
+        #[cfg(not(feature = "grpc"))]
+        if let Some(node) = nodes.iter().find(|node| node.transport_kind == TransportKind::Grpc) {
+            return Err(Error::config(format!(
+                "gRPC node `{}` requires the resolver to be built with `--features grpc`",
+                node.address
+            )));
+        }
+
         let mut connections = self.connections();
 
         let mut tls_any_created = Vec::new();
@@ -120,7 +289,7 @@ impl Monitor {
         tls_any_removed.into_iter().for_each(|connection| {
             let params = connection.params().to_tls(TlsKind::Any);
             let list = connections.entry(params).or_default();
-            list.retain(|connection| connection.node() != connection.node());
+            list.retain(|c| c.node() != connection.node());
         });
 
         // create connections in TlsAny list
@@ -145,12 +314,19 @@ impl Monitor {
                 }),
         );
 
-        for (_network_uid, transport_map) in targets.iter() {
+        for (_network_uid, transport_map) in targets
+            .iter()
+            .filter(|_| !self.args.no_delegation)
+        {
             if let Some(wrpc_borsh) = transport_map.get(&TransportKind::WrpcBorsh) {
-                if let Some(wrpc_json) = transport_map.get(&TransportKind::WrpcJson) {
-                    wrpc_json.bind_delegate(Some(wrpc_borsh.clone()));
-                } else if let Some(grpc) = transport_map.get(&TransportKind::Grpc) {
-                    grpc.bind_delegate(Some(wrpc_borsh.clone()));
+                // the wRPC JSON and gRPC endpoints of a node share its delegate
+                let delegators = [TransportKind::WrpcJson, TransportKind::Grpc]
+                    .into_iter()
+                    .filter_map(|transport_kind| transport_map.get(&transport_kind));
+                for delegator in delegators {
+                    if let Err(err) = delegator.bind_delegate(Some(wrpc_borsh.clone())) {
+                        log_error!("Delegate", "{err}");
+                    }
                 }
             }
         }
@@ -172,12 +348,31 @@ impl Monitor {
             }
         }
 
-        *self.connections.write().unwrap() = connections;
+        // partition the node lists across the shards
+        let mut partitions = (0..self.shards.len())
+            .map(|_| AHashMap::<PathParams, Vec<Arc<Connection>>>::new())
+            .collect::<Vec<_>>();
+        for (params, list) in connections {
+            for partition in partitions.iter_mut() {
+                partition.entry(params).or_default();
+            }
+            for connection in list {
+                let index = Shard::index(connection.node().uid(), self.shards.len());
+                partitions[index].entry(params).or_default().push(connection);
+            }
+        }
+        for (shard, partition) in self.shards.iter().zip(partitions) {
+            *shard.connections().write().unwrap() = partition;
+        }
 
         Ok(())
     }
 
     pub async fn start(self: &Arc<Self>) -> Result<()> {
+        for shard in self.shards.iter() {
+            shard.start();
+        }
+
         let this = self.clone();
         spawn(async move {
             if let Err(error) = this.task().await {
@@ -189,6 +384,13 @@ impl Monitor {
     }
 
     pub async fn stop(&self) -> Result<()> {
+        // close the node connections before the shards they report to
+        let connections = self.to_vec();
+        futures::future::join_all(connections.iter().map(|connection| connection.stop())).await;
+
+        for shard in self.shards.iter() {
+            shard.stop().await?;
+        }
         self.shutdown_ctl
             .signal(())
             .await
@@ -201,24 +403,44 @@ impl Monitor {
         let shutdown_ctl_receiver = self.shutdown_ctl.request.receiver.clone();
         let shutdown_ctl_sender = self.shutdown_ctl.response.sender.clone();
 
-        let mut interval = workflow_core::task::interval(Duration::from_millis(300));
+        let mut changes = workflow_core::task::interval(Duration::from_secs(1));
+        let mut archive = workflow_core::task::interval(Duration::from_secs(7 * 24 * 60 * 60));
+        let mut drill = workflow_core::task::interval(DrillSettings::interval());
 
         loop {
             select! {
 
-                _ = interval.next().fuse() => {
-                    for (params, sort) in self.sorts.iter() {
-                        if sort.load(Ordering::Relaxed) {
-                            sort.store(false, Ordering::Relaxed);
-
-                            let mut connections = self.connections.write().unwrap();
-                            if let Some(nodes) = connections.get_mut(params) {
-                                nodes.sort_by_key(|connection| connection.score());
-                            }
+                _ = changes.next().fuse() => {
+                    self.sample_budget();
+                    let connections = self.to_vec();
+                    self.rollout.update(&connections);
+                    self.archive.update(&connections);
+                    self.stats.update(&connections);
+                    divergence::update(&connections);
+                    peers::update(&connections);
+
+                    let activated = self.changes.lock().unwrap().activate(unix_now());
+                    if !activated.is_empty() {
+                        self.apply_weights(&activated);
+                        if let Err(err) = self.sync_nodes().await {
+                            log_error!("Admin", "Unable to apply changes: {err}");
                         }
                     }
                 }
 
+                _ = archive.next().fuse() => {
+                    self.archive.summary(&self.to_vec());
+                }
+
+                _ = drill.next().fuse() => {
+                    if DrillSettings::enable() {
+                        let this = self.clone();
+                        spawn(async move {
+                            this.drills.run(&this).await;
+                        });
+                    }
+                }
+
                 _ = shutdown_ctl_receiver.recv().fuse() => {
                     break;
                 },
@@ -226,36 +448,51 @@ impl Monitor {
             }
         }
 
+        if StatsSettings::enable() {
+            self.stats.persist();
+        }
+
         shutdown_ctl_sender.send(()).await.unwrap();
 
         Ok(())
     }
 
-    pub fn schedule_sort(&self, params_tls_kind: &PathParams) {
-        self.sorts
-            .get(params_tls_kind)
-            .unwrap()
-            .store(true, Ordering::Relaxed);
-
-        let params_tls_any = params_tls_kind.to_tls(TlsKind::Any);
-        self.sorts
-            .get(&params_tls_any)
-            .unwrap()
-            .store(true, Ordering::Relaxed);
+    /// Schedule a re-sort of the `params` node lists in the shard of node `uid`
+    pub fn schedule_sort(&self, uid: u64, params_tls_kind: &PathParams) {
+        self.shard(uid).schedule_sort(params_tls_kind);
     }
 
     // /// Get JSON string representing node information (id, url, provider, link)
-    pub fn election(&self, params: &PathParams) -> Option<String> {
+    /// Elect a node serving `role` for `params`, `reserved` elections
+    /// (requests carrying an API key) may use reserved node capacity.
+    /// Elections with a `sticky` key map the key to the same available
+    /// node across requests (see [`select_sticky`]) instead of applying
+    /// the election policy. With a running policy experiment, the
+    /// policy is the one of the arm `client` is assigned to, otherwise
+    /// the `--strategy` selects (see [`Strategy::select`]).
+    pub fn election(
+        &self,
+        params: &PathParams,
+        reserved: bool,
+        previous: Option<&str>,
+        role: Role,
+        sticky: Option<&str>,
+        client: IpAddr,
+    ) -> Option<Elected> {
         if self.verbose() {
             println!("election for: {}", params);
         }
 
-        let connections = self.connections.read().unwrap();
+        let span = tracing::debug_span!("election", uid = tracing::field::Empty);
+        let _enter = span.enter();
+
+        let guards = self.read_shards();
+        let merged = merge(&guards, params);
 
         const DELEGATES_ONLY: bool = true;
 
         if self.verbose() {
-            if let Some(connections) = connections.get(params) {
+            if let Some(connections) = merged.as_ref() {
                 connections
                     .iter()
                     .filter(|connection| {
@@ -273,44 +510,224 @@ impl Monitor {
             }
         }
 
-        let connections = connections
-            .get(params)?
+        let all = merged?;
+        let connections = all
             .iter()
+            .copied()
+            .filter(|connection| connection.node().has_role(role))
             .filter(|connection| {
                 if DELEGATES_ONLY {
-                    connection.is_delegate() && connection.is_available()
+                    connection.is_delegate() && connection.is_available_for(reserved)
                 } else {
-                    connection.is_available()
+                    connection.is_available_for(reserved)
                 }
             })
             .collect::<Vec<_>>();
-
-        if !connections.is_empty() {
-            let node = select_with_weighted_rng(connections);
-            serde_json::to_string(&Output::from(node)).ok()
+        let connections = self.rollout.filter(&params.network, connections);
+        let connections = geo::prefer_local(client, connections);
+
+        let node = match sticky {
+            Some(key) => select_sticky(key, &connections)?,
+            None => match self.experiment.assign(client) {
+                Some((arm, policy)) => {
+                    let node = policy.select(&connections)?;
+                    self.experiment.elected(arm, node);
+                    node
+                }
+                None => self.args.strategy.select(PolicySettings::active(), &connections)?,
+            },
+        };
+        span.record("uid", node.node().uid_as_str());
+        let shadow = self
+            .shadow
+            .as_ref()
+            .filter(|_| Features::is_enabled(crate::features::SHADOW_POLICY))
+            .and_then(|shadow| shadow.evaluate(params, node, &connections))
+            .map(|shadow| {
+                (
+                    shadow.node().uid_as_str().to_string(),
+                    shadow.address().to_string(),
+                )
+            });
+
+        let switch_reason = previous
+            .filter(|_| HintSettings::switch_reason())
+            .and_then(|id| all.iter().copied().find(|connection| connection.node().is_id(id)))
+            .filter(|previous| !Arc::ptr_eq(previous, node))
+            .and_then(|previous| previous.switch_reason(reserved));
+
+        let json = if let Some(switch_reason) = switch_reason {
+            let mut output = Output::from(node);
+            output.switch_reason = Some(switch_reason);
+            Bytes::from(serde_json::to_vec(&output).ok()?)
         } else {
-            None
+            // common case: shared pre-serialized response of the node
+            node.output_json()
+        };
+        Some(Elected { json, shadow })
+    }
+
+    /// Dry run of an election: all nodes for `params` in ranking order
+    /// with their score and the election filters each node fails.
+    pub fn candidates(&self, params: &PathParams) -> Option<Vec<Candidate>> {
+        let guards = self.read_shards();
+        let candidates = merge(&guards, params)?
+            .into_iter()
+            .enumerate()
+            .map(|(rank, connection)| Candidate {
+                rank,
+                uid: connection.node().uid_as_str().to_string(),
+                id: connection.node().short_id().to_string(),
+                url: connection.address().to_string(),
+                score: connection.score(),
+                weight: connection.weight(),
+                clients: connection.delegate().state().clients,
+                excluded: connection.exclusions(),
+            })
+            .collect();
+        Some(candidates)
+    }
+
+    /// Best available node per declared region (nodes without a
+    /// declared region are grouped under `default`), serialized as
+    /// a JSON object keyed by region.
+    pub fn regions(&self, params: &PathParams) -> Option<String> {
+        let guards = self.read_shards();
+        // node lists are kept sorted by score, the first
+        // available node of each region is its best candidate
+        let mut regions = BTreeMap::new();
+        for connection in merge(&guards, params)?
+            .into_iter()
+            .filter(|connection| connection.is_delegate() && connection.is_available_for(false))
+        {
+            let region = connection.node().region.as_deref().unwrap_or("default");
+            regions
+                .entry(region)
+                .or_insert_with(|| Output::from(connection));
         }
+        (!regions.is_empty())
+            .then(|| serde_json::to_string(&regions).ok())
+            .flatten()
     }
-}
 
-fn select_with_weighted_rng(nodes: Vec<&Arc<Connection>>) -> &Arc<Connection> {
-    // Calculate total weight based on the position in the sorted list
-    let total_weight: usize = nodes.iter().enumerate().map(|(i, _)| nodes.len() - i).sum();
+    /// Number of available nodes per role, serialized as
+    /// a JSON object keyed by role (roles without nodes are omitted).
+    pub fn roles(&self, params: &PathParams) -> Option<String> {
+        let guards = self.read_shards();
+        let connections = merge(&guards, params)?
+            .into_iter()
+            .filter(|connection| connection.is_delegate())
+            .collect::<Vec<_>>();
 
-    // Generate a random number within the range of total_weight
-    let mut rng = rand::thread_rng();
-    let mut rand_weight = rng.gen_range(0..total_weight);
+        let mut roles = BTreeMap::new();
+        for role in Role::ALL {
+            let nodes = connections
+                .iter()
+                .filter(|connection| connection.node().has_role(role));
+            let mut total = 0;
+            let mut available = 0;
+            for connection in nodes {
+                total += 1;
+                if connection.is_available_for(false) {
+                    available += 1;
+                }
+            }
+            if total > 0 {
+                roles.insert(
+                    role.to_string(),
+                    serde_json::json!({ "nodes" : total, "available" : available }),
+                );
+            }
+        }
+        serde_json::to_string(&roles).ok()
+    }
+
+    /// Write-lock the node lists of all shards (blocking elections)
+    pub fn write_shards(&self) -> Vec<RwLockWriteGuard<'_, AHashMap<PathParams, Vec<Arc<Connection>>>>> {
+        self.shards
+            .iter()
+            .map(|shard| shard.connections().write().unwrap())
+            .collect()
+    }
 
-    // Select a node based on the random weight
-    for (i, node) in nodes.iter().enumerate() {
-        let weight = nodes.len() - i;
-        if rand_weight < weight {
-            return node;
+    pub fn drills(&self) -> &Drills {
+        &self.drills
+    }
+
+    /// Find a connection by node uid (hex) or short id
+    pub fn find(&self, id: &str) -> Option<Arc<Connection>> {
+        self.to_vec()
+            .into_iter()
+            .find(|connection| connection.node().is_id(id))
+    }
+
+    /// Nodes for `params` are available, but only within reserved capacity
+    pub fn is_reserved_only(&self, params: &PathParams) -> bool {
+        let guards = self.read_shards();
+        merge(&guards, params).is_some_and(|connections| {
+            let available = connections
+                .iter()
+                .filter(|connection| connection.is_delegate() && connection.is_available())
+                .collect::<Vec<_>>();
+            !available.is_empty() && !available.iter().any(|connection| connection.is_available_for(false))
+        })
+    }
+
+    fn sample_budget(&self) {
+        let connections = self.to_vec();
+        for network in crate::params::NETWORKS.iter() {
+            let available = connections.iter().any(|connection| {
+                connection.network_id() == *network
+                    && connection.is_delegate()
+                    && connection.is_synced()
+                    && connection.is_available()
+            });
+            self.budget.sample(*network, available);
         }
-        rand_weight -= weight;
     }
 
-    // Fallback in case of error (shouldn't happen)
-    nodes[0]
+    pub fn budget(&self) -> Vec<BudgetReport> {
+        self.budget.reports()
+    }
+
+    pub fn pins(&self) -> &Pins {
+        self.pins
+    }
+
+    pub fn archive(&self) -> &Archive {
+        self.archive
+    }
+
+    pub fn service(&self) -> Service {
+        self.service
+    }
+
+    pub fn stats(&self) -> &Stats {
+        self.stats
+    }
+
+    pub fn rollout(&self) -> Vec<RolloutReport> {
+        self.rollout.reports()
+    }
+
+    pub fn shadow(&self) -> Option<ShadowReport> {
+        self.shadow.as_ref().map(Shadow::report)
+    }
+
+    pub fn experiment(&self) -> &Experiment {
+        self.experiment
+    }
+}
+
+/// Node lists of `params` of all shards merged by score,
+/// `None` if no shard holds a node list for `params`
+fn merge<'a>(
+    guards: &'a [RwLockReadGuard<'_, AHashMap<PathParams, Vec<Arc<Connection>>>>],
+    params: &PathParams,
+) -> Option<Vec<&'a Arc<Connection>>> {
+    let lists = guards
+        .iter()
+        .filter_map(|connections| connections.get(params))
+        .collect::<Vec<_>>();
+    (!lists.is_empty()).then(|| shard::merge(lists))
 }
@@ -1,5 +1,46 @@
 use crate::imports::*;
 
+/// Node role, selected by clients with `?role=` (nodes without
+/// declared roles serve `public-api`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    PublicApi,
+    Mining,
+    Archive,
+    IndexerBackend,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::PublicApi,
+        Role::Mining,
+        Role::Archive,
+        Role::IndexerBackend,
+    ];
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Role::PublicApi => "public-api",
+            Role::Mining => "mining",
+            Role::Archive => "archive",
+            Role::IndexerBackend => "indexer-backend",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Declared roles, `public-api` if none are declared
+fn roles_or_default(roles: Vec<Role>) -> Vec<Role> {
+    if roles.is_empty() {
+        vec![Role::PublicApi]
+    } else {
+        roles
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NodeConfig {
     // service type
@@ -8,51 +49,126 @@ pub struct NodeConfig {
     pub address: Option<String>,
     // is TLS enabled (address wss:// or ws:// ?)
     pub tls: bool,
-    // protocol+encoding
-    #[serde(rename = "transport-type")]
-    pub transport_kind: TransportKind,
+    // protocol+encoding (discovered if not specified)
+    #[serde(default, rename = "transport-type")]
+    pub transport_kind: Option<TransportKind>,
     // node network id
     pub network: NetworkId,
     // entry is enabled
     pub enable: Option<bool>,
     // domain name (abc.example.com)
     pub fqdn: String,
+    // custom RPC probes
+    #[serde(default, rename = "probe")]
+    pub probes: Vec<CustomProbe>,
+    // declared region (e.g. `eu-west`)
+    pub region: Option<String>,
+    // share of elections relative to other nodes (percent, default 100)
+    pub weight: Option<u64>,
+    // roles served by the node (`public-api` if empty)
+    #[serde(default)]
+    pub roles: Vec<Role>,
+    // state poll interval override in seconds (`[sync] poll-sec`)
+    #[serde(rename = "poll-interval")]
+    pub poll_interval: Option<f64>,
+    // ping interval override in seconds (`[sync] ping-sec`)
+    #[serde(rename = "ping-interval")]
+    pub ping_interval: Option<f64>,
+    // connection ttl override in seconds (`[ttl] period-*`)
+    pub ttl: Option<f64>,
+}
+
+impl NodeConfig {
+    /// Entries describing the same node endpoint
+    pub fn is_same(&self, other: &NodeConfig) -> bool {
+        self.fqdn == other.fqdn
+            && self.address == other.address
+            && self.network == other.network
+            && self.transport_kind == other.transport_kind
+            && self.tls == other.tls
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("poll-interval", self.poll_interval),
+            ("ping-interval", self.ping_interval),
+            ("ttl", self.ttl),
+        ] {
+            if let Some(value) = value.filter(|value| !value.is_finite() || *value <= 0.0) {
+                return Err(Error::config(format!(
+                    "{name} {value} of `{}` must be a positive number of seconds",
+                    self.address.as_deref().unwrap_or(&self.fqdn)
+                )));
+            }
+        }
+
+        match self.weight {
+            Some(weight) if !WEIGHT_RANGE.contains(&weight) => Err(Error::config(format!(
+                "weight {weight} of `{}` is out of range ({}..={})",
+                self.address.as_deref().unwrap_or(&self.fqdn),
+                WEIGHT_RANGE.start(),
+                WEIGHT_RANGE.end()
+            ))),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl From<NodeConfig> for Node {
     fn from(config: NodeConfig) -> Self {
+        // unspecified transport: the previously discovered transport
+        // or the first of `[discovery] order` pending discovery
+        let (transport_kind, discovery) = match config.transport_kind {
+            Some(transport_kind) => (transport_kind, None),
+            None => match Discovery::get().lookup(&config) {
+                Some(transport_kind) => (transport_kind, None),
+                None => (DiscoverySettings::order()[0], Some(config.clone())),
+            },
+        };
+
         let NodeConfig {
             service,
             address,
             tls,
-            transport_kind,
             network,
             fqdn,
+            probes,
+            region,
+            weight,
+            roles,
+            poll_interval,
+            ping_interval,
+            ttl,
             ..
         } = config;
 
-        let ws_proto = if tls { "wss://" } else { "ws://" };
-
         let address = address.unwrap_or_else(|| {
+            // gRPC endpoints serve a single service and network (no path)
+            let template = match (transport_kind, tls) {
+                (TransportKind::Grpc, true) => "grpcs://${fqdn}".to_string(),
+                (TransportKind::Grpc, false) => "grpc://${fqdn}".to_string(),
+                (_, true) => "wss://${fqdn}/${service}/${network}/${protocol}/${encoding}".to_string(),
+                (_, false) => "ws://${fqdn}/${service}/${network}/${protocol}/${encoding}".to_string(),
+            };
             let transport = Transport {
                 kind: transport_kind,
                 tls,
-                template: format!(
-                    "{ws_proto}${{fqdn}}/${{service}}/${{network}}/${{protocol}}/${{encoding}}"
-                ),
+                template,
             };
             transport.make_address(&fqdn, &service, &network)
         });
 
-        let tls = address.starts_with("wss://");
+        let tls = address.starts_with("wss://") || address.starts_with("grpcs://");
         let uid = xxh3_64(address.as_bytes());
         let uid_string = format!("{uid:016x}");
+        let short_id = short_id(uid);
         let network_node_uid = xxh3_64(format!("{fqdn}{network}{tls}").as_bytes());
         let params = PathParams::new(transport_kind, tls.into(), network);
 
         Self {
             uid,
             uid_string,
+            short_id,
             service,
             params,
             fqdn,
@@ -60,10 +176,29 @@ impl From<NodeConfig> for Node {
             transport_kind,
             network,
             network_node_uid,
+            probes,
+            region,
+            weight: weight.unwrap_or(100),
+            roles: roles_or_default(roles),
+            poll_interval: poll_interval.map(Duration::from_secs_f64),
+            ping_interval: ping_interval.map(Duration::from_secs_f64),
+            ttl: ttl.map(Duration::from_secs_f64),
+            discovery,
         }
     }
 }
 
+/// Optional attributes of a node declared in a group (see [`Node::new`])
+#[derive(Clone, Debug, Default)]
+pub struct NodeAttributes {
+    // custom RPC probes
+    pub probes: Vec<CustomProbe>,
+    // declared region
+    pub region: Option<String>,
+    // roles served by the node (`public-api` if empty)
+    pub roles: Vec<Role>,
+}
+
 // #[derive(Clone, Debug, Serialize, Deserialize)]
 #[derive(Clone, Debug)]
 pub struct Node {
@@ -72,6 +207,8 @@ pub struct Node {
     pub uid: u64,
     // #[serde(skip)]
     pub uid_string: String,
+    // short human-friendly id (base32 of the uid prefix)
+    pub short_id: String,
     // contains hash(fqdn+network_id)
     // #[serde(skip)]
     pub network_node_uid: u64,
@@ -91,6 +228,20 @@ pub struct Node {
     pub network: NetworkId,
     // domain name (abc.example.com)
     pub fqdn: String,
+    // custom RPC probes
+    pub probes: Vec<CustomProbe>,
+    // declared region
+    pub region: Option<String>,
+    // configured share of elections (percent, 100 is neutral)
+    pub weight: u64,
+    // roles served by the node
+    pub roles: Vec<Role>,
+    // configured health-check interval and ttl overrides
+    pub poll_interval: Option<Duration>,
+    pub ping_interval: Option<Duration>,
+    pub ttl: Option<Duration>,
+    // config of a node pending transport discovery
+    pub discovery: Option<NodeConfig>,
 }
 
 impl Eq for Node {}
@@ -103,7 +254,7 @@ impl PartialEq for Node {
 
 impl std::fmt::Display for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{:016x}] {}", self.uid, self.address)
+        write!(f, "[{}] {}", self.short_id, self.address)
     }
 }
 
@@ -114,17 +265,24 @@ impl Node {
         transport: &Transport,
         fqdn: S1,
         address: S2,
+        attributes: NodeAttributes,
     ) -> Arc<Self>
     where
         S1: Display,
         S2: Display,
     {
         let Transport { tls, kind, .. } = transport;
+        let NodeAttributes {
+            probes,
+            region,
+            roles,
+        } = attributes;
 
         let address = address.to_string();
         let fqdn = fqdn.to_string();
         let uid = xxh3_64(address.as_bytes());
         let uid_string = format!("{uid:016x}");
+        let short_id = short_id(uid);
 
         let network_node_uid = xxh3_64(format!("{fqdn}{network}{tls}").as_bytes());
 
@@ -133,6 +291,7 @@ impl Node {
         let node = Self {
             uid,
             uid_string,
+            short_id,
             service: *service,
             params,
             fqdn,
@@ -140,6 +299,14 @@ impl Node {
             transport_kind: *kind,
             network,
             network_node_uid,
+            probes,
+            region,
+            weight: 100,
+            roles: roles_or_default(roles),
+            poll_interval: None,
+            ping_interval: None,
+            ttl: None,
+            discovery: None,
         };
 
         Arc::new(node)
@@ -175,15 +342,71 @@ impl Node {
         self.network_node_uid
     }
 
+    /// Stable offset within `period` derived from the uid,
+    /// used to spread periodic polling across nodes
+    #[inline]
+    pub fn phase(&self, period: Duration) -> Duration {
+        let period = period.as_millis() as u64;
+        if period == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(self.uid % period)
+        }
+    }
+
+    /// State poll interval of a delegate connection
+    #[inline]
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval.unwrap_or_else(SyncSettings::poll)
+    }
+
+    /// Ping interval of a delegator connection
+    #[inline]
+    pub fn ping_interval(&self) -> Duration {
+        self.ping_interval.unwrap_or_else(SyncSettings::ping)
+    }
+
+    /// Connection ttl (with `[ttl] noise` applied)
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+            .map(TtlSettings::with_noise)
+            .unwrap_or_else(TtlSettings::ttl)
+    }
+
     #[inline]
     pub fn uid_as_str(&self) -> &str {
         self.uid_string.as_str()
     }
 
+    #[inline]
+    pub fn short_id(&self) -> &str {
+        self.short_id.as_str()
+    }
+
+    /// Node matches a full hex uid or a short id
+    pub fn is_id(&self, id: &str) -> bool {
+        self.uid_string == id || self.short_id == id
+    }
+
     #[inline]
     pub fn address(&self) -> &str {
         self.address.as_str()
     }
+
+    #[inline]
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
+}
+
+/// Lowercase base32 (RFC 4648) of the 40-bit uid prefix (8 characters)
+fn short_id(uid: u64) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let prefix = uid >> 24;
+    (0..8)
+        .rev()
+        .map(|i| ALPHABET[((prefix >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
 }
 
 impl AsRef<Node> for Node {
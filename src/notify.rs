@@ -0,0 +1,164 @@
+//!
+//! Webhook notifications for journal events.
+//!
+//! Each journal entry (optionally filtered by kind) is rendered through
+//! an operator-defined template (`${variable}` placeholders, see [`Tpl`])
+//! and posted to the configured webhook url. Available variables:
+//! `kind`, `subject`, `id`, `node` (address), `network`, `status`,
+//! `detail`, `ts`, `duration` (seconds since the previous event
+//! for the same subject), `note` (operator note of the node) and
+//! `correlation` (flap classification of down transitions, see
+//! [`crate::flaps`]).
+//!
+
+use crate::imports::*;
+
+// notifications posted concurrently, further notifications are dropped
+const MAX_PENDING: u64 = 64;
+
+#[derive(Default)]
+pub struct Notifier {
+    // monitors of all services
+    monitors: OnceLock<Vec<Arc<Monitor>>>,
+    annotations: OnceLock<Arc<Annotations>>,
+    last: Mutex<HashMap<String, u64>>,
+    // notifications being posted
+    pending: AtomicU64,
+}
+
+impl Notifier {
+    pub fn get() -> &'static Self {
+        static NOTIFIER: OnceLock<Notifier> = OnceLock::new();
+        NOTIFIER.get_or_init(Notifier::default)
+    }
+
+    /// Attach the monitors (of all services) used to resolve node network
+    /// and status and the annotations providing the operator note of the node
+    pub fn init(monitors: Vec<Arc<Monitor>>, annotations: &Arc<Annotations>) {
+        let _ = Self::get().monitors.set(monitors);
+        let _ = Self::get().annotations.set(annotations.clone());
+    }
+
+    /// Render and post a notification for `entry`
+    pub fn dispatch(entry: &JournalEntry) {
+        let Some(url) = NotifySettings::url() else {
+            return;
+        };
+
+        let kind = serde_json::to_value(entry.kind)
+            .ok()
+            .and_then(|kind| kind.as_str().map(String::from))
+            .unwrap_or_default();
+        if !NotifySettings::is_enabled_for(&kind) {
+            return;
+        }
+
+        let notifier = Self::get();
+        if notifier.pending.fetch_add(1, Ordering::AcqRel) >= MAX_PENDING {
+            notifier.pending.fetch_sub(1, Ordering::AcqRel);
+            log_warn!("Notify", "{MAX_PENDING} notifications pending, dropping `{kind}` for {}", entry.subject);
+            return;
+        }
+
+        let body = notifier.render(&kind, entry);
+        spawn(async move {
+            if let Err(err) = post(url, body).await {
+                log_error!("Notify", "{err}");
+            }
+            notifier.pending.fetch_sub(1, Ordering::AcqRel);
+        });
+    }
+
+    fn render(&self, kind: &str, entry: &JournalEntry) -> String {
+        let duration = self
+            .last
+            .lock()
+            .unwrap()
+            .insert(entry.subject.clone(), entry.ts)
+            .map(|previous| entry.ts.saturating_sub(previous).to_string())
+            .unwrap_or_default();
+
+        // node subjects are rendered as `[id] address`
+        let (id, node) = entry
+            .subject
+            .strip_prefix('[')
+            .and_then(|subject| subject.split_once("] "))
+            .map(|(id, address)| (id.to_string(), address.to_string()))
+            .unwrap_or_else(|| (String::new(), entry.subject.clone()));
+
+        let connections = self
+            .monitors
+            .get()
+            .map(|monitors| monitors.iter().flat_map(|monitor| monitor.to_vec()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let connection = connections
+            .iter()
+            .filter(|_| !id.is_empty())
+            .find(|connection| connection.node().is_id(&id));
+        let network = connection
+            .as_ref()
+            .map(|connection| connection.network_id().to_string())
+            .unwrap_or_default();
+        let status = connection
+            .as_ref()
+            .map(|connection| connection.status().to_string())
+            .unwrap_or_default();
+        let note = connection
+            .as_ref()
+            .zip(self.annotations.get())
+            .and_then(|(connection, annotations)| annotations.get(connection.node().uid_as_str()))
+            .and_then(|annotation| annotation.note)
+            .unwrap_or_default();
+
+        let correlation = flaps::classify(&connections, entry)
+            .map(|event| event.summary())
+            .unwrap_or_default();
+
+        let json = NotifySettings::is_json();
+        let escape = |value: &str| {
+            if json {
+                let quoted = serde_json::to_string(value).unwrap_or_default();
+                quoted[1..quoted.len() - 1].to_string()
+            } else {
+                value.to_string()
+            }
+        };
+
+        let tpl = Tpl::from(
+            &[
+                ("kind", escape(kind)),
+                ("subject", escape(&entry.subject)),
+                ("id", escape(&id)),
+                ("node", escape(&node)),
+                ("network", escape(&network)),
+                ("status", escape(&status)),
+                ("detail", escape(entry.detail.as_deref().unwrap_or_default())),
+                ("ts", entry.ts.to_string()),
+                ("duration", duration),
+                ("note", escape(&note)),
+                ("correlation", escape(&correlation)),
+            ][..],
+        );
+
+        tpl.render(NotifySettings::template(kind))
+    }
+}
+
+async fn post(url: &str, body: String) -> Result<()> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .timeout(NotifySettings::timeout())
+        .header("content-type", NotifySettings::content_type())
+        .body(body)
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(Error::custom(format!(
+            "Notification failed: {}",
+            response.status()
+        )))
+    }
+}
@@ -0,0 +1,75 @@
+//!
+//! Tracing subscriber setup. Connection tasks, state updates, RPC
+//! calls and HTTP resolutions are recorded as `tracing` spans; with
+//! `--otlp=<endpoint>` (requires the `otel` feature) the spans are
+//! additionally exported to an OpenTelemetry collector over OTLP/gRPC,
+//! allowing slow resolutions to be correlated with upstream RPC calls.
+//!
+
+use crate::imports::*;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Install the tracing subscriber: console output at `level` and,
+/// with `--otlp`, OTLP span export at the debug level (connection
+/// and RPC spans are recorded at the debug level).
+pub fn init(args: &Args, level: tracing::Level) -> Result<()> {
+    let fmt = tracing_subscriber::fmt::layer().with_filter(LevelFilter::from_level(level));
+    let registry = tracing_subscriber::registry().with(fmt);
+
+    let Some(endpoint) = args.otlp.as_deref() else {
+        registry.init();
+        return Ok(());
+    };
+
+    #[cfg(feature = "otel")]
+    {
+        let otlp = tracing_opentelemetry::layer()
+            .with_tracer(tracer(endpoint)?)
+            .with_filter(LevelFilter::DEBUG);
+        registry.with(otlp).init();
+        log_info!("Tracing", "exporting spans to `{endpoint}`");
+        Ok(())
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.init();
+        Err(Error::config(format!(
+            "OTLP export to `{endpoint}` requires the resolver to be built with `--features otel`"
+        )))
+    }
+}
+
+#[cfg(feature = "otel")]
+fn tracer(endpoint: &str) -> Result<opentelemetry_sdk::trace::Tracer> {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{runtime, trace, Resource};
+
+    let provider = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![
+            KeyValue::new("service.name", "vecno-resolver"),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ])))
+        .install_batch(runtime::Tokio)
+        .map_err(|err| Error::config(format!("OTLP exporter `{endpoint}`: {err}")))?;
+    let tracer = provider.tracer("vecno-resolver");
+    opentelemetry::global::set_tracer_provider(provider);
+    Ok(tracer)
+}
+
+/// Flush pending spans of the OTLP exporter
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    opentelemetry::global::shutdown_tracer_provider();
+}
@@ -1,11 +1,33 @@
+use crate::config::Limits;
+use crate::exit::ExitCode;
+use crate::utils::unix_now;
 use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const WINDOW_SEC: u64 = 60 * 60;
 
 pub fn init_ungraceful_panic_handler() {
     let default_hook = panic::take_hook();
 
+    // panics within the current one hour window
+    let window = AtomicU64::new(unix_now());
+    let panics = AtomicU64::new(0);
+
     panic::set_hook(Box::new(move |panic_info| {
         default_hook(panic_info);
 
+        let now = unix_now();
+        if now.saturating_sub(window.load(Ordering::Relaxed)) > WINDOW_SEC {
+            window.store(now, Ordering::Relaxed);
+            panics.store(0, Ordering::Relaxed);
+        }
+
+        let count = panics.fetch_add(1, Ordering::Relaxed) + 1;
+        if count > Limits::panics_per_hour() {
+            eprintln!("PANIC THRESHOLD EXCEEDED: {count} panics within an hour, exiting.");
+            ExitCode::PanicThreshold.exit();
+        }
+
         eprintln!("PANIC RECOVERED: The process continues running despite a thread panic.");
         eprintln!("   This is expected in fault-tolerant mode. Check logs for details.");
     }));
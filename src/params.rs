@@ -10,7 +10,8 @@ pub static NETWORKS: &[NetworkId] = &[
 pub static TRANSPORTS: &[TransportKind] = &[
     TransportKind::WrpcBorsh,
     TransportKind::WrpcJson,
-    // TransportKind::Grpc,
+    #[cfg(feature = "grpc")]
+    TransportKind::Grpc,
 ];
 
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
@@ -0,0 +1,179 @@
+//!
+//! Peer-count network health: the peer counts reported by the synced
+//! nodes of each network are aggregated into a median, exported as a
+//! network-level health indicator. A synced node whose peer count
+//! collapses (below `[peer-health] min-peers`, or below `isolation-ratio`
+//! of the network median for networks with at least `min-nodes` synced
+//! nodes) is flagged `isolated`: it still reports `synced` but is about
+//! to fall behind the network. With `demote` set, isolated nodes are
+//! excluded from elections until their peer count recovers (at least
+//! `min-peers` and `recover-ratio` of the median), unless they are the
+//! last available delegates of their network. Only nodes whose peer
+//! count was sampled since they became synced are evaluated.
+//!
+
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Peer-count health of a network
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NetworkHealth {
+    /// synced nodes of the network
+    pub nodes: usize,
+    /// median peer count of the synced nodes
+    pub median_peers: u64,
+    /// synced nodes flagged isolated
+    pub isolated: usize,
+}
+
+/// Synced delegate `connections` with a sampled peer count, grouped by network
+fn synced(connections: &[Arc<Connection>]) -> BTreeMap<String, Vec<&Arc<Connection>>> {
+    let mut networks = BTreeMap::<String, Vec<&Arc<Connection>>>::new();
+    for connection in connections.iter().filter(|connection| {
+        let state = connection.state();
+        connection.is_delegate() && state.is_connected && state.is_synced && connection.is_peers_sampled()
+    }) {
+        networks
+            .entry(connection.network_id().to_string())
+            .or_default()
+            .push(connection);
+    }
+    networks
+}
+
+fn median(connections: &[&Arc<Connection>]) -> u64 {
+    let mut peers = connections
+        .iter()
+        .map(|connection| connection.peers())
+        .collect::<Vec<_>>();
+    peers.sort_unstable();
+    peers.get(peers.len() / 2).copied().unwrap_or_default()
+}
+
+/// Peer-count health of the networks of `connections`
+pub fn health(connections: &[Arc<Connection>]) -> BTreeMap<String, NetworkHealth> {
+    synced(connections)
+        .into_iter()
+        .map(|(network, connections)| {
+            let health = NetworkHealth {
+                nodes: connections.len(),
+                median_peers: median(&connections),
+                isolated: connections
+                    .iter()
+                    .filter(|connection| connection.is_isolated())
+                    .count(),
+            };
+            (network, health)
+        })
+        .collect()
+}
+
+/// Evaluate the peer counts of the delegate `connections`
+pub fn update(connections: &[Arc<Connection>]) {
+    if !PeerHealthSettings::enable() {
+        for connection in connections {
+            connection.set_isolated(false, None);
+        }
+        return;
+    }
+
+    let networks = synced(connections);
+    for connection in connections {
+        // nodes leaving the synced set (or not sampled since) are handled by their own status
+        let is_synced = networks
+            .get(&connection.network_id().to_string())
+            .is_some_and(|synced| synced.iter().any(|synced| Arc::ptr_eq(synced, connection)));
+        if !is_synced {
+            connection.set_isolated(false, None);
+        }
+    }
+
+    // other available delegates of the network of `connection`
+    let others_available = |connection: &Arc<Connection>| {
+        connections.iter().any(|other| {
+            !Arc::ptr_eq(other, connection)
+                && other.is_delegate()
+                && other.network_id() == connection.network_id()
+                && other.is_available()
+        })
+    };
+
+    for connections in networks.values() {
+        // an isolated node still serves better than none at all
+        if PeerHealthSettings::demote() {
+            for connection in connections.iter().filter(|connection| connection.is_isolated()) {
+                if !others_available(connection) {
+                    connection.set_isolated(false, Some("last available node of the network".to_string()));
+                }
+            }
+        }
+
+        // the median is only meaningful with enough synced nodes
+        let median = (connections.len() >= PeerHealthSettings::min_nodes()).then(|| median(connections));
+
+        for connection in connections {
+            let peers = connection.peers();
+            let detail = match median {
+                Some(median) => format!("{peers} peers, network median {median}"),
+                None => format!("{peers} peers"),
+            };
+            let below = |ratio: f64| median.is_some_and(|median| (peers as f64) < median as f64 * ratio);
+            if peers < PeerHealthSettings::min_peers() || below(PeerHealthSettings::isolation_ratio()) {
+                // never demote the last available delegate of the network
+                if PeerHealthSettings::demote() && !connection.is_isolated() && !others_available(connection) {
+                    continue;
+                }
+                connection.set_isolated(true, Some(detail));
+            } else if !below(PeerHealthSettings::recover_ratio()) {
+                connection.set_isolated(false, Some(detail));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::{self, MockClient};
+
+    #[tokio::test]
+    async fn isolates_nodes_below_the_network_median() {
+        let connections =
+            mock::polled("peers", &[40, 40, 40, 2], |client, peers| client.set_peers(*peers)).await;
+        update(&connections);
+        assert!(connections[..3].iter().all(|connection| !connection.is_isolated()));
+        assert!(connections[3].is_isolated());
+
+        let health = health(&connections);
+        let network = health.values().next().unwrap();
+        assert_eq!(network.nodes, 4);
+        assert_eq!(network.median_peers, 40);
+        assert_eq!(network.isolated, 1);
+    }
+
+    #[tokio::test]
+    async fn never_demotes_the_last_available_node() {
+        let connections =
+            mock::polled("peers", &[0], |client, peers| client.set_peers(*peers)).await;
+        update(&connections);
+        assert!(!connections[0].is_isolated());
+        assert!(connections[0].is_available());
+    }
+
+    #[tokio::test]
+    async fn skips_nodes_without_a_peer_count() {
+        let client = MockClient::new(1);
+        client.set_synced(false);
+        client.set_peers(0);
+        let unsynced = mock::connection("peers-unsynced.test", &client);
+        unsynced.poll().await.unwrap_err();
+
+        let mut connections =
+            mock::polled("peers", &[40, 40, 40], |client, peers| client.set_peers(*peers)).await;
+        connections.push(unsynced);
+        update(&connections);
+        assert!(connections.iter().all(|connection| !connection.is_isolated()));
+        assert_eq!(health(&connections).values().next().unwrap().nodes, 3);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::imports::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Pin {
+    /// Addresses pinned after the first successful connection
+    pub addresses: BTreeSet<IpAddr>,
+    pub pinned_at: u64,
+    /// Unexpected addresses awaiting operator confirmation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<BTreeSet<IpAddr>>,
+}
+
+/// Result of checking resolved node addresses against the pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinCheck {
+    /// No pin existed, resolved addresses are now pinned
+    Pinned,
+    /// Resolved addresses match the pin
+    Match,
+    /// Node resolves to unexpected addresses
+    Violation,
+}
+
+/// Resolved IP addresses of node hostnames pinned after the first
+/// successful connection, protecting against DNS hijacks of node
+/// hostnames. Persisted to `~/.vecno-resolver/pins.json`.
+pub struct Pins {
+    map: RwLock<BTreeMap<String, Pin>>,
+    path: PathBuf,
+}
+
+impl Pins {
+    pub fn get() -> &'static Self {
+        static PINS: OnceLock<Pins> = OnceLock::new();
+        PINS.get_or_init(Pins::load)
+    }
+
+    fn load() -> Self {
+        let path = global_config_folder().join("pins.json");
+        let map = if path.exists() {
+            match std::fs::read_to_string(&path)
+                .map_err(|err| Error::file(&path, err))
+                .and_then(|json| Ok(serde_json::from_str::<BTreeMap<String, Pin>>(&json)?))
+            {
+                Ok(map) => map,
+                Err(err) => {
+                    log_error!("Pinning", "Unable to load `{}`: {err}", path.display());
+                    BTreeMap::new()
+                }
+            }
+        } else {
+            BTreeMap::new()
+        };
+
+        Self {
+            map: RwLock::new(map),
+            path,
+        }
+    }
+
+    pub fn to_map(&self) -> BTreeMap<String, Pin> {
+        self.map.read().unwrap().clone()
+    }
+
+    /// Check `resolved` addresses of node `uid` against its pin
+    pub fn check(&self, uid: &str, resolved: BTreeSet<IpAddr>) -> Result<PinCheck> {
+        let mut map = self.map.write().unwrap();
+        let check = match map.get_mut(uid) {
+            None => {
+                map.insert(
+                    uid.to_string(),
+                    Pin {
+                        addresses: resolved,
+                        pinned_at: unix_now(),
+                        pending: None,
+                    },
+                );
+                PinCheck::Pinned
+            }
+            Some(pin) if resolved.is_subset(&pin.addresses) => {
+                if pin.pending.is_none() {
+                    return Ok(PinCheck::Match);
+                }
+                // resolution returned to the pinned addresses
+                pin.pending = None;
+                PinCheck::Match
+            }
+            Some(pin) => {
+                if pin.pending.as_ref() == Some(&resolved) {
+                    return Ok(PinCheck::Violation);
+                }
+                pin.pending = Some(resolved);
+                PinCheck::Violation
+            }
+        };
+        self.store(&map)?;
+        Ok(check)
+    }
+
+    /// Accept the pending addresses of node `uid`, returns `false` if none are pending
+    pub fn confirm(&self, uid: &str) -> Result<bool> {
+        let mut map = self.map.write().unwrap();
+        let Some(pin) = map.get_mut(uid) else {
+            return Ok(false);
+        };
+        let Some(pending) = pin.pending.take() else {
+            return Ok(false);
+        };
+        pin.addresses.extend(pending);
+        pin.pinned_at = unix_now();
+        self.store(&map)?;
+        Ok(true)
+    }
+
+    /// Remove the pin of node `uid` (re-pinned on the next connection)
+    pub fn remove(&self, uid: &str) -> Result<bool> {
+        let mut map = self.map.write().unwrap();
+        let removed = map.remove(uid).is_some();
+        self.store(&map)?;
+        Ok(removed)
+    }
+
+    fn store(&self, map: &BTreeMap<String, Pin>) -> Result<()> {
+        let json = serde_json::to_string_pretty(map)?;
+        std::fs::write(&self.path, json).map_err(|err| Error::file(&self.path, err))
+    }
+}
+
+/// Resolve the IP addresses of a node url, `None` if the url host is an IP literal
+pub async fn resolve(address: &str) -> Result<Option<BTreeSet<IpAddr>>> {
+    let url = reqwest::Url::parse(address)
+        .map_err(|err| Error::custom(format!("Invalid node url `{address}`: {err}")))?;
+    let host = match url.host_str() {
+        Some(host) if host.trim_matches(['[', ']']).parse::<IpAddr>().is_err() => host,
+        _ => return Ok(None),
+    };
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses = tokio::net::lookup_host((host, port))
+        .await?
+        .map(|addr| addr.ip())
+        .collect::<BTreeSet<_>>();
+    Ok(Some(addresses))
+}
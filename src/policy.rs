@@ -0,0 +1,287 @@
+use crate::imports::*;
+use std::collections::VecDeque;
+
+const SHADOW_HISTORY: usize = 64;
+
+/// Node selection policy applied to the list of available
+/// connections (sorted by score, lowest first).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    /// Random selection weighted by the position in the sorted list
+    Weighted,
+    /// Always select the least loaded node
+    LeastLoaded,
+    /// Uniform random selection
+    Random,
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Policy::Weighted => "weighted",
+            Policy::LeastLoaded => "least-loaded",
+            Policy::Random => "random",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Policy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "weighted" => Ok(Policy::Weighted),
+            "least-loaded" => Ok(Policy::LeastLoaded),
+            "random" => Ok(Policy::Random),
+            _ => Err(Error::config(format!("Unknown policy: `{s}`"))),
+        }
+    }
+}
+
+impl Policy {
+    pub fn select<'a>(&self, nodes: &[&'a Arc<Connection>]) -> Option<&'a Arc<Connection>> {
+        self.select_index(nodes.len()).map(|index| nodes[index])
+    }
+
+    /// Select a position in a list of `len` nodes sorted by score
+    pub fn select_index(&self, len: usize) -> Option<usize> {
+        if len == 0 {
+            return None;
+        }
+
+        match self {
+            Policy::Weighted => Some(select_with_weighted_rng(len)),
+            Policy::LeastLoaded => Some(0),
+            Policy::Random => {
+                let mut rng = rand::thread_rng();
+                Some(rng.gen_range(0..len))
+            }
+        }
+    }
+}
+
+/// Node selection strategy (`--strategy`): the ranking of the
+/// available connections (see [`Connection::score`]) and, for
+/// `round-robin` and `random`, the selection replacing the policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// Rank by open sockets (clients and peers)
+    #[default]
+    LeastSockets,
+    /// Rank by client load (clients / capacity)
+    LeastLoad,
+    /// Cycle through the available nodes
+    RoundRobin,
+    /// Uniform random selection
+    Random,
+    /// Rank by RPC latency (`[slo] percentile`)
+    Latency,
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Strategy::LeastSockets => "least-sockets",
+            Strategy::LeastLoad => "least-load",
+            Strategy::RoundRobin => "round-robin",
+            Strategy::Random => "random",
+            Strategy::Latency => "latency",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "least-sockets" => Ok(Strategy::LeastSockets),
+            "least-load" => Ok(Strategy::LeastLoad),
+            "round-robin" => Ok(Strategy::RoundRobin),
+            "random" => Ok(Strategy::Random),
+            "latency" => Ok(Strategy::Latency),
+            _ => Err(Error::config(format!("Unknown strategy: `{s}`"))),
+        }
+    }
+}
+
+impl Strategy {
+    /// Select a node among `nodes` (sorted by score), ranking
+    /// strategies leave the selection to `policy`
+    pub fn select<'a>(&self, policy: Policy, nodes: &[&'a Arc<Connection>]) -> Option<&'a Arc<Connection>> {
+        match self {
+            Strategy::RoundRobin => {
+                static NEXT: AtomicU64 = AtomicU64::new(0);
+                if nodes.is_empty() {
+                    return None;
+                }
+                let next = NEXT.fetch_add(1, Ordering::Relaxed) as usize;
+                // the ranking changes between elections, cycle in uid order
+                let mut nodes = nodes.to_vec();
+                nodes.sort_by_key(|connection| connection.node().uid());
+                Some(nodes[next % nodes.len()])
+            }
+            Strategy::Random => Policy::Random.select(nodes),
+            Strategy::LeastSockets | Strategy::LeastLoad | Strategy::Latency => policy.select(nodes),
+        }
+    }
+}
+
+/// Sticky selection: rendezvous (highest random weight) hashing of
+/// `key` over the node uids, the same key maps to the same node for as
+/// long as it is available; when a node leaves, only the keys mapped to
+/// it move to other nodes.
+pub fn select_sticky<'a>(key: &str, nodes: &[&'a Arc<Connection>]) -> Option<&'a Arc<Connection>> {
+    nodes
+        .iter()
+        .max_by_key(|connection| xxh3_64_with_seed(key.as_bytes(), connection.node().uid()))
+        .copied()
+}
+
+fn select_with_weighted_rng(len: usize) -> usize {
+    // Calculate total weight based on the position in the sorted list
+    let total_weight: usize = (0..len).map(|i| len - i).sum();
+
+    // Generate a random number within the range of total_weight
+    let mut rng = rand::thread_rng();
+    let mut rand_weight = rng.gen_range(0..total_weight);
+
+    // Select a node based on the random weight
+    for i in 0..len {
+        let weight = len - i;
+        if rand_weight < weight {
+            return i;
+        }
+        rand_weight -= weight;
+    }
+
+    // Fallback in case of error (shouldn't happen)
+    0
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Divergence {
+    pub ts: u64,
+    pub params: String,
+    pub active: String,
+    pub shadow: String,
+}
+
+/// Shadow evaluation of a candidate policy. The shadow policy is run
+/// on every election and differences from the active policy are
+/// recorded without affecting the response.
+#[derive(Debug)]
+pub struct Shadow {
+    policy: Policy,
+    evaluations: AtomicU64,
+    divergences: AtomicU64,
+    recent: Mutex<VecDeque<Divergence>>,
+}
+
+impl Shadow {
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            evaluations: AtomicU64::new(0),
+            divergences: AtomicU64::new(0),
+            recent: Mutex::new(VecDeque::with_capacity(SHADOW_HISTORY)),
+        }
+    }
+
+    /// Run the shadow policy against the election candidates,
+    /// returns the node the shadow policy would have selected.
+    pub fn evaluate<'a>(
+        &self,
+        params: &PathParams,
+        active: &Arc<Connection>,
+        nodes: &[&'a Arc<Connection>],
+    ) -> Option<&'a Arc<Connection>> {
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+
+        let shadow = self.policy.select(nodes)?;
+
+        if shadow.node() != active.node() {
+            self.divergences.fetch_add(1, Ordering::Relaxed);
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() == SHADOW_HISTORY {
+                recent.pop_front();
+            }
+            recent.push_back(Divergence {
+                ts: unix_now(),
+                params: params.to_string(),
+                active: active.node().short_id().to_string(),
+                shadow: shadow.node().short_id().to_string(),
+            });
+        }
+
+        Some(shadow)
+    }
+
+    pub fn report(&self) -> ShadowReport {
+        ShadowReport {
+            active: PolicySettings::active(),
+            shadow: self.policy,
+            evaluations: self.evaluations.load(Ordering::Relaxed),
+            divergences: self.divergences.load(Ordering::Relaxed),
+            recent: self.recent.lock().unwrap().iter().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShadowReport {
+    pub active: Policy,
+    pub shadow: Policy,
+    pub evaluations: u64,
+    pub divergences: u64,
+    pub recent: Vec<Divergence>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock;
+
+    #[test]
+    fn empty_lists_select_nothing() {
+        for strategy in [Strategy::LeastSockets, Strategy::RoundRobin, Strategy::Random] {
+            assert!(strategy.select(Policy::Weighted, &[]).is_none());
+        }
+    }
+
+    #[test]
+    fn ranking_strategies_apply_the_policy() {
+        let connections = mock::connections("policy", &[(); 3], |_, _| {});
+        let nodes = connections.iter().collect::<Vec<_>>();
+        for strategy in [Strategy::LeastSockets, Strategy::LeastLoad, Strategy::Latency] {
+            let selected = strategy.select(Policy::LeastLoaded, &nodes).unwrap();
+            assert!(Arc::ptr_eq(selected, nodes[0]));
+        }
+    }
+
+    #[test]
+    fn round_robin_cycles_through_all_nodes() {
+        let connections = mock::connections("policy", &[(); 4], |_, _| {});
+        let nodes = connections.iter().collect::<Vec<_>>();
+        let selected = (0..nodes.len())
+            .map(|_| Strategy::RoundRobin.select(Policy::LeastLoaded, &nodes).unwrap().node().uid())
+            .collect::<HashSet<_>>();
+        assert_eq!(selected.len(), nodes.len());
+    }
+
+    #[test]
+    fn random_selects_a_listed_node() {
+        let connections = mock::connections("policy", &[(); 3], |_, _| {});
+        let nodes = connections.iter().collect::<Vec<_>>();
+        for _ in 0..16 {
+            let selected = Strategy::Random.select(Policy::LeastLoaded, &nodes).unwrap();
+            assert!(nodes.iter().any(|node| Arc::ptr_eq(node, selected)));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::imports::*;
+use std::future::Future;
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::Semaphore;
+
+/// Dedicated runtime for probe RPCs, isolated from the runtime serving
+/// HTTP requests and bounded to `[probe] concurrency` in-flight probes,
+/// so that a fleet-wide event (many simultaneous probe timeouts) can not
+/// starve resolution request handling.
+pub struct ProbePool {
+    runtime: Runtime,
+    permits: Arc<Semaphore>,
+}
+
+impl ProbePool {
+    pub fn get() -> &'static Self {
+        static POOL: OnceLock<ProbePool> = OnceLock::new();
+        POOL.get_or_init(|| {
+            let runtime = Builder::new_multi_thread()
+                .worker_threads(ProbeSettings::threads())
+                .thread_name("probe")
+                .enable_all()
+                .build()
+                .expect("Unable to create the probe runtime");
+            Self {
+                runtime,
+                permits: Arc::new(Semaphore::new(ProbeSettings::concurrency())),
+            }
+        })
+    }
+
+    /// Run `future` on the pool once a permit is available
+    pub async fn run<F, T>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permits = self.permits.clone();
+        self.runtime
+            .spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                future.await
+            })
+            .await
+            .map_err(|err| Error::custom(format!("probe task: {err}")))
+    }
+}
@@ -0,0 +1,119 @@
+//!
+//! Operator-defined custom RPC probes.
+//!
+//! A custom probe issues an RPC `method` on the monitored connection,
+//! selects a value from the JSON representation of the response using
+//! a JSON pointer (`path`) and compares it against `value` using `op`.
+//! A node failing any of its custom probes is excluded from elections.
+//!
+
+use crate::imports::*;
+use serde_json::Value;
+
+/// RPC methods available to custom probes
+pub const METHODS: &[&str] = &[
+    "get-info",
+    "get-server-info",
+    "get-sync-status",
+    "get-block-dag-info",
+    "get-system-info",
+    "get-connections",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProbeOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Exists,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CustomProbe {
+    /// Name used in logs (defaults to `method` + `path`)
+    pub name: Option<String>,
+    /// RPC method, one of [`METHODS`]
+    pub method: String,
+    /// JSON pointer into the response (`/isSynced`), empty for the whole response
+    #[serde(default)]
+    pub path: String,
+    pub op: ProbeOp,
+    /// Expected value (not used by `exists`)
+    pub value: Option<Value>,
+}
+
+impl Display for CustomProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "{}{}", self.method, self.path),
+        }
+    }
+}
+
+impl CustomProbe {
+    pub fn validate(&self) -> Result<()> {
+        if !METHODS.contains(&self.method.as_str()) {
+            return Err(Error::config(format!(
+                "Unknown custom probe method `{}` (supported: {})",
+                self.method,
+                METHODS.join(", ")
+            )));
+        }
+        if !self.path.is_empty() && !self.path.starts_with('/') {
+            return Err(Error::config(format!(
+                "Invalid custom probe path `{}` (must be a JSON pointer)",
+                self.path
+            )));
+        }
+        if self.op != ProbeOp::Exists && self.value.is_none() {
+            return Err(Error::config(format!(
+                "Custom probe `{self}` requires a `value`"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Evaluate the predicate against an RPC response
+    pub fn check(&self, response: &Value) -> std::result::Result<(), String> {
+        let actual = response.pointer(&self.path);
+        let ok = match (self.op, actual, self.value.as_ref()) {
+            (ProbeOp::Exists, actual, _) => actual.is_some_and(|v| !v.is_null()),
+            (_, None, _) | (_, _, None) => false,
+            (ProbeOp::Eq, Some(actual), Some(expected)) => equals(actual, expected),
+            (ProbeOp::Ne, Some(actual), Some(expected)) => !equals(actual, expected),
+            (op, Some(actual), Some(expected)) => match (actual.as_f64(), expected.as_f64()) {
+                (Some(a), Some(b)) => match op {
+                    ProbeOp::Lt => a < b,
+                    ProbeOp::Le => a <= b,
+                    ProbeOp::Gt => a > b,
+                    ProbeOp::Ge => a >= b,
+                    _ => unreachable!(),
+                },
+                _ => false,
+            },
+        };
+
+        if ok {
+            Ok(())
+        } else {
+            let actual = actual.map(Value::to_string).unwrap_or_else(|| "none".to_string());
+            let expected = self.value.as_ref().map(Value::to_string).unwrap_or_default();
+            let op = format!("{:?}", self.op).to_lowercase();
+            Err(format!("{self}: {actual} {op} {expected}"))
+        }
+    }
+}
+
+/// Numbers are compared by value (`1` equals `1.0`)
+fn equals(actual: &Value, expected: &Value) -> bool {
+    match (actual.as_f64(), expected.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => actual == expected,
+    }
+}
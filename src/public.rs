@@ -1,4 +1,5 @@
 use crate::imports::*;
+use crate::status::{json_response, session_from_req};
 use askama::Template;
 
 use axum::{
@@ -32,6 +33,75 @@ pub async fn json_handler(resolver: &Arc<Resolver>, _req: Request<Body>) -> impl
         .unwrap()
 }
 
+/// Default and maximum page size of `/v2/nodes`
+const NODES_LIMIT: usize = 100;
+const NODES_MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct NodesQuery {
+    /// network id (e.g. `mainnet`)
+    pub network: Option<String>,
+    /// connection status (`online`, `syncing`, `offline`, ...)
+    pub status: Option<String>,
+    pub service: Option<Service>,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+#[derive(Serialize)]
+pub struct NodesPage<'a> {
+    /// number of connections matching the filters
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub nodes: Vec<Public<'a>>,
+}
+
+/// Filtered and paginated list of all connections (ordered by uid);
+/// requires a session unless the resolver serves public status (`--public`).
+pub async fn nodes_handler(
+    resolver: &Arc<Resolver>,
+    query: NodesQuery,
+    req: Request<Body>,
+) -> Response<Body> {
+    if !resolver.args().public() && session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let mut connections = resolver
+        .connections()
+        .into_iter()
+        .filter(|connection| {
+            let node = connection.node();
+            query
+                .network
+                .as_ref()
+                .is_none_or(|network| node.network.to_string() == *network)
+                && query
+                    .status
+                    .as_ref()
+                    .is_none_or(|status| connection.status() == status.as_str())
+                && query.service.is_none_or(|service| node.service() == service)
+        })
+        .collect::<Vec<_>>();
+    connections.sort_by_key(|connection| connection.node().uid());
+
+    let limit = query.limit.unwrap_or(NODES_LIMIT).min(NODES_MAX_LIMIT);
+    let page = NodesPage {
+        total: connections.len(),
+        offset: query.offset,
+        limit,
+        nodes: connections
+            .iter()
+            .skip(query.offset)
+            .take(limit)
+            .map(Public::from)
+            .collect(),
+    };
+    json_response(StatusCode::OK, serde_json::to_string(&page).unwrap())
+}
+
 pub async fn status_handler(_resolver: &Arc<Resolver>, _req: Request<Body>) -> impl IntoResponse {
     let index = PublicTemplate {};
 
@@ -54,6 +124,7 @@ pub struct Public<'a> {
     pub sid: u64,
     #[serde(with = "SerHex::<Strict>")]
     pub uid: u64,
+    pub id: &'a str,
     pub service: String,
     pub protocol: ProtocolKind,
     pub encoding: EncodingKind,
@@ -63,6 +134,9 @@ pub struct Public<'a> {
     pub peers: u64,
     pub clients: u64,
     pub capacity: u64,
+    /// clients / capacity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub load: Option<f64>,
 }
 
 impl<'a> From<&'a Arc<Connection>> for Public<'a> {
@@ -71,17 +145,19 @@ impl<'a> From<&'a Arc<Connection>> for Public<'a> {
 
         let node = connection.node();
         let uid = node.uid();
+        let id = node.short_id();
         let service = node.service().to_string();
         let protocol = node.params().protocol();
         let encoding = node.params().encoding();
         let encryption = node.params().tls();
         let network = &node.network;
         let status = connection.status();
-        let clients = delegate.clients();
-        let peers = delegate.peers();
-        let (version, sid, capacity) = delegate
-            .caps()
-            .as_ref()
+        let state = delegate.state();
+        let clients = state.clients;
+        let peers = state.peers;
+        let load = state.load();
+        let (version, sid, capacity) = state
+            .caps
             .as_ref()
             .map(|caps| (caps.version.clone(), caps.system_id, caps.clients_limit))
             .unwrap_or_else(|| ("n/a".to_string(), 0, 0));
@@ -89,6 +165,7 @@ impl<'a> From<&'a Arc<Connection>> for Public<'a> {
         Self {
             sid,
             uid,
+            id,
             version,
             service,
             protocol,
@@ -99,6 +176,7 @@ impl<'a> From<&'a Arc<Connection>> for Public<'a> {
             clients,
             peers,
             capacity,
+            load,
         }
     }
 }
@@ -0,0 +1,104 @@
+//!
+//! Per client address token-bucket rate limiting of the resolution
+//! routes (`/v2/...`): each address may issue up to `[rate-limit] burst`
+//! requests at once, refilled at `rate` requests per second. Limited
+//! requests are rejected with `429 Too Many Requests` and a `Retry-After`
+//! header (seconds until a token is available).
+//!
+
+use crate::imports::*;
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::{IpAddr, SocketAddr};
+use workflow_core::time::Instant;
+
+// interval of dropping idle (refilled) buckets
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+pub struct RateLimiter {
+    buckets: Mutex<AHashMap<IpAddr, Bucket>>,
+    last_prune: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn get() -> &'static Self {
+        static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+        LIMITER.get_or_init(|| Self {
+            buckets: Default::default(),
+            last_prune: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Take a token of `ip`, returns the delay until a token
+    /// is available if the bucket is empty
+    fn acquire(&self, ip: IpAddr) -> std::result::Result<(), Duration> {
+        let now = Instant::now();
+        let burst = RateLimitSettings::burst();
+        let rate = RateLimitSettings::rate();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        self.prune(&mut buckets, now, burst, rate);
+
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: burst,
+            last: now,
+        });
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.last = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    /// Drop buckets that refilled completely (equivalent to a new bucket)
+    fn prune(&self, buckets: &mut AHashMap<IpAddr, Bucket>, now: Instant, burst: f64, rate: f64) {
+        let mut last_prune = self.last_prune.lock().unwrap();
+        if now.duration_since(*last_prune) < PRUNE_INTERVAL {
+            return;
+        }
+        *last_prune = now;
+        buckets.retain(|_, bucket| {
+            bucket.tokens + now.duration_since(bucket.last).as_secs_f64() * rate < burst
+        });
+    }
+}
+
+/// Middleware rate limiting the resolution routes per client address
+pub async fn guard(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    if !request.uri().path().starts_with("/v2/") {
+        return next.run(request).await;
+    }
+
+    let ip = blocklist::client_ip(addr, request.headers());
+    match RateLimiter::get().acquire(ip) {
+        Ok(()) => next.run(request).await,
+        Err(delay) => {
+            metrics::HttpMetrics::get().rate_limited();
+            let retry_after = delay.as_secs_f64().ceil().max(1.0) as u64;
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, HeaderValue::from(retry_after))],
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+// period a delegation lock recovery marks the delegation unhealthy
+const RECOVERY_WINDOW: u64 = 60 * 60;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StageReport {
+    pub ready: bool,
+    /// Time (UNIX seconds) the stage became ready
+    pub since: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ServingReport {
+    #[serde(flatten)]
+    pub stage: StageReport,
+    /// Availability of each required network
+    pub networks: BTreeMap<String, bool>,
+}
+
+/// Health of the delegation subsystem (not a readiness stage: a
+/// recovered lock does not make the resolver unready)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DelegationReport {
+    /// No lock recovery within the last hour
+    pub healthy: bool,
+    /// Delegation locks recovered from poisoning (panics while held)
+    pub recoveries: u64,
+    /// Time (UNIX seconds) of the last recovery
+    pub last_recovery: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReadinessReport {
+    pub process: StageReport,
+    pub monitoring: StageReport,
+    pub serving: ServingReport,
+    pub delegation: DelegationReport,
+}
+
+/// Readiness stage requested via `/ready?stage=`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stage {
+    Process,
+    Monitoring,
+    #[default]
+    Serving,
+}
+
+#[derive(Debug, Default)]
+struct StageState(AtomicU64);
+
+impl StageState {
+    fn set(&self, ready: bool, now: u64) {
+        if !ready {
+            self.0.store(0, Ordering::Relaxed);
+        } else if self.0.load(Ordering::Relaxed) == 0 {
+            self.0.store(now, Ordering::Relaxed);
+        }
+    }
+
+    fn report(&self) -> StageReport {
+        let since = self.0.load(Ordering::Relaxed);
+        StageReport {
+            ready: since != 0,
+            since: (since != 0).then_some(since),
+        }
+    }
+}
+
+/// Multi-stage readiness: the process is up, the monitoring plane is
+/// established (node list applied and node connections established)
+/// and at least one node is available for each required network.
+/// Readiness is process-wide, covering the nodes of all services.
+#[derive(Debug)]
+pub struct Readiness {
+    process: u64,
+    monitoring: StageState,
+    serving: StageState,
+    networks: Mutex<BTreeMap<String, bool>>,
+    recoveries: AtomicU64,
+    last_recovery: AtomicU64,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self {
+            process: unix_now(),
+            monitoring: Default::default(),
+            serving: Default::default(),
+            networks: Default::default(),
+            recoveries: Default::default(),
+            last_recovery: Default::default(),
+        }
+    }
+}
+
+impl Readiness {
+    pub fn get() -> &'static Self {
+        static READINESS: OnceLock<Readiness> = OnceLock::new();
+        READINESS.get_or_init(Readiness::default)
+    }
+
+    /// Update the stages from the node `connections` of all services
+    pub fn update(&self, connections: &[Arc<Connection>]) {
+        let now = unix_now();
+
+        let monitoring = connections.iter().any(|connection| connection.is_connected());
+        self.monitoring.set(monitoring, now);
+
+        let required = ReadinessSettings::networks().unwrap_or_else(|| {
+            let mut networks = connections
+                .iter()
+                .map(|connection| connection.network_id())
+                .collect::<Vec<_>>();
+            networks.sort_by_key(|network| network.to_string());
+            networks.dedup();
+            networks
+        });
+
+        let networks = required
+            .iter()
+            .map(|network| {
+                let available = connections.iter().any(|connection| {
+                    connection.network_id() == *network
+                        && connection.is_delegate()
+                        && connection.is_available()
+                });
+                (network.to_string(), available)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let serving = !networks.is_empty() && networks.values().all(|available| *available);
+        self.serving.set(serving, now);
+        *self.networks.lock().unwrap() = networks;
+    }
+
+    /// Record the recovery of a poisoned delegation lock
+    pub fn delegation_recovered(&self) {
+        self.recoveries.fetch_add(1, Ordering::Relaxed);
+        self.last_recovery.store(unix_now(), Ordering::Relaxed);
+    }
+
+    pub fn is_ready(&self, stage: Stage) -> bool {
+        match stage {
+            Stage::Process => true,
+            Stage::Monitoring => self.monitoring.report().ready,
+            Stage::Serving => self.serving.report().ready,
+        }
+    }
+
+    pub fn report(&self) -> ReadinessReport {
+        ReadinessReport {
+            process: StageReport {
+                ready: true,
+                since: Some(self.process),
+            },
+            monitoring: self.monitoring.report(),
+            serving: ServingReport {
+                stage: self.serving.report(),
+                networks: self.networks.lock().unwrap().clone(),
+            },
+            delegation: {
+                let last_recovery = Some(self.last_recovery.load(Ordering::Relaxed)).filter(|ts| *ts > 0);
+                DelegationReport {
+                    healthy: last_recovery
+                        .is_none_or(|ts| unix_now().saturating_sub(ts) > RECOVERY_WINDOW),
+                    recoveries: self.recoveries.load(Ordering::Relaxed),
+                    last_recovery,
+                }
+            },
+        }
+    }
+}
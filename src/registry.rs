@@ -0,0 +1,147 @@
+//!
+//! Node inventory fetched from an authoritative registry (`--registry`).
+//!
+//! The registry serves `{ "config": "<node config JSON>", "signature": "<hex>" }`
+//! where `config` has the structure of the TOML node configuration
+//! (`transport`, `group`, `node` and `profile` entries) along with a
+//! `serial` number, and `signature` is a Schnorr signature of the SHA256
+//! hash of `config`, verified against the `--registry-key` x-only public
+//! key. The serial must increase with every config change: a config
+//! with a lower serial than the applied one (e.g. an old signed config
+//! replayed to roll back the fleet) is rejected. The last applied
+//! response is cached in `~/.vecno-resolver/registry.json` and used
+//! at startup while the registry is unreachable.
+//!
+
+use crate::imports::*;
+use secp256k1::{schnorr::Signature, Message, XOnlyPublicKey, SECP256K1};
+use sha2::{Digest, Sha256};
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    config: String,
+    signature: String,
+}
+
+// version of the signed config
+#[derive(Deserialize)]
+struct Serial {
+    serial: u64,
+}
+
+/// Verified registry response
+struct Verified {
+    serial: u64,
+    digest: [u8; 32],
+    data: String,
+}
+
+pub struct Registry {
+    url: String,
+    public_key: XOnlyPublicKey,
+    // serial and hash of the last applied config
+    previous: Mutex<Option<(u64, [u8; 32])>>,
+    // verified response awaiting [`Registry::commit`]
+    pending: Mutex<Option<Verified>>,
+    path: PathBuf,
+}
+
+impl Registry {
+    pub fn try_new(url: &str, public_key: Option<&str>) -> Result<Self> {
+        let public_key = public_key
+            .ok_or_else(|| Error::config("`--registry` requires `--registry-key`"))?;
+        let public_key = XOnlyPublicKey::from_str(public_key)
+            .map_err(|err| Error::config(format!("Invalid registry key: {err}")))?;
+
+        let this = Self {
+            url: url.to_string(),
+            public_key,
+            previous: Mutex::new(None),
+            pending: Mutex::new(None),
+            path: global_config_folder().join("registry.json"),
+        };
+        // configs older than the cached one are rejected across restarts
+        if let Ok((verified, _)) = this.read_cached() {
+            this.previous.lock().unwrap().replace((verified.serial, verified.digest));
+        }
+        Ok(this)
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// Verify a registry response, returning it along with its config
+    fn verify(&self, data: String) -> Result<(Verified, String)> {
+        let Envelope { config, signature } = serde_json::from_str(&data)?;
+        let digest: [u8; 32] = Sha256::digest(config.as_bytes()).into();
+        let signature = Signature::from_str(&signature)
+            .map_err(|err| Error::custom(format!("Registry: invalid signature: {err}")))?;
+        SECP256K1
+            .verify_schnorr(&signature, &Message::from_digest(digest), &self.public_key)
+            .map_err(|err| Error::custom(format!("Registry: signature verification failed: {err}")))?;
+        let Serial { serial } = serde_json::from_str(&config)
+            .map_err(|err| Error::custom(format!("Registry: missing config serial: {err}")))?;
+
+        Ok((Verified { serial, digest, data }, config))
+    }
+
+    fn read_cached(&self) -> Result<(Verified, String)> {
+        let data = std::fs::read_to_string(&self.path).map_err(|err| Error::file(&self.path, err))?;
+        self.verify(data)
+    }
+
+    /// Fetch the node list, `None` if unchanged since the last applied
+    /// update. The node list must be applied with [`Registry::commit`].
+    pub async fn fetch(&self) -> Result<Option<Vec<Arc<Node>>>> {
+        let response = reqwest::Client::new()
+            .get(&self.url)
+            .timeout(RegistrySettings::timeout())
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Error::custom(format!(
+                "Registry: unable to fetch `{}`: {}",
+                self.url,
+                response.status()
+            )));
+        }
+
+        let (verified, config) = self.verify(response.text().await?)?;
+        if let Some((serial, digest)) = *self.previous.lock().unwrap() {
+            if verified.serial == serial && verified.digest == digest {
+                return Ok(None);
+            }
+            if verified.serial <= serial {
+                return Err(Error::custom(format!(
+                    "Registry: rejected config serial {} (applied serial {serial})",
+                    verified.serial
+                )));
+            }
+        }
+
+        let nodes = Config::try_parse_json(&config)?;
+        self.pending.lock().unwrap().replace(verified);
+        Ok(Some(nodes))
+    }
+
+    /// Node list of the last applied registry response cached on disk,
+    /// to be applied with [`Registry::commit`]
+    pub fn load_cached(&self) -> Result<Vec<Arc<Node>>> {
+        let (verified, config) = self.read_cached()?;
+        let nodes = Config::try_parse_json(&config)?;
+        self.pending.lock().unwrap().replace(verified);
+        Ok(nodes)
+    }
+
+    /// Record the last fetched node list as applied and cache it
+    pub fn commit(&self) {
+        let Some(Verified { serial, digest, data }) = self.pending.lock().unwrap().take() else {
+            return;
+        };
+        self.previous.lock().unwrap().replace((serial, digest));
+        if let Err(err) = std::fs::write(&self.path, data) {
+            log_error!("Registry", "Unable to cache `{}`: {err}", self.path.display());
+        }
+    }
+}
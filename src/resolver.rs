@@ -1,38 +1,64 @@
 use crate::imports::*;
 
 use axum::{
-    // extract::Query,
     body::Body,
-    extract::Form,
-    http::{header, HeaderValue, Request, StatusCode},
+    extract::{ws::WebSocketUpgrade, ConnectInfo, Form, Query},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Request, StatusCode},
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use axum::{error_handling::HandleErrorLayer, BoxError};
+use std::collections::BTreeMap;
+use std::future::IntoFuture;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 use tokio::net::TcpListener;
 use tower::{buffer::BufferLayer, limit::RateLimitLayer, ServiceBuilder};
 use tower_http::cors::{Any, CorsLayer};
+use workflow_core::time::Instant;
 
 struct Inner {
     args: Arc<Args>,
     http_server: Mutex<Option<(TcpListener, Router)>>,
-    vecno: Arc<Monitor>,
+    // monitor of each service, keyed by service
+    monitors: BTreeMap<Service, Arc<Monitor>>,
     shutdown_ctl: DuplexChannel<()>,
     events: Channel<Events>,
     sessions: Sessions,
+    annotations: Arc<Annotations>,
+    api_keys: ApiKeys,
+    publisher: Option<Publisher>,
+    registry: Option<Registry>,
+    federation: Option<Arc<Federation>>,
+    telemetry: Telemetry,
+    // set once a shutdown signal was received
+    is_shutting_down: AtomicBool,
 }
 
 impl Inner {
-    fn new(args: &Arc<Args>) -> Self {
+    fn new(
+        args: &Arc<Args>,
+        publisher: Option<Publisher>,
+        registry: Option<Registry>,
+        federation: Option<Federation>,
+    ) -> Self {
         Self {
             args: args.clone(),
             http_server: Default::default(),
-            vecno: Arc::new(Monitor::new(args, Service::Vecno)),
+            monitors: Service::iter()
+                .map(|service| (service, Arc::new(Monitor::new(args, service))))
+                .collect(),
             shutdown_ctl: DuplexChannel::oneshot(),
             events: Channel::unbounded(),
             sessions: Sessions::new(HttpStatus::sessions(), HttpStatus::ttl()),
+            annotations: Arc::new(Annotations::load()),
+            api_keys: ApiKeys::load(),
+            publisher,
+            registry,
+            federation: federation.map(Arc::new),
+            telemetry: Telemetry::default(),
+            is_shutting_down: AtomicBool::new(false),
         }
     }
 }
@@ -44,8 +70,22 @@ pub struct Resolver {
 
 impl Resolver {
     pub fn try_new(args: &Arc<Args>) -> Result<Self> {
+        tls::init(args)?;
+        let publisher = args
+            .snapshot_url
+            .as_deref()
+            .map(Publisher::try_new)
+            .transpose()?;
+        let registry = args
+            .registry
+            .as_deref()
+            .map(|url| Registry::try_new(url, args.registry_key.as_deref()))
+            .transpose()?;
+        let federation = Federation::try_new(args)?;
+        Geo::init(args)?;
+
         Ok(Self {
-            inner: Arc::new(Inner::new(args)),
+            inner: Arc::new(Inner::new(args, publisher, registry, federation)),
         })
     }
 
@@ -53,13 +93,132 @@ impl Resolver {
         &self.inner.args
     }
 
+    /// Monitor of the Vecno service (served by the status pages,
+    /// the admin API and federation)
+    pub fn monitor(&self) -> &Arc<Monitor> {
+        self.service_monitor(Service::Vecno)
+    }
+
+    pub fn service_monitor(&self, service: Service) -> &Arc<Monitor> {
+        &self.inner.monitors[&service]
+    }
+
+    pub fn monitors(&self) -> impl Iterator<Item = &Arc<Monitor>> {
+        self.inner.monitors.values()
+    }
+
+    /// Connection of the node `id` (hex uid or short id) of any service
+    pub fn find(&self, id: &str) -> Option<Arc<Connection>> {
+        self.monitors().find_map(|monitor| monitor.find(id))
+    }
+
     pub async fn init_http_server(self: &Arc<Self>) -> Result<()> {
         let mut router = Router::new();
 
+        for service in Service::iter() {
+            let this = self.clone();
+            router = router.route(
+                &format!("/v2/{service}/:network/:tls/:protocol/:encoding"),
+                get(
+                    move |ConnectInfo(addr): ConnectInfo<SocketAddr>,
+                          headers: HeaderMap,
+                          query,
+                          path| async move {
+                        this.get_elected(service, addr, headers, query, path).await
+                    },
+                ),
+            );
+
+            let this = self.clone();
+            router = router.route(
+                &format!("/v2/{service}/:network/:tls/:protocol/:encoding/regions"),
+                get(move |UrlPath(params): UrlPath<PathParams>| async move {
+                    match this.service_monitor(service).regions(&params) {
+                        Some(json) => with_json_string(json),
+                        None => not_found(),
+                    }
+                }),
+            );
+
+            let this = self.clone();
+            router = router.route(
+                &format!("/v2/{service}/:network/:tls/:protocol/:encoding/roles"),
+                get(move |UrlPath(params): UrlPath<PathParams>| async move {
+                    match this.service_monitor(service).roles(&params) {
+                        Some(json) => with_json_string(json),
+                        None => not_found(),
+                    }
+                }),
+            );
+        }
+
+        let this = self.clone();
+        router = router.route(
+            "/v1/events",
+            get(|req: Request<Body>| async move { status::events_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/v1/:network/candidates",
+            get(
+                |UrlPath(network): UrlPath<NetworkId>, req: Request<Body>| async move {
+                    status::candidates_handler(&this, network, req).await
+                },
+            ),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/v1/subscribe",
+            get(
+                |ConnectInfo(addr): ConnectInfo<SocketAddr>,
+                 headers: HeaderMap,
+                 Query(query): Query<subscribe::SubscribeQuery>,
+                 ws: WebSocketUpgrade| async move {
+                    let client = blocklist::client_ip(addr, &headers);
+                    subscribe::subscribe_handler(&this, client, &headers, query, ws).await
+                },
+            ),
+        );
+
+        router = router.route(
+            "/v1/fleet/events",
+            get(
+                |ConnectInfo(addr): ConnectInfo<SocketAddr>, req: Request<Body>| async move {
+                    fleet::events_handler(addr, req).await
+                },
+            ),
+        );
+
+        router = router.route(
+            "/v1/fleet/events.json",
+            get(
+                |ConnectInfo(addr): ConnectInfo<SocketAddr>, query, req: Request<Body>| async move {
+                    fleet::poll_handler(addr, query, req).await
+                },
+            ),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/v1/federation/nodes",
+            get(|headers: HeaderMap| async move {
+                match this.inner.federation.as_ref() {
+                    Some(federation) => federation.serve(&this.connections(), &headers),
+                    None => not_found(),
+                }
+            }),
+        );
+
         let this = self.clone();
         router = router.route(
-            "/v2/vecno/:network/:tls/:protocol/:encoding",
-            get(|path| async move { this.get_elected_vecno(path).await }),
+            "/v1/feedback",
+            post(
+                |ConnectInfo(addr): ConnectInfo<SocketAddr>, req: Request<Body>| async move {
+                    feedback::feedback_handler(&this, addr, req).await
+                },
+            ),
         );
 
         let this = self.clone();
@@ -68,6 +227,13 @@ impl Resolver {
             get(|req: Request<Body>| async move { status::logout_handler(&this, req).await }),
         );
 
+        router = router.route(
+            "/ready",
+            get(|Query(query): Query<status::ReadyQuery>| async move {
+                status::ready_handler(query).await
+            }),
+        );
+
         let this = self.clone();
         router = router.route(
             "/status",
@@ -90,6 +256,294 @@ impl Resolver {
             get(|req: Request<Body>| async move { status::json_handler(&this, req).await }),
         );
 
+        let this = self.clone();
+        router = router.route(
+            "/status/query",
+            post(|req: Request<Body>| async move { status::query_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/changes",
+            get(|req: Request<Body>| async move { admin::list_changes_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/changes",
+            post(|req: Request<Body>| async move {
+                admin::schedule_change_handler(&this, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/dry-run",
+            post(|req: Request<Body>| async move { admin::dry_run_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/annotations",
+            get(|req: Request<Body>| async move {
+                admin::list_annotations_handler(&this, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/annotations/:uid",
+            put(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::set_annotation_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/annotations/:uid",
+            delete(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::remove_annotation_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/faults",
+            get(|req: Request<Body>| async move { admin::list_faults_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/faults/:uid",
+            post(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::inject_fault_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/faults/:uid",
+            delete(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::clear_faults_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/logs/tail",
+            get(|Query(query): Query<admin::TailQuery>, req: Request<Body>| async move {
+                admin::logs_tail_handler(&this, query, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/pins",
+            get(|req: Request<Body>| async move { admin::list_pins_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/pins/:uid/confirm",
+            post(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::confirm_pin_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/pins/:uid",
+            delete(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::remove_pin_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/batch",
+            post(|req: Request<Body>| async move { admin::batch_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/drills",
+            get(|req: Request<Body>| async move { admin::drills_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/journal",
+            get(|req: Request<Body>| async move { admin::journal_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/features",
+            get(|req: Request<Body>| async move { admin::list_features_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/features/:name",
+            put(|UrlPath(name): UrlPath<String>, req: Request<Body>| async move {
+                admin::set_feature_handler(&this, name, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/budget",
+            get(|req: Request<Body>| async move { admin::budget_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/reload",
+            post(|req: Request<Body>| async move { admin::reload_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/blocklist",
+            get(|req: Request<Body>| async move { admin::blocklist_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/blocklist/:ip",
+            delete(|UrlPath(ip): UrlPath<IpAddr>, req: Request<Body>| async move {
+                admin::unblock_handler(&this, ip, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/config",
+            get(|req: Request<Body>| async move { admin::config_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/zone",
+            get(|req: Request<Body>| async move { admin::zone_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/federation",
+            get(|req: Request<Body>| async move { admin::federation_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/topology",
+            get(
+                |Query(query): Query<topology::TopologyQuery>, req: Request<Body>| async move {
+                    admin::topology_handler(&this, query, req).await
+                },
+            ),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/flaps",
+            get(|Query(query): Query<flaps::FlapsQuery>, req: Request<Body>| async move {
+                admin::flaps_handler(&this, query, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/stats",
+            get(|req: Request<Body>| async move { admin::stats_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/archive",
+            get(|req: Request<Body>| async move { admin::archive_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/rollout",
+            get(|req: Request<Body>| async move { admin::rollout_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/shadow",
+            get(|req: Request<Body>| async move { admin::shadow_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/experiment",
+            get(|req: Request<Body>| async move { admin::experiment_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/experiment",
+            put(|req: Request<Body>| async move {
+                admin::start_experiment_handler(&this, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/experiment",
+            delete(|req: Request<Body>| async move {
+                admin::stop_experiment_handler(&this, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/changes/:id",
+            delete(|UrlPath(id): UrlPath<String>, req: Request<Body>| async move {
+                admin::cancel_change_handler(&this, id, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/nodes",
+            post(|req: Request<Body>| async move { admin::add_node_handler(&this, req).await }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/nodes/:uid",
+            delete(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::remove_node_handler(&this, uid, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/nodes/:uid/drain",
+            post(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::drain_handler(&this, uid, true, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/admin/nodes/:uid/drain",
+            delete(|UrlPath(uid): UrlPath<String>, req: Request<Body>| async move {
+                admin::drain_handler(&this, uid, false, req).await
+            }),
+        );
+
+        let this = self.clone();
+        router = router.route(
+            "/v2/nodes",
+            get(|Query(query): Query<public::NodesQuery>, req: Request<Body>| async move {
+                public::nodes_handler(&this, query, req).await
+            }),
+        );
+
         if self.args().public() {
             let this = self.clone();
             router = router.route(
@@ -104,6 +558,22 @@ impl Resolver {
             );
         }
 
+        if MetricsSettings::enable() {
+            let this = self.clone();
+            router = router
+                .route(
+                    "/metrics",
+                    get(|| async move { metrics::metrics_handler(&this).await }),
+                )
+                .route("/stats.json", {
+                    let this = self.clone();
+                    get(|Query(query): Query<metrics::StatsQuery>| async move {
+                        metrics::stats_handler(&this, query).await
+                    })
+                })
+                .route_layer(axum::middleware::from_fn(metrics::track));
+        }
+
         if let Some(rate_limit) = self.args().rate_limit.as_ref() {
             log_success!(
                 "Limits",
@@ -129,7 +599,34 @@ impl Resolver {
             log_warn!("Limits", "HTTP rate limit is disabled");
         };
 
-        router = router.layer(CorsLayer::new().allow_origin(Any));
+        router = router.layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_headers([
+                    HeaderName::from_static(API_KEY_HEADER),
+                    HeaderName::from_static(AB_HEADER),
+                ])
+                .expose_headers([
+                    HeaderName::from_static(SHADOW_UID_HEADER),
+                    HeaderName::from_static(SHADOW_URL_HEADER),
+                    header::RETRY_AFTER,
+                ]),
+        );
+
+        if RateLimitSettings::enable() {
+            log_success!(
+                "Limits",
+                "Per-address resolution rate limit: burst {}, {} requests/sec",
+                RateLimitSettings::burst(),
+                RateLimitSettings::rate()
+            );
+            router = router.layer(axum::middleware::from_fn(ratelimit::guard));
+        }
+
+        if BlocklistSettings::enable() {
+            // outermost layer: blocked addresses are rejected before routing
+            router = router.layer(axum::middleware::from_fn(blocklist::guard));
+        }
 
         log_success!(
             "Server",
@@ -138,7 +635,7 @@ impl Resolver {
         );
         let listener = tokio::net::TcpListener::bind(self.args().listen.as_str())
             .await
-            .unwrap();
+            .map_err(|err| Error::Bind(self.args().listen.clone(), err))?;
 
         self.inner
             .http_server
@@ -149,14 +646,53 @@ impl Resolver {
         Ok(())
     }
 
+    /// Serve HTTP requests until a shutdown signal (SIGTERM, Ctrl+C) is
+    /// received, then stop accepting connections and allow in-flight
+    /// responses `[shutdown] timeout-sec` to complete
     pub async fn listen(self: &Arc<Self>) -> Result<()> {
         let (listener, router) = self.inner.http_server.lock().unwrap().take().unwrap();
-        axum::serve(listener, router).await?;
+        let (signalled, drain) = tokio::sync::oneshot::channel::<()>();
+        let this = self.clone();
+        let serve = axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            this.inner.is_shutting_down.store(true, Ordering::Relaxed);
+            log_info!("Shutdown", "draining HTTP connections");
+            let _ = signalled.send(());
+        })
+        .into_future()
+        .fuse();
+        let deadline = async move {
+            if drain.await.is_ok() {
+                workflow_core::task::sleep(ShutdownSettings::timeout()).await;
+            } else {
+                futures::future::pending::<()>().await;
+            }
+        }
+        .fuse();
+        futures::pin_mut!(serve, deadline);
+
+        select! {
+            result = serve => result?,
+            _ = deadline => {
+                log_warn!("Shutdown", "in-flight HTTP responses did not complete in time");
+            }
+        }
         Ok(())
     }
 
+    pub fn is_shutting_down(&self) -> bool {
+        self.inner.is_shutting_down.load(Ordering::Relaxed)
+    }
+
     pub async fn start(self: &Arc<Self>) -> Result<()> {
-        self.inner.vecno.start().await?;
+        Notifier::init(self.monitors().cloned().collect(), &self.inner.annotations);
+        for monitor in self.monitors() {
+            monitor.start().await?;
+        }
 
         let this = self.clone();
         spawn(async move {
@@ -167,11 +703,28 @@ impl Resolver {
 
         self.inner.events.send(Events::Start).await?;
 
+        if let Some(path) = self.args().control.clone() {
+            control::start(self, path)?;
+        }
+
         Ok(())
     }
 
+    /// Stop the node connections (within `[shutdown] timeout-sec`)
+    /// and the resolver tasks
     pub async fn stop(self: &Arc<Self>) -> Result<()> {
-        self.inner.vecno.stop().await?;
+        let monitors =
+            futures::future::join_all(self.monitors().map(|monitor| monitor.stop())).fuse();
+        let timeout = workflow_core::task::sleep(ShutdownSettings::timeout()).fuse();
+        futures::pin_mut!(monitors, timeout);
+        select! {
+            results = monitors => {
+                results.into_iter().collect::<Result<Vec<_>>>()?;
+            }
+            _ = timeout => {
+                log_warn!("Shutdown", "node connections did not close in time");
+            }
+        }
 
         self.inner
             .shutdown_ctl
@@ -188,7 +741,17 @@ impl Resolver {
         let shutdown_ctl_sender = self.inner.shutdown_ctl.response.sender.clone();
 
         let mut sessions = workflow_core::task::interval(Duration::from_secs(3600));
-        let mut update = workflow_core::task::interval(Updates::duration());
+        let mut readiness = workflow_core::task::interval(Duration::from_secs(1));
+        let mut update = workflow_core::task::interval(if self.inner.registry.is_some() {
+            RegistrySettings::interval()
+        } else {
+            Updates::duration()
+        });
+        let mut snapshots = workflow_core::task::interval(SnapshotSettings::interval());
+        let mut telemetry = workflow_core::task::interval(TelemetrySettings::interval());
+        let mut zone = workflow_core::task::interval(ZoneSettings::interval());
+        let mut federation = workflow_core::task::interval(FederationSettings::interval());
+        let mut fd_guard = workflow_core::task::interval(FdGuardSettings::interval());
 
         loop {
             select! {
@@ -207,6 +770,11 @@ impl Resolver {
                                         log_error!("Config", "[update] {err}");
                                     }
                                 },
+                                Events::Reload => {
+                                    if let Err(err) = self.reload_nodes().await {
+                                        log_error!("Config", "[reload] {err}");
+                                    }
+                                },
                             }
                         }
                         Err(err) => {
@@ -221,10 +789,64 @@ impl Resolver {
                     self.inner.sessions.cleanup();
                 }
 
+                _ = readiness.next().fuse() => {
+                    // process-wide state, sampled across the monitors of all services
+                    let connections = self.connections();
+                    Fleet::get().update(&connections);
+                    Readiness::get().update(&connections);
+                }
+
+                _ = federation.next().fuse() => {
+                    if let Some(federation) = self.inner.federation.clone() {
+                        spawn(async move {
+                            federation.update().await;
+                        });
+                    }
+                }
+
+                _ = fd_guard.next().fuse() => {
+                    if FdGuardSettings::enable() {
+                        FdGuard::get().update(&self.connections());
+                    }
+                }
+
                 _ = update.next().fuse() => {
                     self.inner.events.send(Events::Update).await?;
                 }
 
+                _ = snapshots.next().fuse() => {
+                    if self.inner.publisher.is_some() {
+                        let this = self.clone();
+                        spawn(async move {
+                            if let Some(publisher) = this.inner.publisher.as_ref() {
+                                if let Err(err) = publisher.publish(&this.connections()).await {
+                                    log_error!("Snapshot", "{err}");
+                                }
+                            }
+                        });
+                    }
+                }
+
+                _ = telemetry.next().fuse() => {
+                    if Telemetry::is_enabled() {
+                        let this = self.clone();
+                        spawn(async move {
+                            if let Err(err) = this.inner.telemetry.send(&this.connections()).await {
+                                log_error!("Telemetry", "{err}");
+                            }
+                        });
+                    }
+                }
+
+                _ = zone.next().fuse() => {
+                    if ZoneSettings::path().is_some() {
+                        let this = self.clone();
+                        spawn(async move {
+                            zone::publish(&this.connections()).await;
+                        });
+                    }
+                }
+
                 _ = shutdown_ctl_receiver.recv().fuse() => {
                     break;
                 },
@@ -237,8 +859,11 @@ impl Resolver {
         Ok(())
     }
 
-    async fn update_nodes(self: &Arc<Self>, mut global_node_list: Vec<Arc<Node>>) -> Result<()> {
-        self.inner.vecno.update_nodes(&mut global_node_list).await?;
+    async fn update_nodes(self: &Arc<Self>, global_node_list: Vec<Arc<Node>>) -> Result<()> {
+        let mut global_node_list = Discovery::get().resolve(global_node_list).await;
+        for monitor in self.monitors() {
+            monitor.update_nodes(&mut global_node_list).await?;
+        }
 
         for node in global_node_list.iter() {
             log_error!("Update", "Dangling node record: {}", node);
@@ -247,7 +872,29 @@ impl Resolver {
     }
 
     async fn update(self: &Arc<Self>, first_update: bool) -> Result<()> {
-        if let Some(node_list) = user_config() {
+        if let Some(registry) = self.inner.registry.as_ref() {
+            // registry mode, local node configuration is not used
+            match registry.fetch().await {
+                Ok(Some(node_list)) => {
+                    // retained only once applied, so a failed update is retried
+                    self.update_nodes(node_list).await?;
+                    registry.commit();
+                    log_success!("Registry", "Node list updated from `{}`", registry.url());
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(err) if first_update => {
+                    log_error!("Registry", "{err}");
+                    // fallback to the last applied registry response
+                    let node_list = registry.load_cached()?;
+                    log_warn!("Registry", "Using the cached node list");
+                    self.update_nodes(node_list).await?;
+                    registry.commit();
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            }
+        } else if let Some(node_list) = user_config() {
             // load user config
             // occurs only during start
             if first_update {
@@ -278,36 +925,234 @@ impl Resolver {
         }
     }
 
+    /// Re-read the node configuration and reconcile node connections:
+    /// connections are started for new nodes and gracefully stopped for
+    /// removed ones, connections of unchanged nodes are retained.
+    async fn reload_nodes(self: &Arc<Self>) -> Result<()> {
+        let before = self.connections().len();
+        let reloaded = match self.inner.registry {
+            None => reload_user_config()?,
+            Some(_) => None,
+        };
+        match reloaded {
+            Some(node_list) => {
+                self.update_nodes(node_list.clone()).await?;
+                set_user_config(node_list);
+            }
+            None => self.update(false).await?,
+        }
+        let after = self.connections().len();
+        log_success!("Config", "Node configuration reloaded ({before} -> {after} connections)");
+        Journal::record(
+            JournalKind::ConfigReloaded,
+            "nodes",
+            Some(format!("{before} -> {after} connections")),
+        );
+        Ok(())
+    }
+
+    /// Trigger a node configuration reload
+    pub async fn reload(&self) -> Result<()> {
+        self.inner.events.send(Events::Reload).await?;
+        Ok(())
+    }
+
+    /// Validate a batch of admin operations and apply it atomically:
+    /// all nodes (of any service) must resolve before any operation is
+    /// applied, and elections are blocked while the batch is being applied.
+    pub fn apply_batch(&self, batch: &Batch) -> Result<()> {
+        batch.validate()?;
+
+        // the monitors are locked in service order
+        let guards = self
+            .monitors()
+            .flat_map(|monitor| monitor.write_shards())
+            .collect::<Vec<_>>();
+        let targets = batch
+            .operations
+            .iter()
+            .map(|operation| {
+                guards
+                    .iter()
+                    .flat_map(|connections| connections.values())
+                    .flatten()
+                    .find(|connection| connection.node().is_id(operation.uid()))
+                    .cloned()
+                    .map(|connection| (operation, connection))
+                    .ok_or_else(|| Error::custom(format!("node `{}` not found", operation.uid())))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (operation, connection) in targets {
+            operation.apply(&connection);
+        }
+
+        Ok(())
+    }
+
+    /// Wait until at least one delegate connection (of any service) is available,
+    /// fails with [`Error::NoConnections`] after `timeout`.
+    pub async fn wait_for_available(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let available = self
+                .connections()
+                .iter()
+                .any(|connection| connection.is_delegate() && connection.is_available());
+            if available {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::NoConnections(timeout.as_secs()));
+            }
+            workflow_core::task::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    /// Wait until at least `min_nodes` and `min_percent` of the
+    /// configured nodes (of all services) are available (`--strict-startup`)
+    pub async fn wait_for_quorum(
+        &self,
+        timeout: Duration,
+        min_nodes: usize,
+        min_percent: f64,
+    ) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let connections = self.connections();
+            let total = connections.len();
+            let available = connections
+                .iter()
+                .filter(|connection| connection.is_available())
+                .count();
+            let percent = if total == 0 {
+                0.0
+            } else {
+                available as f64 * 100.0 / total as f64
+            };
+            if total > 0 && available >= min_nodes && percent >= min_percent {
+                log_success!("Startup", "{available}/{total} nodes available");
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::StartupQuorum(available, total, timeout.as_secs()));
+            }
+            workflow_core::task::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
     // // respond with a JSON object containing the status of all nodes
     pub fn connections(&self) -> Vec<Arc<Connection>> {
-        self.inner.vecno.to_vec()
-
-        // let vecno = self.inner.vecno.to_vec();
+        self.monitors().flat_map(|monitor| monitor.to_vec()).collect()
     }
 
     // respond with a JSON object containing the elected node
-    async fn get_elected_vecno(
+    async fn get_elected(
         &self,
-        // Query(_query): Query<QueryParams>,
+        service: Service,
+        addr: SocketAddr,
+        headers: HeaderMap,
+        Query(query): Query<ElectionQuery>,
         UrlPath(params): UrlPath<PathParams>,
     ) -> impl IntoResponse {
         // println!("params: {:?}", params);
         // println!("query: {:?}", query);
 
-        if let Some(json) = self.inner.vecno.election(&params) {
+        let reserved = self.is_reserved(&headers);
+
+        if self.is_shutting_down() {
+            return service_unavailable();
+        }
+
+        let client = blocklist::client_ip(addr, &headers);
+        let previous = query.previous.as_deref();
+        let role = query.role.unwrap_or(Role::PublicApi);
+        // sticky resolution by explicit key or by client address
+        let sticky = query.key.or_else(|| {
+            query
+                .sticky
+                .then(|| client.to_string())
+        });
+        let span = tracing::debug_span!("resolve", %params, %client, ?role);
+        let monitor = self.service_monitor(service);
+        let elected = span.in_scope(|| {
+            monitor.election(&params, reserved, previous, role, sticky.as_deref(), client)
+        });
+        if let Some(Elected { json, shadow }) = elected {
+            metrics::HttpMetrics::get().resolution(&params.network);
+            let mut response = with_json_string(json);
+            // clients enrolled in A/B testing receive the shadow policy pick
+            let enrolled = headers
+                .get(AB_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| matches!(value, "1" | "true"));
+            if let (true, Some((uid, url))) = (enrolled, shadow) {
+                if let (Ok(uid), Ok(url)) = (HeaderValue::from_str(&uid), HeaderValue::from_str(&url)) {
+                    response.headers_mut().insert(SHADOW_UID_HEADER, uid);
+                    response.headers_mut().insert(SHADOW_URL_HEADER, url);
+                }
+            }
+            response
+        } else if !reserved && monitor.is_reserved_only(&params) {
+            service_unavailable()
+        } else if let Some(json) = self
+            .inner
+            .federation
+            .as_ref()
+            .and_then(|federation| federation.elect(service, &params, role))
+        {
+            // no local node available, elected among peer resolver nodes
             with_json_string(json)
         } else {
             not_found()
         }
     }
 
+    /// Request carries an API key for reserved capacity
+    pub fn is_reserved(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|key| self.inner.api_keys.contains(key))
+    }
+
+    pub fn federation(&self) -> Option<&Arc<Federation>> {
+        self.inner.federation.as_ref()
+    }
+
     pub fn sessions(&self) -> &Sessions {
         &self.inner.sessions
     }
+
+    pub fn annotations(&self) -> &Annotations {
+        &self.inner.annotations
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ElectionQuery {
+    /// uid (or short id) of the node the client was previously using
+    previous: Option<String>,
+    /// node role to elect (`public-api` by default)
+    role: Option<Role>,
+    /// sticky resolution key (e.g. a wallet or session id)
+    key: Option<String>,
+    /// sticky resolution keyed by the client address
+    #[serde(default)]
+    sticky: bool,
 }
 
+/// Request header carrying the API key for reserved capacity
+const API_KEY_HEADER: &str = "x-api-key";
+/// Request header enrolling the client in A/B testing
+const AB_HEADER: &str = "x-resolver-ab";
+/// Response headers carrying the shadow policy pick
+const SHADOW_UID_HEADER: &str = "x-resolver-shadow-uid";
+const SHADOW_URL_HEADER: &str = "x-resolver-shadow-url";
+
 #[inline]
-fn with_json_string(json: String) -> Response<Body> {
+fn with_json_string(json: impl Into<Body>) -> Response<Body> {
+    let json: Body = json.into();
     (
         StatusCode::OK,
         [
@@ -342,6 +1187,62 @@ fn with_mime(body: impl Into<String>, mime: &'static str) -> Response<Body> {
         .into_response()
 }
 
+/// Resolve once SIGTERM (unix) or Ctrl+C is received
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            log_error!("Shutdown", "unable to listen for Ctrl+C: {err}");
+            futures::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                log_error!("Shutdown", "unable to listen for SIGTERM: {err}");
+                futures::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = futures::future::pending::<()>();
+
+    let ctrl_c = ctrl_c.fuse();
+    let terminate = terminate.fuse();
+    futures::pin_mut!(ctrl_c, terminate);
+    select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[inline]
+fn service_unavailable() -> Response<Body> {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(mime::TEXT_PLAIN_UTF_8.as_ref()),
+            ),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(
+                    "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
+                ),
+            ),
+            (header::CONNECTION, HeaderValue::from_static("close")),
+        ],
+        "SERVICE UNAVAILABLE",
+    )
+        .into_response()
+}
+
 #[inline]
 fn not_found() -> Response<Body> {
     (
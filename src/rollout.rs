@@ -0,0 +1,161 @@
+use crate::imports::*;
+
+#[derive(Debug, Default)]
+struct Gate {
+    upgraded: usize,
+    total: usize,
+    // time (UNIX seconds) the gate opened
+    opened_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RolloutReport {
+    pub network: NetworkId,
+    pub version: String,
+    pub upgraded: usize,
+    pub total: usize,
+    pub opened_at: Option<u64>,
+    pub preference: f64,
+}
+
+/// Coordinated version rollout. Once the configured fraction of
+/// delegate nodes on a network report the target version, new
+/// resolutions increasingly prefer upgraded nodes over the ramp
+/// period, migrating clients ahead of breaking protocol changes.
+#[derive(Debug, Default)]
+pub struct Rollout {
+    gates: Mutex<AHashMap<NetworkId, Gate>>,
+}
+
+impl Rollout {
+    /// Re-evaluate the gate of each network against the current connections.
+    pub fn update(&self, connections: &[Arc<Connection>]) {
+        let Some(target) = RolloutSettings::target() else {
+            self.gates.lock().unwrap().clear();
+            return;
+        };
+
+        let now = unix_now();
+        let mut gates = self.gates.lock().unwrap();
+        for network in crate::params::NETWORKS.iter() {
+            let versions = connections
+                .iter()
+                .filter(|connection| {
+                    connection.network_id() == *network && connection.is_delegate()
+                })
+                .filter_map(|connection| connection.delegate().state().caps.clone())
+                .collect::<Vec<_>>();
+
+            let gate = gates.entry(*network).or_default();
+            gate.total = versions.len();
+            gate.upgraded = versions
+                .iter()
+                .filter(|caps| is_version_at_least(&caps.version, &target))
+                .count();
+
+            let ratio = if gate.total > 0 {
+                gate.upgraded as f64 / gate.total as f64
+            } else {
+                0.0
+            };
+            let open = gate.total > 0 && ratio >= RolloutSettings::threshold();
+
+            match (open, gate.opened_at) {
+                (true, None) => {
+                    gate.opened_at = Some(now);
+                    let detail = format!("{target}: {}/{} nodes upgraded", gate.upgraded, gate.total);
+                    log_success!("Rollout", "{network} rollout gate open ({detail})");
+                    Journal::record(JournalKind::RolloutGate, network, Some(format!("open {detail}")));
+                }
+                (false, Some(_)) => {
+                    gate.opened_at = None;
+                    let detail = format!("{target}: {}/{} nodes upgraded", gate.upgraded, gate.total);
+                    log_warn!("Rollout", "{network} rollout gate closed ({detail})");
+                    Journal::record(JournalKind::RolloutGate, network, Some(format!("closed {detail}")));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Probability (0..1) of restricting an election on `network`
+    /// to upgraded nodes, ramping up from the time the gate opened.
+    pub fn preference(&self, network: &NetworkId) -> f64 {
+        let gates = self.gates.lock().unwrap();
+        gates
+            .get(network)
+            .and_then(|gate| gate.opened_at)
+            .map(|opened_at| ramp(opened_at, unix_now()))
+            .unwrap_or_default()
+    }
+
+    /// Narrow election candidates to upgraded nodes according to the
+    /// current preference (if there are any upgraded nodes available).
+    pub fn filter<'a>(
+        &self,
+        network: &NetworkId,
+        nodes: Vec<&'a Arc<Connection>>,
+    ) -> Vec<&'a Arc<Connection>> {
+        let Some(target) = RolloutSettings::target() else {
+            return nodes;
+        };
+
+        let preference = self.preference(network);
+        if preference <= 0.0 || !rand::thread_rng().gen_bool(preference.min(1.0)) {
+            return nodes;
+        }
+
+        let upgraded = nodes
+            .iter()
+            .copied()
+            .filter(|connection| {
+                connection
+                    .delegate()
+                    .state()
+                    .caps
+                    .as_ref()
+                    .is_some_and(|caps| is_version_at_least(&caps.version, &target))
+            })
+            .collect::<Vec<_>>();
+
+        if upgraded.is_empty() {
+            nodes
+        } else {
+            upgraded
+        }
+    }
+
+    pub fn reports(&self) -> Vec<RolloutReport> {
+        let Some(version) = RolloutSettings::target() else {
+            return vec![];
+        };
+
+        let now = unix_now();
+        self.gates
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(network, gate)| RolloutReport {
+                network: *network,
+                version: version.clone(),
+                upgraded: gate.upgraded,
+                total: gate.total,
+                opened_at: gate.opened_at,
+                preference: gate
+                    .opened_at
+                    .map(|opened_at| ramp(opened_at, now))
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+fn ramp(opened_at: u64, now: u64) -> f64 {
+    let ramp = RolloutSettings::ramp().as_secs_f64();
+    if ramp <= 0.0 {
+        1.0
+    } else {
+        (now.saturating_sub(opened_at) as f64 / ramp).min(1.0)
+    }
+}
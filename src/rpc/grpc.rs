@@ -0,0 +1,406 @@
+//!
+//! gRPC node client (`--features grpc`).
+//!
+//! vecnod exposes gRPC as a single bidirectional `MessageStream`; requests
+//! are tagged with an id and matched to responses read by the stream
+//! task. [`Ctl::Connect`] / [`Ctl::Disconnect`] are emitted through the
+//! client multiplexer when the stream is opened and closed; reconnects
+//! are scheduled by the connection task, as with the wRPC client.
+//!
+
+use super::proto::{
+    rpc_client::RpcClient, vecnod_request, vecnod_response, GetConnectionsRequestMessage,
+    GetConnectionsResponseMessage, GetServerInfoRequestMessage, GetServerInfoResponseMessage,
+    GetSyncStatusRequestMessage, GetSyncStatusResponseMessage,
+    GetSystemInfoRequestMessage, GetSystemInfoResponseMessage, PingRequestMessage, RpcError,
+    VecnodRequest, VecnodResponse,
+};
+use super::{Bandwidth, Caps, Connections};
+use crate::imports::*;
+use futures::channel::{mpsc, oneshot};
+use prost::Message;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
+use tonic::Streaming;
+
+type Pending = HashMap<u64, oneshot::Sender<Option<vecnod_response::Payload>>>;
+
+pub struct Client {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    // tonic endpoint url (`http://` or `https://`)
+    url: String,
+    multiplexer: Multiplexer<Ctl>,
+    sender: Mutex<Option<mpsc::UnboundedSender<VecnodRequest>>>,
+    pending: Mutex<Pending>,
+    id: AtomicU64,
+    // id of the current stream session
+    session: AtomicU64,
+    is_connected: AtomicBool,
+    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("grpc::Client")
+            .field("url", &self.inner.url)
+            .finish()
+    }
+}
+
+impl Client {
+    pub fn try_new(address: &str) -> Result<Self> {
+        let url = match address.split_once("://") {
+            Some(("grpc", rest)) => format!("http://{rest}"),
+            Some(("grpcs", rest)) => format!("https://{rest}"),
+            Some(_) => address.to_string(),
+            None => format!("http://{address}"),
+        };
+        // validate the url upfront
+        Endpoint::from_shared(url.clone())?;
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                url,
+                multiplexer: Multiplexer::new(),
+                sender: Mutex::new(None),
+                pending: Mutex::new(HashMap::new()),
+                id: AtomicU64::new(0),
+                session: AtomicU64::new(0),
+                is_connected: AtomicBool::new(false),
+                shutdown: Mutex::new(None),
+                sent: AtomicU64::new(0),
+                received: AtomicU64::new(0),
+            }),
+        })
+    }
+}
+
+/// Open a message stream to `url`
+async fn open(
+    url: &str,
+    timeout: Duration,
+) -> Result<(mpsc::UnboundedSender<VecnodRequest>, Streaming<VecnodResponse>)> {
+    let mut endpoint = Endpoint::from_shared(url.to_string())?
+        .connect_timeout(timeout)
+        .tcp_keepalive(Some(KeepaliveSettings::tcp_idle()));
+    // `grpcs://` node addresses are mapped to `https://` endpoints
+    if let Some(config) = tls::get().filter(|_| url.starts_with("https://")) {
+        let mut tls_config = ClientTlsConfig::new();
+        if let Some(ca) = &config.ca {
+            tls_config = tls_config.ca_certificate(Certificate::from_pem(ca));
+        }
+        if let Some((cert, key)) = &config.identity {
+            tls_config = tls_config.identity(Identity::from_pem(cert, key));
+        }
+        endpoint = endpoint.tls_config(tls_config)?;
+    }
+    let channel = endpoint.connect().await?;
+    let (sender, receiver) = mpsc::unbounded();
+    let stream = RpcClient::new(channel)
+        .message_stream(receiver)
+        .await?
+        .into_inner();
+    Ok((sender, stream))
+}
+
+impl Inner {
+    /// Read responses until the stream ends or the client is disconnected
+    async fn run(
+        self: Arc<Self>,
+        session: u64,
+        mut stream: Streaming<VecnodResponse>,
+        shutdown: oneshot::Receiver<()>,
+    ) {
+        let messages = self.receive(&mut stream).fuse();
+        futures::pin_mut!(messages);
+        select! {
+            _ = messages => {},
+            _ = shutdown.fuse() => {},
+        }
+
+        self.close(session).await;
+    }
+
+    /// Release the state of `session` unless a new session was opened since
+    async fn close(&self, session: u64) {
+        if self.session.load(Ordering::Relaxed) != session {
+            return;
+        }
+
+        self.sender.lock().unwrap().take();
+        self.shutdown.lock().unwrap().take();
+        // dropping the pending senders fails the in-flight requests
+        self.pending.lock().unwrap().clear();
+        if self.is_connected.swap(false, Ordering::Relaxed) {
+            let _ = self.multiplexer.broadcast(Ctl::Disconnect).await;
+        }
+    }
+
+    async fn receive(&self, stream: &mut Streaming<VecnodResponse>) -> Result<()> {
+        while let Some(response) = stream.message().await? {
+            self.received
+                .fetch_add(response.encoded_len() as u64, Ordering::Relaxed);
+            if let Some(pending) = self.pending.lock().unwrap().remove(&response.id) {
+                let _ = pending.send(response.payload);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn request(&self, payload: vecnod_request::Payload) -> Result<vecnod_response::Payload> {
+        let id = self.id.fetch_add(1, Ordering::Relaxed) + 1;
+        let request = VecnodRequest {
+            id,
+            payload: Some(payload),
+        };
+        let sender = self
+            .sender
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| Error::Grpc("not connected".to_string()))?;
+
+        let (response_sender, response) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, response_sender);
+        self.sent
+            .fetch_add(request.encoded_len() as u64, Ordering::Relaxed);
+        if sender.unbounded_send(request).is_err() {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(Error::Grpc("connection closed".to_string()));
+        }
+
+        let response = response.fuse();
+        let timeout = workflow_core::task::sleep(KeepaliveSettings::silence()).fuse();
+        futures::pin_mut!(response, timeout);
+        select! {
+            response = response => response
+                .ok()
+                .flatten()
+                .ok_or_else(|| Error::Grpc("connection closed".to_string())),
+            _ = timeout => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(Error::Grpc("request timeout".to_string()))
+            }
+        }
+    }
+}
+
+fn check(error: Option<RpcError>) -> Result<()> {
+    match error {
+        Some(RpcError { message }) => Err(Error::Grpc(message)),
+        None => Ok(()),
+    }
+}
+
+fn unexpected() -> Error {
+    Error::Grpc("unexpected response".to_string())
+}
+
+impl Inner {
+    async fn get_system_info(&self) -> Result<GetSystemInfoResponseMessage> {
+        let request = vecnod_request::Payload::GetSystemInfoRequest(GetSystemInfoRequestMessage {});
+        match self.request(request).await? {
+            vecnod_response::Payload::GetSystemInfoResponse(response) => {
+                check(response.error.clone())?;
+                Ok(response)
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn get_sync_status(&self) -> Result<GetSyncStatusResponseMessage> {
+        let request = vecnod_request::Payload::GetSyncStatusRequest(GetSyncStatusRequestMessage {});
+        match self.request(request).await? {
+            vecnod_response::Payload::GetSyncStatusResponse(response) => {
+                check(response.error.clone())?;
+                Ok(response)
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn get_server_info(&self) -> Result<GetServerInfoResponseMessage> {
+        let request = vecnod_request::Payload::GetServerInfoRequest(GetServerInfoRequestMessage {});
+        match self.request(request).await? {
+            vecnod_response::Payload::GetServerInfoResponse(response) => {
+                check(response.error.clone())?;
+                Ok(response)
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn get_connections(&self) -> Result<GetConnectionsResponseMessage> {
+        let request = vecnod_request::Payload::GetConnectionsRequest(GetConnectionsRequestMessage {
+            include_profile_data: false,
+        });
+        match self.request(request).await? {
+            vecnod_response::Payload::GetConnectionsResponse(response) => {
+                check(response.error.clone())?;
+                Ok(response)
+            }
+            _ => Err(unexpected()),
+        }
+    }
+}
+
+#[async_trait]
+impl rpc::ClientT for Client {
+    fn multiplexer(&self) -> Multiplexer<Ctl> {
+        self.inner.multiplexer.clone()
+    }
+
+    async fn connect(&self) -> Result<()> {
+        if self.inner.is_connected.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let (sender, stream) = open(&self.inner.url, KeepaliveSettings::connect_timeout()).await?;
+        let session = self.inner.session.fetch_add(1, Ordering::Relaxed) + 1;
+        let (shutdown, shutdown_receiver) = oneshot::channel();
+        self.inner.shutdown.lock().unwrap().replace(shutdown);
+        self.inner.sender.lock().unwrap().replace(sender);
+        self.inner.is_connected.store(true, Ordering::Relaxed);
+        let _ = self.inner.multiplexer.broadcast(Ctl::Connect).await;
+        spawn(self.inner.clone().run(session, stream, shutdown_receiver));
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        let session = self.inner.session.load(Ordering::Relaxed);
+        if let Some(shutdown) = self.inner.shutdown.lock().unwrap().take() {
+            let _ = shutdown.send(());
+        }
+        self.inner.close(session).await;
+        Ok(())
+    }
+
+    async fn trigger_abort(&self) -> Result<()> {
+        // disconnecting does not wait for the node: dropping the
+        // request stream closes the connection
+        self.disconnect().await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        let request = vecnod_request::Payload::PingRequest(PingRequestMessage {});
+        match self.inner.request(request).await? {
+            vecnod_response::Payload::PingResponse(response) => check(response.error),
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn get_caps(&self) -> Result<Caps> {
+        let GetSystemInfoResponseMessage {
+            version,
+            system_id,
+            git_hash,
+            core_num,
+            total_memory,
+            fd_limit,
+            proxy_socket_limit_per_cpu_core,
+            ..
+        } = self.inner.get_system_info().await?;
+
+        // the system id is reported as a hex string
+        let system_id = system_id
+            .get(0..16)
+            .and_then(|prefix| u64::from_str_radix(prefix, 16).ok())
+            .unwrap_or_default();
+        let git_hash = (!git_hash.is_empty()).then_some(git_hash);
+        let sockets_per_core =
+            (proxy_socket_limit_per_cpu_core != 0).then_some(proxy_socket_limit_per_cpu_core);
+
+        Ok(Caps::new(
+            version,
+            system_id,
+            git_hash,
+            total_memory,
+            core_num as u64,
+            fd_limit as u64,
+            sockets_per_core,
+        ))
+    }
+
+    async fn get_sync(&self) -> Result<bool> {
+        Ok(self.inner.get_sync_status().await?.is_synced)
+    }
+
+    async fn get_daa_score(&self) -> Result<u64> {
+        Ok(self.inner.get_server_info().await?.virtual_daa_score)
+    }
+
+    async fn get_active_connections(&self) -> Result<Connections> {
+        let GetConnectionsResponseMessage { clients, peers, .. } =
+            self.inner.get_connections().await?;
+
+        Ok(Connections {
+            clients: clients as u64,
+            peers: peers as u64,
+        })
+    }
+
+    async fn probe(&self) -> Result<()> {
+        let (sender, mut stream) = open(&self.inner.url, ProbeSettings::timeout()).await?;
+        let request = VecnodRequest {
+            id: 1,
+            payload: Some(vecnod_request::Payload::GetSyncStatusRequest(
+                GetSyncStatusRequestMessage {},
+            )),
+        };
+        sender
+            .unbounded_send(request)
+            .map_err(|_| Error::Grpc("connection closed".to_string()))?;
+
+        match stream.message().await?.and_then(|response| response.payload) {
+            Some(vecnod_response::Payload::GetSyncStatusResponse(response)) => {
+                check(response.error)
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    fn bandwidth(&self) -> Option<Bandwidth> {
+        // protobuf payload sizes are exact (excluding HTTP/2 framing)
+        Some(Bandwidth {
+            sent: self.inner.sent.load(Ordering::Relaxed),
+            received: self.inner.received.load(Ordering::Relaxed),
+        })
+    }
+
+    async fn call(&self, method: &str) -> Result<serde_json::Value> {
+        // JSON field names follow the wRPC serialization of the same responses
+        let value = match method {
+            "get-sync-status" => {
+                let response = self.inner.get_sync_status().await?;
+                serde_json::json!({ "isSynced" : response.is_synced })
+            }
+            "get-system-info" => {
+                let response = self.inner.get_system_info().await?;
+                serde_json::json!({
+                    "version" : response.version,
+                    "systemId" : response.system_id,
+                    "gitHash" : response.git_hash,
+                    "cpuPhysicalCores" : response.core_num,
+                    "totalMemory" : response.total_memory,
+                    "fdLimit" : response.fd_limit,
+                    "proxySocketLimitPerCpuCore" : response.proxy_socket_limit_per_cpu_core,
+                })
+            }
+            "get-connections" => {
+                let response = self.inner.get_connections().await?;
+                serde_json::json!({ "clients" : response.clients, "peers" : response.peers })
+            }
+            method => {
+                return Err(Error::custom(format!(
+                    "probe method `{method}` is not supported over gRPC"
+                )))
+            }
+        };
+        Ok(value)
+    }
+}
@@ -0,0 +1,187 @@
+//!
+//! Test double of the node RPC client: the node state reported to the
+//! connection (sync, client and peer counts, DAA score, version) is set
+//! by the test, and [`connection`] builds a connection driven by it
+//! ([`connections`] and [`polled`] build a set of them).
+//!
+
+use super::{Caps, ClientT, Connections};
+use crate::imports::*;
+
+#[derive(Debug)]
+pub struct MockClient {
+    multiplexer: Multiplexer<Ctl>,
+    system_id: u64,
+    version: Mutex<String>,
+    is_reachable: AtomicBool,
+    is_synced: AtomicBool,
+    clients: AtomicU64,
+    peers: AtomicU64,
+    daa_score: AtomicU64,
+}
+
+impl MockClient {
+    /// Reachable, synced node `system_id` without clients
+    pub fn new(system_id: u64) -> Arc<Self> {
+        Arc::new(Self {
+            multiplexer: Multiplexer::new(),
+            system_id,
+            version: Mutex::new("1.0.0".to_string()),
+            is_reachable: AtomicBool::new(true),
+            is_synced: AtomicBool::new(true),
+            clients: AtomicU64::new(0),
+            peers: AtomicU64::new(8),
+            daa_score: AtomicU64::new(1),
+        })
+    }
+
+    pub fn set_reachable(&self, reachable: bool) {
+        self.is_reachable.store(reachable, Ordering::Relaxed);
+    }
+
+    pub fn set_synced(&self, synced: bool) {
+        self.is_synced.store(synced, Ordering::Relaxed);
+    }
+
+    pub fn set_clients(&self, clients: u64) {
+        self.clients.store(clients, Ordering::Relaxed);
+    }
+
+    pub fn set_peers(&self, peers: u64) {
+        self.peers.store(peers, Ordering::Relaxed);
+    }
+
+    pub fn set_daa_score(&self, daa_score: u64) {
+        self.daa_score.store(daa_score, Ordering::Relaxed);
+    }
+
+    pub fn set_version(&self, version: &str) {
+        *self.version.lock().unwrap() = version.to_string();
+    }
+
+    fn reachable(&self) -> Result<()> {
+        if self.is_reachable.load(Ordering::Relaxed) {
+            Ok(())
+        } else {
+            Err(Error::custom("mock node unreachable"))
+        }
+    }
+}
+
+#[async_trait]
+impl ClientT for MockClient {
+    fn multiplexer(&self) -> Multiplexer<Ctl> {
+        self.multiplexer.clone()
+    }
+
+    async fn connect(&self) -> Result<()> {
+        self.reachable()?;
+        let _ = self.multiplexer.try_broadcast(Ctl::Connect);
+        Ok(())
+    }
+
+    async fn disconnect(&self) -> Result<()> {
+        let _ = self.multiplexer.try_broadcast(Ctl::Disconnect);
+        Ok(())
+    }
+
+    async fn trigger_abort(&self) -> Result<()> {
+        self.disconnect().await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.reachable()
+    }
+
+    async fn get_caps(&self) -> Result<Caps> {
+        self.reachable()?;
+        let version = self.version.lock().unwrap().clone();
+        Ok(Caps::new(version, self.system_id, None, 0, 4, 65536, None))
+    }
+
+    async fn get_sync(&self) -> Result<bool> {
+        self.reachable()?;
+        Ok(self.is_synced.load(Ordering::Relaxed))
+    }
+
+    async fn get_daa_score(&self) -> Result<u64> {
+        self.reachable()?;
+        Ok(self.daa_score.load(Ordering::Relaxed))
+    }
+
+    async fn get_active_connections(&self) -> Result<Connections> {
+        self.reachable()?;
+        Ok(Connections {
+            clients: self.clients.load(Ordering::Relaxed),
+            peers: self.peers.load(Ordering::Relaxed),
+        })
+    }
+
+    async fn probe(&self) -> Result<()> {
+        self.reachable()
+    }
+
+    async fn call(&self, _method: &str) -> Result<serde_json::Value> {
+        self.reachable()?;
+        Ok(serde_json::json!({}))
+    }
+}
+
+/// Mainnet wRPC (borsh) node `fqdn`
+pub fn node(fqdn: &str) -> Arc<Node> {
+    let transport = Transport {
+        kind: TransportKind::WrpcBorsh,
+        tls: false,
+        template: String::new(),
+    };
+    Node::new(
+        &Service::Vecno,
+        NetworkId::new(NetworkType::Mainnet),
+        &transport,
+        fqdn,
+        format!("ws://{fqdn}"),
+        NodeAttributes::default(),
+    )
+}
+
+/// Connection to the mainnet node `fqdn` driven by `client`, each
+/// connection has its own monitor
+pub fn connection(fqdn: &str, client: &Arc<MockClient>) -> Arc<Connection> {
+    let args = Arc::new(Args::default());
+    let monitor = Arc::new(Monitor::new(&args, Service::Vecno));
+    let client: super::Client = client.clone();
+    Arc::new(Connection::with_client(monitor, node(fqdn), client, &args))
+}
+
+/// Connections to the mainnet nodes `<prefix>-<index>.test`, one per
+/// entry of `states`, each driven by its own client (system id derived
+/// from the node name) configured with `setup`
+pub fn connections<T>(
+    prefix: &str,
+    states: &[T],
+    setup: impl Fn(&MockClient, &T),
+) -> Vec<Arc<Connection>> {
+    states
+        .iter()
+        .enumerate()
+        .map(|(index, state)| {
+            let fqdn = format!("{prefix}-{index}.test");
+            let client = MockClient::new(xxh3_64(fqdn.as_bytes()));
+            setup(&client, state);
+            connection(&fqdn, &client)
+        })
+        .collect()
+}
+
+/// [`connections`] after a successful poll cycle of each connection
+pub async fn polled<T>(
+    prefix: &str,
+    states: &[T],
+    setup: impl Fn(&MockClient, &T),
+) -> Vec<Arc<Connection>> {
+    let connections = connections(prefix, states, setup);
+    for connection in &connections {
+        connection.poll().await.unwrap();
+    }
+    connections
+}
@@ -1,9 +1,27 @@
 pub mod vecno;
 
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "grpc")]
+pub mod proto;
+#[cfg(test)]
+pub mod mock;
+
 use crate::imports::*;
 
 const SOCKETS_PER_CORE: u32 = 768;
 
+// reduce fd_limit by this amount to ensure the
+// system has enough file descriptors for other
+// tasks (peers, db, etc)
+// while default kHOST setup is:
+// outgoing peers: 256
+// incoming peers: 32
+// peers are included the reported
+// node connection count
+// reserved for db etc.: 1024
+const FD_MARGIN: u64 = 1024;
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct Caps {
@@ -26,6 +44,39 @@ pub struct Caps {
 }
 
 impl Caps {
+    /// Derive the client capacity from the system info reported by the node
+    pub fn new(
+        version: String,
+        system_id: u64,
+        git_hash: Option<String>,
+        total_memory: u64,
+        cpu_physical_cores: u64,
+        fd_limit: u64,
+        sockets_per_core: Option<u32>,
+    ) -> Self {
+        // reduce node's fd_limit by FD_MARGIN to ensure
+        // the system has enough file descriptors for other
+        // tasks (peers, db, etc)
+        let fd_limit_actual = fd_limit.checked_sub(FD_MARGIN).unwrap_or(32);
+        // by default we assume that the node is able to accept
+        // 1024 connections per core (default NGINX worker configuration)
+        // TODO: this should be increased in the future once a custom
+        // proxy is implemented
+        let clients_limit =
+            cpu_physical_cores * sockets_per_core.unwrap_or(SOCKETS_PER_CORE) as u64;
+        let capacity = fd_limit_actual.min(clients_limit);
+        Self {
+            version,
+            system_id,
+            git_hash,
+            total_memory,
+            cpu_physical_cores,
+            fd_limit: fd_limit_actual,
+            clients_limit,
+            capacity,
+        }
+    }
+
     pub fn system_id(&self) -> u64 {
         self.system_id
     }
@@ -51,18 +102,43 @@ pub struct Connections {
     pub peers: u64,
 }
 
-#[enum_dispatch]
-#[derive(Debug)]
-pub enum Client {
-    Vecno(vecno::Client),
+/// Monitoring traffic of a connection (payload bytes)
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct Bandwidth {
+    pub sent: u64,
+    pub received: u64,
 }
 
-#[enum_dispatch(Client)]
-pub trait ClientT: std::fmt::Debug + Sized + Send + Sync + 'static {
+/// RPC client driven by a [`Connection`], held as a trait object so
+/// that alternate transports (and test doubles) can be plugged in.
+pub type Client = Arc<dyn ClientT>;
+
+/// Create the RPC client for the transport of `node`
+pub fn try_new(node: &Node) -> Result<Client> {
+    let client: Client = match node.transport_kind {
+        TransportKind::WrpcBorsh => Arc::new(vecno::Client::try_new(WrpcEncoding::Borsh, &node.address)?),
+        TransportKind::WrpcJson => Arc::new(vecno::Client::try_new(WrpcEncoding::SerdeJson, &node.address)?),
+        #[cfg(feature = "grpc")]
+        TransportKind::Grpc => Arc::new(grpc::Client::try_new(&node.address)?),
+        #[cfg(not(feature = "grpc"))]
+        TransportKind::Grpc => {
+            return Err(Error::config(format!(
+                "gRPC node `{}` requires the resolver to be built with `--features grpc`",
+                node.address
+            )))
+        }
+    };
+    Ok(client)
+}
+
+#[async_trait]
+pub trait ClientT: std::fmt::Debug + Send + Sync + 'static {
     fn multiplexer(&self) -> Multiplexer<Ctl> {
         unimplemented!()
     }
 
+    /// Connect to the node (a single attempt, failing if the node is
+    /// unreachable); reconnects are scheduled by the connection task.
     async fn connect(&self) -> Result<()> {
         unimplemented!()
     }
@@ -82,13 +158,30 @@ pub trait ClientT: std::fmt::Debug + Sized + Send + Sync + 'static {
     async fn get_sync(&self) -> Result<bool> {
         unimplemented!()
     }
-    #[allow(dead_code)]
+
+    /// Virtual DAA score of the node
+    async fn get_daa_score(&self) -> Result<u64>;
+
+    /// Close the connection without a graceful disconnect, the fallback
+    /// for a node that does not answer [`ClientT::disconnect`]
     async fn trigger_abort(&self) -> Result<()> {
         unimplemented!()
-    }    
+    }
 
     async fn get_active_connections(&self) -> Result<Connections> {
         unimplemented!()
     }
 
+    /// Connect to the node using a fresh client (as a wallet would),
+    /// issue a request and disconnect.
+    async fn probe(&self) -> Result<()>;
+
+    /// Monitoring traffic, `None` if the transport does not expose it
+    fn bandwidth(&self) -> Option<Bandwidth> {
+        None
+    }
+
+    /// Issue an RPC method by name (see [`crate::probes::METHODS`]),
+    /// returning the JSON representation of the response.
+    async fn call(&self, method: &str) -> Result<serde_json::Value>;
 }
@@ -0,0 +1,8 @@
+//!
+//! Generated vecnod gRPC protocol types (see `proto/rpc.proto`).
+//!
+
+#![allow(dead_code)]
+#![allow(clippy::all)]
+
+tonic::include_proto!("protowire");
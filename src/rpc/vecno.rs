@@ -1,24 +1,22 @@
-use super::{Caps, Connections};
+use super::{Bandwidth, Caps, Connections};
 use crate::imports::*;
 pub use vecno_rpc_core::api::rpc::RpcApi;
 use vecno_rpc_core::{GetConnectionsResponse, GetSystemInfoResponse};
 pub use vecno_wrpc_client::VecnoRpcClient;
 
-// reduce fd_limit by this amount to ensure the
-// system has enough file descriptors for other
-// tasks (peers, db, etc)
-// while default kHOST setup is:
-// outgoing peers: 256
-// incoming peers: 32
-// peers are included the reported
-// node connection count
-// reserved for db etc.: 1024
-const FD_MARGIN: u64 = 1024;
+// approximate size of the JSON request/response
+// envelope (id, method, params/payload keys)
+const ENVELOPE: usize = 32;
 
 #[derive(Debug)]
 pub struct Client {
     client: VecnoRpcClient,
+    encoding: WrpcEncoding,
     url: String,
+    // loopback tunnel to the node (see [`crate::tunnel`])
+    tunnel: Option<Tunnel>,
+    sent: AtomicU64,
+    received: AtomicU64,
 }
 
 impl Client {
@@ -27,21 +25,49 @@ impl Client {
 
         Ok(Self {
             client,
+            encoding,
             url: url.to_string(),
+            tunnel: Tunnel::try_new(url)?,
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
         })
     }
+
+    /// Account the payload of a monitoring request. Message sizes are
+    /// not exposed by the wRPC client; with the JSON encoding the
+    /// serialized response matches the wire payload (excluding
+    /// websocket framing), the borsh encoding is not accounted.
+    fn account<T: Serialize>(&self, method: &str, response: &T) {
+        if matches!(self.encoding, WrpcEncoding::SerdeJson) {
+            self.sent
+                .fetch_add((method.len() + ENVELOPE) as u64, Ordering::Relaxed);
+            if let Ok(len) = serde_json::to_vec(response).map(|data| data.len()) {
+                self.received
+                    .fetch_add((len + ENVELOPE) as u64, Ordering::Relaxed);
+            }
+        }
+    }
 }
 
+#[async_trait]
 impl rpc::ClientT for Client {
     fn multiplexer(&self) -> Multiplexer<Ctl> {
         self.client.ctl_multiplexer()
     }
 
     async fn connect(&self) -> Result<()> {
+        // Nodes are connected through their tunnel (TCP keepalive, and
+        // resuming the TLS session of the previous connection).
+        // A single attempt, reconnects are scheduled by the connection task.
+        let url = match self.tunnel.as_ref() {
+            Some(tunnel) => tunnel.url().await?,
+            None => self.url.clone(),
+        };
         let options = ConnectOptions {
-            block_async_connect: false,
-            strategy: ConnectStrategy::Retry,
-            url: Some(self.url.clone()),
+            block_async_connect: true,
+            strategy: ConnectStrategy::Fallback,
+            url: Some(url),
+            connect_timeout: Some(KeepaliveSettings::connect_timeout()),
             ..Default::default()
         };
 
@@ -53,11 +79,27 @@ impl rpc::ClientT for Client {
         Ok(self.client.disconnect().await?)
     }
 
+    async fn trigger_abort(&self) -> Result<()> {
+        // the wRPC client does not expose its socket: aborting the relays
+        // of the tunnel closes it, and the client reports the disconnect
+        match self.tunnel.as_ref() {
+            Some(tunnel) => {
+                tunnel.abort();
+                Ok(())
+            }
+            None => Err(Error::custom(format!("`{}` is not tunneled, unable to abort", self.url))),
+        }
+    }
+
     async fn ping(&self) -> Result<()> {
-        Ok(self.client.ping().await?)
+        let response = self.client.ping().await?;
+        self.account("ping", &response);
+        Ok(response)
     }
 
     async fn get_caps(&self) -> Result<Caps> {
+        let response = self.client.get_system_info().await?;
+        self.account("getSystemInfo", &response);
         let GetSystemInfoResponse {
             version,
             system_id,
@@ -66,44 +108,39 @@ impl rpc::ClientT for Client {
             total_memory,
             fd_limit,
             proxy_socket_limit_per_cpu_core,
-        } = self.client.get_system_info().await?;
-        let cpu_physical_cores = cpu_physical_cores as u64;
-        let fd_limit = fd_limit as u64;
-        // reduce node's fd_limit by FD_MARGIN to ensure
-        // the system has enough file descriptors for other
-        // tasks (peers, db, etc)
-        let fd_limit_actual = fd_limit.checked_sub(FD_MARGIN).unwrap_or(32);
-        // by default we assume that the node is able to accept
-        // 1024 connections per core (default NGINX worker configuration)
-        // TODO: this should be increased in the future once a custom
-        // proxy is implemented
-        let clients_limit = cpu_physical_cores
-            * proxy_socket_limit_per_cpu_core.unwrap_or(rpc::SOCKETS_PER_CORE) as u64;
+        } = response;
         let system_id = system_id
             .and_then(|v| v[0..8].try_into().ok().map(u64::from_be_bytes))
             .unwrap_or_default();
-        let capacity = fd_limit_actual.min(clients_limit);
         // let system_id_hex_string = format!("{:016x}", system_id);
         let git_hash = git_hash.as_ref().map(ToHex::to_hex);
-        Ok(Caps {
+        Ok(Caps::new(
             version,
             system_id,
             git_hash,
             total_memory,
-            cpu_physical_cores,
-            fd_limit: fd_limit_actual,
-            clients_limit,
-            capacity,
-        })
+            cpu_physical_cores as u64,
+            fd_limit as u64,
+            proxy_socket_limit_per_cpu_core,
+        ))
     }
 
     async fn get_sync(&self) -> Result<bool> {
-        Ok(self.client.get_sync_status().await?)
+        let response = self.client.get_sync_status().await?;
+        self.account("getSyncStatus", &response);
+        Ok(response)
+    }
+
+    async fn get_daa_score(&self) -> Result<u64> {
+        let response = self.client.get_server_info().await?;
+        self.account("getServerInfo", &response);
+        Ok(response.virtual_daa_score)
     }
 
     async fn get_active_connections(&self) -> Result<Connections> {
-        let GetConnectionsResponse { clients, peers, .. } =
-            self.client.get_connections(false).await?;
+        let response = self.client.get_connections(false).await?;
+        self.account("getConnections", &response);
+        let GetConnectionsResponse { clients, peers, .. } = response;
 
         Ok(Connections {
             clients: clients as u64,
@@ -111,4 +148,41 @@ impl rpc::ClientT for Client {
         })
     }
 
+    async fn probe(&self) -> Result<()> {
+        let client = VecnoRpcClient::new(self.encoding, Some(&self.url), None, None, None)?;
+        let options = ConnectOptions {
+            block_async_connect: true,
+            strategy: ConnectStrategy::Fallback,
+            url: Some(self.url.clone()),
+            connect_timeout: Some(ProbeSettings::timeout()),
+            ..Default::default()
+        };
+
+        client.connect(Some(options)).await?;
+        let result = client.get_sync_status().await;
+        let _ = client.disconnect().await;
+        result?;
+        Ok(())
+    }
+
+    fn bandwidth(&self) -> Option<Bandwidth> {
+        matches!(self.encoding, WrpcEncoding::SerdeJson).then(|| Bandwidth {
+            sent: self.sent.load(Ordering::Relaxed),
+            received: self.received.load(Ordering::Relaxed),
+        })
+    }
+
+    async fn call(&self, method: &str) -> Result<serde_json::Value> {
+        let value = match method {
+            "get-info" => serde_json::to_value(self.client.get_info().await?)?,
+            "get-server-info" => serde_json::to_value(self.client.get_server_info().await?)?,
+            "get-sync-status" => serde_json::to_value(self.client.get_sync_status().await?)?,
+            "get-block-dag-info" => serde_json::to_value(self.client.get_block_dag_info().await?)?,
+            "get-system-info" => serde_json::to_value(self.client.get_system_info().await?)?,
+            "get-connections" => serde_json::to_value(self.client.get_connections(false).await?)?,
+            method => return Err(Error::custom(format!("unsupported probe method `{method}`"))),
+        };
+        Ok(value)
+    }
+
 }
@@ -0,0 +1,334 @@
+//!
+//! Test vectors of the public response types (`vecno-resolver schema`):
+//! canonical examples are produced by serializing the actual serde types,
+//! and a JSON Schema of each type is inferred from its examples, so client
+//! SDKs can validate their parsers against the resolver itself. Every type
+//! has a minimal example (optional fields omitted) and a full example (all
+//! fields present); fields missing from any example are optional.
+//!
+
+use crate::connection::CapsDigest;
+use crate::feed::StatusEvent;
+use crate::imports::*;
+use crate::public::{NodesPage, Public};
+use crate::status::Status;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Public response type with its canonical examples
+struct Vector {
+    name: &'static str,
+    description: &'static str,
+    examples: Vec<Value>,
+}
+
+impl Vector {
+    fn new(name: &'static str, description: &'static str, examples: Vec<Value>) -> Self {
+        Self {
+            name,
+            description,
+            examples,
+        }
+    }
+
+    fn schema(&self) -> Value {
+        let mut schema = infer(&self.examples);
+        if let Value::Object(object) = &mut schema {
+            object.insert("$schema".to_string(), json!(SCHEMA_DRAFT));
+            object.insert("$id".to_string(), json!(format!("vecno-resolver/{}", self.name)));
+            object.insert("title".to_string(), json!(self.name));
+            object.insert("description".to_string(), json!(self.description));
+        }
+        schema
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "schema" : self.schema(),
+            "examples" : self.examples,
+        })
+    }
+}
+
+/// Canonical example of a response type
+fn example(value: impl Serialize) -> Value {
+    serde_json::to_value(value).expect("example serializes")
+}
+
+const UID: u64 = 0x1f2e3d4c5b6a7988;
+const SID: u64 = 0x0123456789abcdef;
+
+fn caps() -> CapsDigest {
+    CapsDigest {
+        protocol: ProtocolKind::Wrpc,
+        encoding: EncodingKind::Borsh,
+        network: NetworkId::new(NetworkType::Mainnet),
+        version: "1.0.0".to_string(),
+        features: vec!["tls", "synced"],
+        digest: 0x5a5a5a5a5a5a5a5a,
+    }
+}
+
+fn output(full: bool) -> Output<'static> {
+    Output {
+        uid: "1f2e3d4c5b6a7988",
+        id: "1f2e3d4c",
+        url: "wss://node.example.com/vecno/mainnet/wrpc/borsh",
+        region: full.then_some("eu"),
+        ttl: 600,
+        switch_reason: full.then_some("offline"),
+        caps: full.then(caps),
+    }
+}
+
+fn status<'a>(network: &'a NetworkId, full: bool) -> Status<'a> {
+    Status {
+        version: "1.0.0".to_string(),
+        sid: SID,
+        uid: UID,
+        id: "1f2e3d4c",
+        url: "wss://node.example.com/vecno/mainnet/wrpc/borsh",
+        fqdn: "node.example.com",
+        service: Service::Vecno.to_string(),
+        protocol: ProtocolKind::Wrpc,
+        encoding: EncodingKind::Borsh,
+        encryption: TlsKind::Tls,
+        network,
+        cores: 8,
+        memory: 17179869184,
+        status: "online",
+        peers: 32,
+        clients: 120,
+        capacity: 1000,
+        retries: 0,
+        recoveries: 1,
+        probe_failures: 0,
+        probe_error: full.then(|| "custom probe `height` failed".to_string()),
+        slow: full,
+        latency: full.then_some(42),
+        suspect: full,
+        failure_reports: 0,
+        daa_score: full.then_some(123456789),
+        lagging: full,
+        outdated: full,
+        isolated: full,
+        cert_expiry: full.then_some(1767225600),
+        cert_expiring: full,
+        bandwidth: full.then_some(Bandwidth {
+            sent: 1024,
+            received: 4096,
+        }),
+        delegates: full.then(|| vec!["[5b6a7988] wss://node.example.com".to_string()]),
+        annotation: full.then(|| Annotation {
+            note: Some("maintenance window on sundays".to_string()),
+            labels: BTreeMap::from([("rack".to_string(), "a1".to_string())]),
+        }),
+    }
+}
+
+fn public(network: &NetworkId, full: bool) -> Public<'_> {
+    Public {
+        version: "1.0.0".to_string(),
+        sid: SID,
+        uid: UID,
+        id: "1f2e3d4c",
+        service: Service::Vecno.to_string(),
+        protocol: ProtocolKind::Wrpc,
+        encoding: EncodingKind::Borsh,
+        encryption: TlsKind::Tls,
+        network,
+        status: "online",
+        peers: 32,
+        clients: 120,
+        capacity: 1000,
+        load: full.then_some(0.12),
+    }
+}
+
+fn vectors() -> Vec<Vector> {
+    let network = NetworkId::new(NetworkType::Mainnet);
+    let nodes_page = |nodes: Vec<Public<'_>>| NodesPage {
+        total: nodes.len(),
+        offset: 0,
+        limit: 100,
+        nodes,
+    };
+
+    vec![
+        Vector::new(
+            "output",
+            "Elected node (`GET /v2/<service>/<network>/<tls>/<protocol>/<encoding>`)",
+            vec![example(output(false)), example(output(true))],
+        ),
+        Vector::new(
+            "status",
+            "Node status (`/status/json`, `/status/query`)",
+            vec![example(status(&network, false)), example(status(&network, true))],
+        ),
+        Vector::new(
+            "status-event",
+            "Node status transition (`/v1/events` `status` events)",
+            vec![example(StatusEvent {
+                ts: 1767225600000,
+                uid: "1f2e3d4c5b6a7988".to_string(),
+                id: "1f2e3d4c".to_string(),
+                network: network.to_string(),
+                status: "offline",
+                previous: "online",
+            })],
+        ),
+        Vector::new(
+            "nodes",
+            "Node list page (`GET /v2/nodes`)",
+            vec![
+                example(nodes_page(vec![])),
+                example(nodes_page(vec![public(&network, false), public(&network, true)])),
+            ],
+        ),
+        Vector::new(
+            "error",
+            "JSON error response (admin API, refused subscribers)",
+            vec![json!({
+                "error" : "resolver is near its file descriptor limit (7373 of 8192 open), retry later",
+            })],
+        ),
+    ]
+}
+
+/// JSON Schema of values matching all of `values`
+fn infer(values: &[Value]) -> Value {
+    let mut types = BTreeSet::new();
+    let mut objects = Vec::new();
+    let mut items = Vec::new();
+    for value in values {
+        match value {
+            Value::Null => {
+                types.insert("null");
+            }
+            Value::Bool(_) => {
+                types.insert("boolean");
+            }
+            Value::Number(number) if number.is_f64() => {
+                types.insert("number");
+            }
+            Value::Number(_) => {
+                types.insert("integer");
+            }
+            Value::String(_) => {
+                types.insert("string");
+            }
+            Value::Array(array) => {
+                types.insert("array");
+                items.extend(array.iter().cloned());
+            }
+            Value::Object(object) => {
+                types.insert("object");
+                objects.push(object);
+            }
+        }
+    }
+    if types.contains("number") {
+        types.remove("integer");
+    }
+
+    let mut schema = Map::new();
+    schema.insert(
+        "type".to_string(),
+        match types.iter().collect::<Vec<_>>().as_slice() {
+            [single] => json!(single),
+            multiple => json!(multiple),
+        },
+    );
+
+    if types.contains("object") {
+        let mut properties = BTreeMap::<&String, Vec<Value>>::new();
+        for object in objects.iter() {
+            for (key, value) in object.iter() {
+                properties.entry(key).or_default().push(value.clone());
+            }
+        }
+        let required = properties
+            .keys()
+            .filter(|key| objects.iter().all(|object| object.contains_key(key.as_str())))
+            .collect::<Vec<_>>();
+        let properties = properties
+            .iter()
+            .map(|(key, values)| (key.to_string(), infer(values)))
+            .collect::<Map<_, _>>();
+        schema.insert("required".to_string(), json!(required));
+        schema.insert("properties".to_string(), Value::Object(properties));
+        // clients must accept fields added in later resolver versions
+        schema.insert("additionalProperties".to_string(), json!(true));
+    }
+
+    if types.contains("array") && !items.is_empty() {
+        schema.insert("items".to_string(), infer(&items));
+    }
+
+    Value::Object(schema)
+}
+
+/// Print the test vectors of all public response types as one JSON
+/// document or, with `dir`, write `<type>.schema.json` and
+/// `<type>.examples.json` files into `dir`
+pub fn run(dir: Option<PathBuf>) -> Result<()> {
+    let vectors = vectors();
+    match dir {
+        None => {
+            let document = vectors
+                .iter()
+                .map(|vector| (vector.name.to_string(), vector.to_json()))
+                .collect::<Map<_, _>>();
+            println!("{}", serde_json::to_string_pretty(&document)?);
+        }
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)?;
+            for vector in vectors.iter() {
+                let files = [
+                    (format!("{}.schema.json", vector.name), vector.schema()),
+                    (format!("{}.examples.json", vector.name), json!(vector.examples)),
+                ];
+                for (name, value) in files {
+                    let path = dir.join(name);
+                    std::fs::write(&path, serde_json::to_string_pretty(&value)?)
+                        .map_err(|err| Error::file(&path, err))?;
+                    log_success!("Schema", "Wrote `{}`", path.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_widen_to_numbers() {
+        assert_eq!(infer(&[json!(1), json!(2)]), json!({ "type": "integer" }));
+        assert_eq!(infer(&[json!(1), json!(2.5)]), json!({ "type": "number" }));
+    }
+
+    #[test]
+    fn mixed_values_list_their_types() {
+        assert_eq!(infer(&[json!(null), json!("a")]), json!({ "type": ["null", "string"] }));
+    }
+
+    #[test]
+    fn fields_missing_from_an_example_are_optional() {
+        let schema = infer(&[json!({ "a": 1, "b": "x" }), json!({ "a": 2 })]);
+        assert_eq!(schema["type"], json!("object"));
+        assert_eq!(schema["required"], json!(["a"]));
+        assert_eq!(schema["properties"]["b"], json!({ "type": "string" }));
+        assert_eq!(schema["additionalProperties"], json!(true));
+    }
+
+    #[test]
+    fn array_items_are_inferred() {
+        let schema = infer(&[json!([1, 2]), json!([3])]);
+        assert_eq!(schema, json!({ "type": "array", "items": { "type": "integer" } }));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::imports::*;
+
+// hour-of-week buckets (Monday 00:00 UTC first)
+const BUCKETS: usize = 7 * 24;
+const HOUR_SEC: u64 = 3600;
+// minimum interval between samples
+const SAMPLE_INTERVAL_SEC: u64 = 60;
+
+// client samples of the hour in progress
+#[derive(Debug, Default)]
+struct Hour {
+    // hours since the UNIX epoch
+    hour: u64,
+    clients: u64,
+    samples: u64,
+}
+
+/// Time-of-day / day-of-week client load profile of a node. Client
+/// counts are averaged over each hour; the hourly mean is folded into
+/// its hour-of-week bucket (an exponentially weighted average) when the
+/// hour ends, so each bucket learns the week-over-week average load of
+/// that hour. The buckets are retained across restarts by [`Stats`].
+#[derive(Debug)]
+pub struct Seasonality {
+    buckets: Mutex<[Option<f64>; BUCKETS]>,
+    hour: Mutex<Hour>,
+    last_sample: AtomicU64,
+}
+
+impl Default for Seasonality {
+    fn default() -> Self {
+        Self {
+            buckets: Mutex::new([None; BUCKETS]),
+            hour: Mutex::new(Hour::default()),
+            last_sample: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Seasonality {
+    /// Create a profile from previously learned `buckets`
+    /// (ignored unless there is one bucket per hour-of-week)
+    pub fn with_buckets(buckets: &[Option<f64>]) -> Self {
+        let seasonality = Self::default();
+        if buckets.len() == BUCKETS {
+            seasonality
+                .buckets
+                .lock()
+                .unwrap()
+                .copy_from_slice(buckets);
+        }
+        seasonality
+    }
+
+    /// Learned hour-of-week buckets
+    pub fn buckets(&self) -> Vec<Option<f64>> {
+        self.buckets.lock().unwrap().to_vec()
+    }
+
+    /// Record the current number of clients, returns `true`
+    /// if a bucket was updated (once per hour)
+    pub fn record(&self, clients: u64) -> bool {
+        let now = unix_now();
+        let last = self.last_sample.load(Ordering::Relaxed);
+        if now.saturating_sub(last) < SAMPLE_INTERVAL_SEC {
+            return false;
+        }
+        self.last_sample.store(now, Ordering::Relaxed);
+
+        let mut hour = self.hour.lock().unwrap();
+        let mut updated = false;
+        if hour.hour != now / HOUR_SEC {
+            if hour.samples > 0 {
+                let mean = hour.clients as f64 / hour.samples as f64;
+                let alpha = SeasonalitySettings::alpha();
+                let mut buckets = self.buckets.lock().unwrap();
+                let bucket = &mut buckets[hour_of_week(hour.hour)];
+                *bucket = Some(match *bucket {
+                    Some(avg) => avg + alpha * (mean - avg),
+                    None => mean,
+                });
+                updated = true;
+            }
+            *hour = Hour {
+                hour: now / HOUR_SEC,
+                ..Default::default()
+            };
+        }
+        hour.clients += clients;
+        hour.samples += 1;
+        updated
+    }
+
+    /// Expected number of clients `lookahead` from now, interpolated
+    /// between the hourly means (taken at the middle of each hour),
+    /// `None` if there is no history for that time.
+    pub fn predict(&self, lookahead: Duration) -> Option<f64> {
+        let ts = (unix_now() + lookahead.as_secs()).saturating_sub(HOUR_SEC / 2);
+        let buckets = self.buckets.lock().unwrap();
+        let before = buckets[hour_of_week(ts / HOUR_SEC)];
+        let after = buckets[hour_of_week(ts / HOUR_SEC + 1)];
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                let fraction = (ts % HOUR_SEC) as f64 / HOUR_SEC as f64;
+                Some(before + fraction * (after - before))
+            }
+            (before, after) => before.or(after),
+        }
+    }
+}
+
+fn hour_of_week(hour: u64) -> usize {
+    // 1970-01-01 was a Thursday (3 days after Monday)
+    ((hour + 3 * 24) % BUCKETS as u64) as usize
+}
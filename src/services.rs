@@ -1,11 +1,26 @@
 use crate::imports::*;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Service {
+    #[default]
     Vecno,
 }
 
+impl Service {
+    /// All services resolved by the daemon (a monitor is run for each)
+    pub fn iter() -> impl Iterator<Item = Service> {
+        [Service::Vecno].into_iter()
+    }
+
+    /// Health check of the nodes of the service
+    pub fn health(&self) -> &'static dyn HealthT {
+        match self {
+            Service::Vecno => &VecnoHealth,
+        }
+    }
+}
+
 impl Display for Service {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -14,3 +29,24 @@ impl Display for Service {
         f.write_str(s)
     }
 }
+
+/// Service-specific health check of a delegate node connection, run on
+/// every poll cycle once the connection is established. Client and peer
+/// counts are polled for all services; a node is only elected while its
+/// health check reports it ready.
+#[async_trait]
+pub trait HealthT: Send + Sync + 'static {
+    /// `Ok(true)` if the node is ready to serve clients, `Ok(false)` if it
+    /// is reachable but not ready (e.g. syncing), `Err` on RPC failures
+    async fn is_ready(&self, connection: &Connection) -> Result<bool>;
+}
+
+/// Vecno nodes are ready once synced
+struct VecnoHealth;
+
+#[async_trait]
+impl HealthT for VecnoHealth {
+    async fn is_ready(&self, connection: &Connection) -> Result<bool> {
+        connection.rpc("get_sync", |client| client.get_sync()).await
+    }
+}
@@ -0,0 +1,225 @@
+//!
+//! Monitor shards: the fleet is partitioned across `[sort] shards`
+//! shards by a stable hash of the node uid. Each shard holds its own
+//! node lists, delegate sub-map and sort scheduler task, so that large
+//! fleets do not contend on a single lock or scheduler. The sorted node
+//! lists of all shards are merged at selection time by the scores
+//! snapshotted when they were sorted.
+//!
+
+use crate::imports::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use workflow_core::time::Instant;
+
+pub struct Shard {
+    connections: RwLock<AHashMap<PathParams, Vec<Arc<Connection>>>>,
+    delegates: RwLock<AHashMap<Delegate, Arc<Connection>>>,
+    sorts: AHashMap<PathParams, AtomicBool>,
+    shutdown_ctl: DuplexChannel<()>,
+}
+
+impl Default for Shard {
+    fn default() -> Self {
+        let sorts = PathParams::iter_tls_any()
+            .map(|params| (params, AtomicBool::new(false)))
+            .collect();
+
+        Self {
+            connections: Default::default(),
+            delegates: Default::default(),
+            sorts,
+            shutdown_ctl: DuplexChannel::oneshot(),
+        }
+    }
+}
+
+impl Shard {
+    /// Shard of the node `uid` among `shards`
+    pub fn index(uid: u64, shards: usize) -> usize {
+        (xxh3_64_with_seed(&uid.to_le_bytes(), 0) % shards.max(1) as u64) as usize
+    }
+
+    pub fn connections(&self) -> &RwLock<AHashMap<PathParams, Vec<Arc<Connection>>>> {
+        &self.connections
+    }
+
+    pub fn delegates(&self) -> &RwLock<AHashMap<Delegate, Arc<Connection>>> {
+        &self.delegates
+    }
+
+    /// Flag the node lists of `params_tls_kind` (and its `TlsKind::Any`
+    /// list) for sorting; params of transports not served by this build
+    /// (e.g. gRPC without the `grpc` feature) have no node list
+    pub fn schedule_sort(&self, params_tls_kind: &PathParams) {
+        let params_tls_any = params_tls_kind.to_tls(TlsKind::Any);
+        for params in [params_tls_kind, &params_tls_any] {
+            if let Some(sort) = self.sorts.get(params) {
+                sort.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn start(self: &Arc<Self>) {
+        let this = self.clone();
+        spawn(async move {
+            this.task().await;
+        });
+    }
+
+    pub async fn stop(&self) -> Result<()> {
+        self.shutdown_ctl
+            .signal(())
+            .await
+            .expect("Shard shutdown signal error");
+        Ok(())
+    }
+
+    /// Sort scheduler: re-sort the node lists flagged by
+    /// [`Self::schedule_sort`], rate limited per node list
+    async fn task(self: Arc<Self>) {
+        let shutdown_ctl_receiver = self.shutdown_ctl.request.receiver.clone();
+        let shutdown_ctl_sender = self.shutdown_ctl.response.sender.clone();
+
+        let mut interval = workflow_core::task::interval(Duration::from_millis(300));
+        let mut buckets: AHashMap<PathParams, SortBucket> = AHashMap::new();
+
+        loop {
+            select! {
+                _ = interval.next().fuse() => {
+                    let now = Instant::now();
+                    for (params, sort) in self.sorts.iter() {
+                        if sort.load(Ordering::Relaxed) {
+                            let bucket = buckets.entry(*params).or_insert_with(|| SortBucket::new(now));
+                            if !bucket.acquire(now) {
+                                continue;
+                            }
+                            sort.store(false, Ordering::Relaxed);
+
+                            let mut connections = self.connections.write().unwrap();
+                            if let Some(nodes) = connections.get_mut(params) {
+                                nodes.sort_by_cached_key(|connection| connection.update_rank());
+                            }
+                        }
+                    }
+                }
+
+                _ = shutdown_ctl_receiver.recv().fuse() => {
+                    break;
+                },
+            }
+        }
+
+        shutdown_ctl_sender.send(()).await.unwrap();
+    }
+}
+
+/// Merge the sorted node lists of all shards by the rank (score
+/// snapshot) of their connections; a single list is returned as is
+pub fn merge<'a>(lists: Vec<&'a Vec<Arc<Connection>>>) -> Vec<&'a Arc<Connection>> {
+    if lists.len() == 1 {
+        return lists[0].iter().collect();
+    }
+    let mut merged = Vec::with_capacity(lists.iter().map(|list| list.len()).sum());
+    // (rank, list, position) of the head of each list, lowest first
+    let mut heads = lists
+        .iter()
+        .enumerate()
+        .filter_map(|(index, list)| list.first().map(|head| Reverse((head.rank(), index, 0))))
+        .collect::<BinaryHeap<_>>();
+    while let Some(Reverse((_, index, position))) = heads.pop() {
+        let list = lists[index];
+        merged.push(&list[position]);
+        if let Some(next) = list.get(position + 1) {
+            heads.push(Reverse((next.rank(), index, position + 1)));
+        }
+    }
+    merged
+}
+
+/// Token bucket limiting the re-sort rate of a node list, so that
+/// oscillating connection state does not cause constant ranking churn.
+struct SortBucket {
+    tokens: f64,
+    last: Instant,
+    pending_since: Option<Instant>,
+}
+
+impl SortBucket {
+    fn new(now: Instant) -> Self {
+        Self {
+            tokens: SortSettings::burst(),
+            last: now,
+            pending_since: None,
+        }
+    }
+
+    /// Consume a token for a pending re-sort; a re-sort pending
+    /// longer than [`SortSettings::max_delay`] is always allowed.
+    fn acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * SortSettings::rate()).min(SortSettings::burst());
+        self.last = now;
+
+        let pending_since = *self.pending_since.get_or_insert(now);
+        if self.tokens >= 1.0 || now.duration_since(pending_since) >= SortSettings::max_delay() {
+            self.tokens = (self.tokens - 1.0).max(0.0);
+            self.pending_since = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::mock::{self, MockClient};
+
+    async fn ranked(prefix: &str, clients: &[u64]) -> Vec<Arc<Connection>> {
+        let connections =
+            mock::polled(prefix, clients, |client, clients| client.set_clients(*clients)).await;
+        for connection in &connections {
+            connection.update_rank();
+        }
+        connections
+    }
+
+    #[tokio::test]
+    async fn merges_shard_lists_by_score() {
+        let first = ranked("shard-first", &[10, 30]).await;
+        let second = ranked("shard-second", &[20, 40]).await;
+        let merged = merge(vec![&first, &second]);
+        let ranks = merged.iter().map(|connection| connection.rank()).collect::<Vec<_>>();
+        assert_eq!(merged.len(), 4);
+        assert!(ranks.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(Arc::ptr_eq(merged[0], &first[0]));
+        assert!(Arc::ptr_eq(merged[1], &second[0]));
+    }
+
+    #[tokio::test]
+    async fn merge_uses_the_ranks_of_the_last_sort() {
+        let client = MockClient::new(1);
+        let first = vec![mock::connection("g.test", &client)];
+        client.set_clients(10);
+        first[0].poll().await.unwrap();
+        first[0].update_rank();
+        let second = ranked("shard-second", &[20]).await;
+
+        // load changes after the sort do not reorder the merge
+        client.set_clients(30);
+        first[0].poll().await.unwrap();
+        let merged = merge(vec![&first, &second]);
+        assert!(Arc::ptr_eq(merged[0], &first[0]));
+        assert!(first[0].score() > second[0].score());
+    }
+
+    #[tokio::test]
+    async fn single_list_is_returned_as_is() {
+        let list = ranked("shard", &[50, 10]).await;
+        let merged = merge(vec![&list]);
+        assert!(Arc::ptr_eq(merged[0], &list[0]));
+        assert!(Arc::ptr_eq(merged[1], &list[1]));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::imports::*;
+use std::collections::BTreeMap;
+
+/// Simulation time step
+const STEP_SEC: u64 = 60;
+/// Elections per node list and time step
+const ELECTIONS_PER_STEP: u64 = 10;
+/// Fraction of simulated clients remaining connected after each step
+const RETENTION: f64 = 0.95;
+
+#[derive(Debug, Default)]
+struct Outcome {
+    elections: u64,
+    failures: u64,
+    picks: BTreeMap<String, u64>,
+}
+
+impl Outcome {
+    /// Coefficient of variation of the picks across nodes (0 is perfectly balanced)
+    fn imbalance(&self, nodes: usize) -> f64 {
+        if nodes == 0 {
+            return 0.0;
+        }
+        let mean = self.picks.values().sum::<u64>() as f64 / nodes as f64;
+        if mean == 0.0 {
+            return 0.0;
+        }
+        // nodes that were never picked contribute `mean^2` each
+        let unpicked = nodes.saturating_sub(self.picks.len()) as f64;
+        let variance = (self
+            .picks
+            .values()
+            .map(|picks| (*picks as f64 - mean).powi(2))
+            .sum::<f64>()
+            + unpicked * mean.powi(2))
+            / nodes as f64;
+        variance.sqrt() / mean
+    }
+}
+
+/// Replay node availability recorded in the journal through the active
+/// and the candidate `policy` and report how node choice distribution,
+/// election failures and load balance would have differed.
+pub fn run(nodes: Vec<Arc<Node>>, journal: &Path, policy: Policy) -> Result<()> {
+    let text = std::fs::read_to_string(journal).map_err(|err| Error::file(journal, err))?;
+    let mut entries = text
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.ts);
+
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Err(Error::custom(format!("No journal entries in `{}`", journal.display())));
+    };
+    let (start, end) = (first.ts, last.ts.max(first.ts + STEP_SEC));
+
+    let active = PolicySettings::active();
+    println!();
+    println!(
+        "Simulating {} journal entries over {:.1} hours: `{active}` (active) vs `{policy}`",
+        entries.len(),
+        (end - start) as f64 / 3600.0
+    );
+
+    let mut groups = BTreeMap::<String, Vec<String>>::new();
+    for node in nodes.iter() {
+        groups
+            .entry(node.params().to_string())
+            .or_default()
+            .push(node.to_string());
+    }
+
+    for (params, nodes) in groups {
+        let baseline = simulate(&nodes, &entries, start, end, active);
+        let candidate = simulate(&nodes, &entries, start, end, policy);
+
+        println!();
+        println!("{params} ({} nodes)", nodes.len());
+        println!(
+            "{:>14} {:>12} {:>12}",
+            "",
+            active.to_string(),
+            policy.to_string()
+        );
+        println!("{:>14} {:>12} {:>12}", "elections", baseline.elections, candidate.elections);
+        println!("{:>14} {:>12} {:>12}", "failures", baseline.failures, candidate.failures);
+        println!(
+            "{:>14} {:>12.3} {:>12.3}",
+            "imbalance",
+            baseline.imbalance(nodes.len()),
+            candidate.imbalance(nodes.len())
+        );
+        for node in nodes.iter() {
+            let share = |outcome: &Outcome| {
+                let picks = outcome.picks.get(node).copied().unwrap_or_default();
+                let total = (outcome.elections - outcome.failures).max(1);
+                picks as f64 / total as f64 * 100.0
+            };
+            println!(
+                "{:>14} {:>11.1}% {:>11.1}%  {node}",
+                "",
+                share(&baseline),
+                share(&candidate)
+            );
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+fn simulate(nodes: &[String], entries: &[JournalEntry], start: u64, end: u64, policy: Policy) -> Outcome {
+    let mut online = nodes.iter().map(|node| (node.as_str(), true)).collect::<BTreeMap<_, _>>();
+    let mut load = nodes.iter().map(|node| (node.as_str(), 0.0)).collect::<BTreeMap<_, f64>>();
+    let mut outcome = Outcome::default();
+    let mut entries = entries.iter().peekable();
+
+    let mut ts = start;
+    while ts <= end {
+        while let Some(entry) = entries.next_if(|entry| entry.ts <= ts) {
+            if let Some(state) = online.get_mut(entry.subject.as_str()) {
+                match entry.kind {
+                    JournalKind::Demoted | JournalKind::Archived => *state = false,
+                    JournalKind::Restored | JournalKind::Unarchived => *state = true,
+                    _ => {}
+                }
+            }
+        }
+
+        load.values_mut().for_each(|load| *load *= RETENTION);
+
+        for _ in 0..ELECTIONS_PER_STEP {
+            outcome.elections += 1;
+            let mut candidates = online
+                .iter()
+                .filter(|(_, online)| **online)
+                .map(|(node, _)| *node)
+                .collect::<Vec<_>>();
+            candidates.sort_by(|a, b| load[a].total_cmp(&load[b]));
+
+            match policy.select_index(candidates.len()) {
+                Some(index) => {
+                    let node = candidates[index];
+                    *load.get_mut(node).unwrap() += 1.0;
+                    *outcome.picks.entry(node.to_string()).or_default() += 1;
+                }
+                None => outcome.failures += 1,
+            }
+        }
+
+        ts += STEP_SEC;
+    }
+
+    outcome
+}
@@ -0,0 +1,98 @@
+//!
+//! Latency SLO: the RPC latency percentile of each node connection over
+//! `[slo] window-min` is compared to the latency objective of the node
+//! network. Nodes violating it are flagged `slow` and ranked after the
+//! nodes meeting the objective (they remain electable). A slow node is
+//! reinstated once its latency stays below `recover-ratio` of the
+//! objective for `recover-min`, so that nodes hovering around the
+//! objective do not flap between states.
+//!
+
+use crate::imports::*;
+use std::collections::VecDeque;
+
+/// Latency samples and SLO state of a connection
+#[derive(Default)]
+pub struct Latency {
+    // (sample time, latency) in msec, within the SLO window
+    samples: Mutex<VecDeque<(u64, u64)>>,
+    is_slow: AtomicBool,
+    // time (msec) since the latency is below the reinstatement threshold
+    recovering_since: AtomicU64,
+}
+
+impl Latency {
+    /// Record the latency of a successful RPC call
+    pub fn record(&self, latency: Duration) {
+        let now = unix_now_msec();
+        let window = SloSettings::window().as_millis() as u64;
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back((now, latency.as_millis() as u64));
+        while samples
+            .front()
+            .is_some_and(|(ts, _)| now.saturating_sub(*ts) > window)
+        {
+            samples.pop_front();
+        }
+    }
+
+    /// Latency percentile (`[slo] percentile`) over the SLO window,
+    /// `None` with fewer than `min-samples` samples
+    pub fn percentile(&self) -> Option<Duration> {
+        let mut latencies = self
+            .samples
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, latency)| *latency)
+            .collect::<Vec<_>>();
+        if latencies.is_empty() || latencies.len() < SloSettings::min_samples() {
+            return None;
+        }
+
+        latencies.sort_unstable();
+        let rank = (SloSettings::percentile() / 100.0 * latencies.len() as f64).ceil() as usize;
+        let index = rank.clamp(1, latencies.len()) - 1;
+        Some(Duration::from_millis(latencies[index]))
+    }
+
+    #[inline]
+    pub fn is_slow(&self) -> bool {
+        self.is_slow.load(Ordering::Relaxed)
+    }
+
+    /// Evaluate the latency objective of `network`,
+    /// returning the new state (`true` if slow) on a transition
+    pub fn evaluate(&self, network: &NetworkId) -> Option<bool> {
+        if !SloSettings::enable() {
+            return self.is_slow.swap(false, Ordering::Relaxed).then_some(false);
+        }
+
+        let latency = self.percentile()?;
+        let objective = SloSettings::latency(network);
+        if !self.is_slow() {
+            if latency > objective {
+                self.recovering_since.store(0, Ordering::Relaxed);
+                self.is_slow.store(true, Ordering::Relaxed);
+                return Some(true);
+            }
+        } else if latency.as_secs_f64() <= objective.as_secs_f64() * SloSettings::recover_ratio() {
+            let now = unix_now_msec();
+            let since = match self.recovering_since.load(Ordering::Relaxed) {
+                0 => {
+                    self.recovering_since.store(now, Ordering::Relaxed);
+                    now
+                }
+                since => since,
+            };
+            if now.saturating_sub(since) >= SloSettings::recover().as_millis() as u64 {
+                self.recovering_since.store(0, Ordering::Relaxed);
+                self.is_slow.store(false, Ordering::Relaxed);
+                return Some(false);
+            }
+        } else {
+            self.recovering_since.store(0, Ordering::Relaxed);
+        }
+        None
+    }
+}
@@ -0,0 +1,238 @@
+//!
+//! Publishing of signed fleet snapshots to S3-compatible object storage.
+//!
+//! Each snapshot is stored under its content hash (`snapshots/<sha256>.json`),
+//! uploaded only when the node list or a node status changed, and a
+//! `latest.json` pointer (carrying the publication time) is updated on
+//! every publication to reference the most recent snapshot. The pointer
+//! carries a Schnorr signature of `sha256(hash || ts)` (the snapshot hash
+//! followed by the big-endian `u64` publication time), allowing clients
+//! to verify snapshots fetched from untrusted mirrors and to reject
+//! replayed pointers. The public key is not published with the pointer:
+//! clients must pin it (it is logged at startup).
+//!
+
+use crate::imports::*;
+use crate::utils::to_hex;
+use chrono::prelude::*;
+use hmac::{Hmac, Mac};
+use secp256k1::{Keypair, Message, SECP256K1};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct SnapshotNode<'a> {
+    uid: &'a str,
+    id: &'a str,
+    url: &'a str,
+    network: NetworkId,
+    protocol: ProtocolKind,
+    encoding: EncodingKind,
+    status: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Snapshot<'a> {
+    version: &'static str,
+    nodes: Vec<SnapshotNode<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Latest {
+    ts: u64,
+    hash: String,
+    object: String,
+    size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+/// S3 credentials and signing key obtained from the environment:
+/// `RESOLVER_S3_ACCESS_KEY`, `RESOLVER_S3_SECRET_KEY`, `RESOLVER_S3_REGION`
+/// (default `us-east-1`) and an optional `RESOLVER_SNAPSHOT_KEY`
+/// (hex secp256k1 secret key) used to sign snapshots.
+pub struct Publisher {
+    url: reqwest::Url,
+    access_key: String,
+    secret_key: String,
+    region: String,
+    keypair: Option<Keypair>,
+    previous: Mutex<Option<String>>,
+    is_publishing: AtomicBool,
+}
+
+impl Publisher {
+    pub fn try_new(url: &str) -> Result<Self> {
+        let url = reqwest::Url::parse(url.trim_end_matches('/'))
+            .map_err(|err| Error::config(format!("Invalid snapshot url `{url}`: {err}")))?;
+        let env = |name: &str| {
+            std::env::var(name).map_err(|_| Error::config(format!("Missing `{name}` env var")))
+        };
+        let access_key = env("RESOLVER_S3_ACCESS_KEY")?;
+        let secret_key = env("RESOLVER_S3_SECRET_KEY")?;
+        let region = env("RESOLVER_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let keypair = match std::env::var("RESOLVER_SNAPSHOT_KEY") {
+            Ok(key) => {
+                let keypair = Keypair::from_seckey_str(SECP256K1, key.trim())
+                    .map_err(|err| Error::config(format!("Invalid snapshot key: {err}")))?;
+                log_info!("Snapshot", "signing with public key {}", keypair.x_only_public_key().0);
+                Some(keypair)
+            }
+            Err(_) => {
+                log_warn!("Snapshot", "`RESOLVER_SNAPSHOT_KEY` is not set, snapshots will not be signed");
+                None
+            }
+        };
+
+        Ok(Self {
+            url,
+            access_key,
+            secret_key,
+            region,
+            keypair,
+            previous: Mutex::new(None),
+            is_publishing: AtomicBool::new(false),
+        })
+    }
+
+    /// Publish a snapshot of `connections`, skipped while the
+    /// previous publication is still in progress
+    pub async fn publish(&self, connections: &[Arc<Connection>]) -> Result<()> {
+        if self.is_publishing.swap(true, Ordering::AcqRel) {
+            log_warn!("Snapshot", "previous publication still in progress, skipping");
+            return Ok(());
+        }
+        let result = self.upload(connections).await;
+        self.is_publishing.store(false, Ordering::Release);
+        result
+    }
+
+    async fn upload(&self, connections: &[Arc<Connection>]) -> Result<()> {
+        let mut nodes = connections
+            .iter()
+            .filter(|connection| connection.is_delegate())
+            .map(|connection| {
+                let node = connection.node();
+                SnapshotNode {
+                    uid: node.uid_as_str(),
+                    id: node.short_id(),
+                    url: node.address(),
+                    network: node.network,
+                    protocol: node.params().protocol(),
+                    encoding: node.params().encoding(),
+                    status: connection.status(),
+                }
+            })
+            .collect::<Vec<_>>();
+        // connections are ordered by score, sort the nodes so that the
+        // content address only changes with the node list and statuses
+        nodes.sort_by(|a, b| a.uid.cmp(b.uid));
+
+        // the content address covers the node payload only, so that
+        // unchanged snapshots are not uploaded again
+        let snapshot = Snapshot {
+            version: crate::VERSION,
+            nodes,
+        };
+        let data = serde_json::to_vec(&snapshot)?;
+        let digest: [u8; 32] = Sha256::digest(&data).into();
+        let hash = to_hex(&digest);
+
+        let object = format!("snapshots/{hash}.json");
+        if self.previous.lock().unwrap().as_deref() != Some(hash.as_str()) {
+            self.put(&object, data.clone()).await?;
+        }
+
+        // the pointer is re-signed with every publication, so that
+        // clients can reject pointers older than the publishing interval
+        let ts = unix_now();
+        let signature = self.keypair.as_ref().map(|keypair| {
+            let mut hasher = Sha256::new();
+            hasher.update(digest);
+            hasher.update(ts.to_be_bytes());
+            let message = Message::from_digest(hasher.finalize().into());
+            SECP256K1.sign_schnorr_no_aux_rand(&message, keypair).to_string()
+        });
+
+        let latest = Latest {
+            ts,
+            hash: hash.clone(),
+            object,
+            size: data.len(),
+            signature,
+        };
+        self.put("latest.json", serde_json::to_vec(&latest)?).await?;
+
+        self.previous.lock().unwrap().replace(hash);
+        Ok(())
+    }
+
+    /// PUT an object using AWS Signature Version 4 (path-style url).
+    async fn put(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let mut url = self.url.clone();
+        let path = format!("{}/{}", url.path().trim_end_matches('/'), key);
+        url.set_path(&path);
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            _ => return Err(Error::config(format!("Invalid snapshot url: `{url}`"))),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = to_hex(&Sha256::digest(&data));
+
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\nhost;x-amz-content-sha256;x-amz-date\n{payload_hash}",
+            url.path()
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = hmac(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        let signing_key = hmac(&signing_key, self.region.as_bytes());
+        let signing_key = hmac(&signing_key, b"s3");
+        let signing_key = hmac(&signing_key, b"aws4_request");
+        let signature = to_hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+            self.access_key
+        );
+
+        let response = reqwest::Client::new()
+            .put(url)
+            .timeout(SnapshotSettings::timeout())
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+            .header("content-type", "application/json")
+            .body(data)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(Error::custom(format!(
+                "Snapshot upload of `{key}` failed: {}",
+                response.status()
+            )))
+        }
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
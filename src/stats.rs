@@ -0,0 +1,294 @@
+use crate::imports::*;
+use std::collections::{BTreeMap, VecDeque};
+
+const DAY_SEC: u64 = 24 * 60 * 60;
+
+/// Node statistics for one UTC day
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Day {
+    /// Day number (UNIX seconds / 86400)
+    pub day: u64,
+    /// Seconds the node was monitored by the resolver
+    pub observed: u64,
+    /// Seconds the node was online
+    pub online: u64,
+    /// Highest client count
+    pub peak_clients: u64,
+    // client count sum and number of samples (for the average)
+    clients: u64,
+    samples: u64,
+}
+
+impl Day {
+    pub fn avg_clients(&self) -> u64 {
+        self.clients.checked_div(self.samples).unwrap_or_default()
+    }
+}
+
+/// Operational history of a node (by uid), retained across restarts
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NodeStats {
+    /// Time (UNIX seconds) the node was first monitored
+    pub first_seen: u64,
+    /// Time (UNIX seconds) the node was last seen online
+    #[serde(default)]
+    pub last_seen: Option<u64>,
+    /// Number of online to offline transitions
+    #[serde(default)]
+    pub outages: u64,
+    /// Daily history, oldest first, at most `[stats] history-days`
+    #[serde(default)]
+    pub days: VecDeque<Day>,
+    /// Learned hour-of-week client load profile (see [`Seasonality`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seasonality: Vec<Option<f64>>,
+    #[serde(default)]
+    online: bool,
+}
+
+impl NodeStats {
+    /// Fraction of the monitored time the node was online
+    pub fn uptime(&self) -> Option<f64> {
+        let (online, observed) = self.days.iter().fold((0, 0), |(online, observed), day| {
+            (online + day.online, observed + day.observed)
+        });
+        (observed > 0).then(|| online as f64 / observed as f64)
+    }
+
+    /// Average client count over the retained history
+    pub fn avg_clients(&self) -> u64 {
+        let total = self.days.iter().fold(Day::default(), |mut total, day| {
+            total.clients += day.clients;
+            total.samples += day.samples;
+            total
+        });
+        total.avg_clients()
+    }
+
+    fn sample(&mut self, now: u64, elapsed: u64, online: bool, clients: u64) {
+        if self.online && !online {
+            self.outages += 1;
+        }
+        self.online = online;
+        if online {
+            self.last_seen = Some(now);
+        }
+
+        let today = now / DAY_SEC;
+        if self.days.back().is_none_or(|day| day.day != today) {
+            self.days.push_back(Day {
+                day: today,
+                ..Default::default()
+            });
+        }
+        while self.days.len() > StatsSettings::history_days() {
+            self.days.pop_front();
+        }
+
+        let day = self.days.back_mut().unwrap();
+        day.observed += elapsed;
+        if online {
+            day.online += elapsed;
+        }
+        day.peak_clients = day.peak_clients.max(clients);
+        day.clients += clients;
+        day.samples += 1;
+    }
+}
+
+/// Statistics summary served at `/admin/stats`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StatsReport {
+    pub uid: String,
+    pub node: Option<String>,
+    pub first_seen: u64,
+    pub last_seen: Option<u64>,
+    pub outages: u64,
+    pub uptime: Option<f64>,
+    pub avg_clients: u64,
+    pub peak_clients: u64,
+    pub days: VecDeque<Day>,
+}
+
+/// Persistence backend of the node statistics
+pub trait StatsStore: Send + Sync {
+    fn load(&self) -> Result<BTreeMap<String, NodeStats>>;
+    fn store(&self, stats: &BTreeMap<String, NodeStats>) -> Result<()>;
+}
+
+/// Statistics stored as a JSON file (`~/.vecno-resolver/stats.json`)
+pub struct JsonStore {
+    path: PathBuf,
+}
+
+impl JsonStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl StatsStore for JsonStore {
+    fn load(&self) -> Result<BTreeMap<String, NodeStats>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::default());
+        }
+        let json =
+            std::fs::read_to_string(&self.path).map_err(|err| Error::file(&self.path, err))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn store(&self, stats: &BTreeMap<String, NodeStats>) -> Result<()> {
+        // write to a temporary file first, so that an interrupted
+        // write does not truncate the history
+        let tmp = self.path.with_extension("json.tmp");
+        let json = serde_json::to_string(stats)?;
+        std::fs::write(&tmp, json).map_err(|err| Error::file(&tmp, err))?;
+        std::fs::rename(&tmp, &self.path).map_err(|err| Error::file(&self.path, err))
+    }
+}
+
+/// Per-node uptime history, last-seen time and client counts,
+/// sampled by the monitor and persisted every `[stats] persist-sec`
+/// (and on shutdown), so that chronically unreliable nodes can be
+/// spotted across resolver restarts. The statistics are shared by the
+/// monitors of all services (keyed by node uid).
+pub struct Stats {
+    store: Box<dyn StatsStore>,
+    nodes: RwLock<BTreeMap<String, NodeStats>>,
+    // time (UNIX seconds) of the last sample of each node
+    last_sample: Mutex<HashMap<String, u64>>,
+    // time (UNIX seconds) of the last store
+    last_store: AtomicU64,
+}
+
+impl Stats {
+    pub fn get() -> &'static Self {
+        static STATS: OnceLock<Stats> = OnceLock::new();
+        STATS.get_or_init(Stats::load)
+    }
+
+    fn load() -> Self {
+        Self::with_store(Box::new(JsonStore::new(
+            global_config_folder().join("stats.json"),
+        )))
+    }
+
+    pub fn with_store(store: Box<dyn StatsStore>) -> Self {
+        let nodes = store.load().unwrap_or_else(|err| {
+            log_error!("Stats", "Unable to load node statistics: {err}");
+            BTreeMap::default()
+        });
+
+        Self {
+            store,
+            nodes: RwLock::new(nodes),
+            last_sample: Mutex::new(HashMap::new()),
+            last_store: AtomicU64::new(unix_now()),
+        }
+    }
+
+    /// Sample the state of `connections` (delegates only, delegators
+    /// share the node state), persisting the statistics periodically
+    pub fn update(&self, connections: &[Arc<Connection>]) {
+        if !StatsSettings::enable() {
+            return;
+        }
+
+        let now = unix_now();
+        {
+            let mut last_sample = self.last_sample.lock().unwrap();
+            let mut nodes = self.nodes.write().unwrap();
+            for connection in connections
+                .iter()
+                .filter(|connection| connection.is_delegate())
+            {
+                let node = connection.node();
+                let state = connection.state();
+                // the first sample of a node after startup only establishes the baseline
+                let elapsed = last_sample
+                    .insert(node.uid_as_str().to_string(), now)
+                    .map_or(0, |last| now.saturating_sub(last));
+                nodes
+                    .entry(node.uid_as_str().to_string())
+                    .or_insert_with(|| NodeStats {
+                        first_seen: now,
+                        ..Default::default()
+                    })
+                    .sample(now, elapsed, state.is_online, state.clients);
+            }
+        }
+
+        let last_store = self.last_store.load(Ordering::Relaxed);
+        if now.saturating_sub(last_store) >= StatsSettings::persist().as_secs() {
+            self.persist();
+        }
+    }
+
+    /// Learned seasonality buckets of the node `uid`
+    pub fn seasonality(&self, uid: &str) -> Vec<Option<f64>> {
+        self.nodes
+            .read()
+            .unwrap()
+            .get(uid)
+            .map(|stats| stats.seasonality.clone())
+            .unwrap_or_default()
+    }
+
+    /// Retain the seasonality `buckets` of the node `uid`
+    /// (stored with the next persist)
+    pub fn set_seasonality(&self, uid: &str, buckets: Vec<Option<f64>>) {
+        let now = unix_now();
+        self.nodes
+            .write()
+            .unwrap()
+            .entry(uid.to_string())
+            .or_insert_with(|| NodeStats {
+                first_seen: now,
+                ..Default::default()
+            })
+            .seasonality = buckets;
+    }
+
+    /// Store the statistics
+    pub fn persist(&self) {
+        self.last_store.store(unix_now(), Ordering::Relaxed);
+        let nodes = self.nodes.read().unwrap();
+        if let Err(err) = self.store.store(&nodes) {
+            log_error!("Stats", "Unable to store node statistics: {err}");
+        }
+    }
+
+    /// Statistics of all known nodes, including nodes no longer
+    /// configured (`node` is `None` for these)
+    pub fn report(&self, connections: &[Arc<Connection>]) -> Vec<StatsReport> {
+        let nodes = self.nodes.read().unwrap();
+        nodes
+            .iter()
+            .map(|(uid, stats)| {
+                let node = connections
+                    .iter()
+                    .find(|connection| connection.node().uid_as_str() == uid)
+                    .map(|connection| connection.node().to_string());
+                StatsReport {
+                    uid: uid.clone(),
+                    node,
+                    first_seen: stats.first_seen,
+                    last_seen: stats.last_seen,
+                    outages: stats.outages,
+                    uptime: stats.uptime(),
+                    avg_clients: stats.avg_clients(),
+                    peak_clients: stats
+                        .days
+                        .iter()
+                        .map(|day| day.peak_clients)
+                        .max()
+                        .unwrap_or_default(),
+                    days: stats.days.clone(),
+                }
+            })
+            .collect()
+    }
+}
@@ -5,8 +5,14 @@ use axum::{
     body::Body,
     extract::Form,
     http::{header, HeaderValue, Request, StatusCode},
-    response::{IntoResponse, Redirect, Response},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Redirect, Response,
+    },
 };
+use futures::StreamExt;
+use std::collections::BTreeMap;
+use tokio::sync::broadcast::error::RecvError;
 
 pub enum RequestKind {
     AsHtml(Request<Body>),
@@ -86,32 +92,137 @@ pub fn resolve_session(
 pub async fn json_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
     if session_from_req(resolver, &req).is_some() {
         let connections = resolver.connections(); //.iter().map(Status::from).collect::<Vec<_>>();
-        let connections = connections.iter().map(Status::from).collect::<Vec<_>>();
+        let connections = connections
+            .iter()
+            .map(|connection| Status::from(connection).with_annotations(resolver.annotations()))
+            .collect::<Vec<_>>();
         let nodes = serde_json::to_string(&connections).unwrap();
-        Response::builder()
-            .status(StatusCode::OK)
-            .header(header::CONTENT_TYPE, "application/json")
-            .header(
-                header::CACHE_CONTROL,
-                HeaderValue::from_static(
-                    "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
-                ),
-            )
-            .body(Body::from(nodes))
-            .unwrap()
+        json_response(StatusCode::OK, nodes)
     } else {
-        Response::builder()
-            .status(StatusCode::UNAUTHORIZED)
-            .header(header::CONTENT_TYPE, "application/json")
-            .header(
-                header::CACHE_CONTROL,
-                HeaderValue::from_static(
-                    "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
-                ),
-            )
-            .body(Body::from(""))
-            .unwrap()
+        json_response(StatusCode::UNAUTHORIZED, "")
+    }
+}
+
+/// Stream node status transitions as Server-Sent Events; requires a
+/// session unless the resolver serves public status (`--public`).
+pub async fn events_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> Response {
+    if !resolver.args().public() && session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+    if FdGuard::get().is_exhausted() {
+        return fdguard::refuse();
     }
+
+    let receiver = Feed::get().subscribe();
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .map(|event| Event::default().event("status").json_data(&event));
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Dry run of the elections of `network`: the ranked nodes of each
+/// path with the filters they fail, keyed by service and path. Requires
+/// a session unless the resolver serves public status (`--public`).
+pub async fn candidates_handler(
+    resolver: &Arc<Resolver>,
+    network: NetworkId,
+    req: Request<Body>,
+) -> impl IntoResponse {
+    if !resolver.args().public() && session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    // candidates are ranked per service
+    let candidates = resolver
+        .monitors()
+        .map(|monitor| {
+            let paths = PathParams::iter_tls_strict()
+                .filter(|params| params.network == network)
+                .filter_map(|params| Some((params.to_string(), monitor.candidates(&params)?)))
+                .collect::<BTreeMap<_, _>>();
+            (monitor.service(), paths)
+        })
+        .filter(|(_, paths)| !paths.is_empty())
+        .collect::<BTreeMap<_, _>>();
+    if candidates.is_empty() {
+        json_response(StatusCode::NOT_FOUND, "")
+    } else {
+        json_response(StatusCode::OK, serde_json::to_string(&candidates).unwrap())
+    }
+}
+
+/// Status of a specific subset of nodes. Accepts a JSON array
+/// of node ids (hex uids or short ids) and responds with the matching entries.
+pub async fn query_handler(resolver: &Arc<Resolver>, req: Request<Body>) -> impl IntoResponse {
+    if session_from_req(resolver, &req).is_none() {
+        return json_response(StatusCode::UNAUTHORIZED, "");
+    }
+
+    let Ok(body) = axum::body::to_bytes(req.into_body(), QUERY_BODY_LIMIT).await else {
+        return json_response(StatusCode::BAD_REQUEST, "");
+    };
+
+    let Ok(uids) = serde_json::from_slice::<Vec<String>>(&body) else {
+        return json_response(StatusCode::BAD_REQUEST, "");
+    };
+
+    let uids = uids
+        .iter()
+        .map(|uid| uid.trim().to_lowercase())
+        .collect::<HashSet<_>>();
+
+    let connections = resolver.connections();
+    let connections = connections
+        .iter()
+        .filter(|connection| uids.iter().any(|uid| connection.node().is_id(uid)))
+        .map(|connection| Status::from(connection).with_annotations(resolver.annotations()))
+        .collect::<Vec<_>>();
+    let nodes = serde_json::to_string(&connections).unwrap();
+    json_response(StatusCode::OK, nodes)
+}
+
+const QUERY_BODY_LIMIT: usize = 64 * 1024;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReadyQuery {
+    stage: Option<Stage>,
+}
+
+/// Readiness probe: responds with `200` if the requested stage
+/// (`serving` by default) is ready and `503` otherwise, the body
+/// reports all stages.
+pub async fn ready_handler(query: ReadyQuery) -> impl IntoResponse {
+    let readiness = Readiness::get();
+    let status = if readiness.is_ready(query.stage.unwrap_or_default()) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    json_response(status, serde_json::to_string(&readiness.report()).unwrap())
+}
+
+pub fn json_response(status: StatusCode, body: impl Into<Body>) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(
+                "no-cache, no-store, must-revalidate, proxy-revalidate, max-age=0",
+            ),
+        )
+        .body(body.into())
+        .unwrap()
 }
 
 pub async fn status_handler(resolver: &Arc<Resolver>, req: RequestKind) -> impl IntoResponse {
@@ -178,6 +289,7 @@ pub struct Status<'a> {
     pub sid: u64,
     #[serde(with = "SerHex::<Strict>")]
     pub uid: u64,
+    pub id: &'a str,
     pub url: &'a str,
     pub fqdn: &'a str,
     pub service: String,
@@ -192,8 +304,53 @@ pub struct Status<'a> {
     pub peers: u64,
     pub clients: u64,
     pub capacity: u64,
+    pub retries: u64,
+    pub recoveries: u64,
+    pub probe_failures: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probe_error: Option<String>,
+    // violating the latency SLO
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub slow: bool,
+    // RPC latency percentile over the SLO window (msec)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<u64>,
+    // client failure spike pending a probe
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub suspect: bool,
+    // client failure reports within the feedback window
+    pub failure_reports: usize,
+    // virtual DAA score (with `[divergence]` enabled)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daa_score: Option<u64>,
+    // lagging behind the DAA score of the network
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub lagging: bool,
+    // reporting a version below the minimum node version
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub outdated: bool,
+    // peer count collapsed below the network health thresholds
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub isolated: bool,
+    // endpoint certificate expiry (UNIX seconds, `wss://` nodes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_expiry: Option<u64>,
+    // certificate within the `[cert-expiry]` window
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cert_expiring: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bandwidth: Option<Bandwidth>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delegates: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<Annotation>,
+}
+
+impl<'a> Status<'a> {
+    pub fn with_annotations(mut self, annotations: &Annotations) -> Self {
+        self.annotation = annotations.get(&format!("{:016x}", self.uid));
+        self
+    }
 }
 
 impl<'a> From<&'a Arc<Connection>> for Status<'a> {
@@ -202,6 +359,7 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
 
         let node = connection.node();
         let uid = node.uid();
+        let id = node.short_id();
         let url = node.address.as_str();
         let fqdn = node.fqdn.as_str();
         let service = node.service().to_string();
@@ -210,11 +368,26 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
         let encryption = node.params().tls();
         let network = &node.network;
         let status = connection.status();
-        let clients = delegate.clients();
-        let peers = delegate.peers();
-        let (version, sid, capacity, cores, memory) = delegate
-            .caps()
-            .as_ref()
+        let state = delegate.state();
+        let clients = state.clients;
+        let peers = state.peers;
+        let retries = connection.retries();
+        let recoveries = connection.recoveries();
+        let probe_failures = connection.probe_failures();
+        let probe_error = connection.custom_probe_error();
+        let slow = connection.is_slow();
+        let latency = connection.latency().map(|latency| latency.as_millis() as u64);
+        let suspect = connection.is_suspect();
+        let failure_reports = connection.failure_reports();
+        let daa_score = Some(connection.daa_score()).filter(|score| *score > 0);
+        let lagging = connection.is_lagging();
+        let outdated = connection.is_outdated();
+        let isolated = connection.is_isolated();
+        let cert_expiry = connection.cert_expiry();
+        let cert_expiring = connection.is_cert_expiring();
+        let bandwidth = connection.bandwidth();
+        let (version, sid, capacity, cores, memory) = state
+            .caps
             .as_ref()
             .map(|caps| {
                 (
@@ -230,13 +403,14 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
         let delegates = connection
             .resolve_delegators()
             .iter()
-            .map(|connection| format!("[{:016x}] {}", connection.system_id(), connection.address()))
+            .map(|connection| connection.node().to_string())
             .collect::<Vec<String>>();
         let delegates = (!delegates.is_empty()).then_some(delegates);
 
         Self {
             sid,
             uid,
+            id,
             version,
             fqdn,
             service,
@@ -251,7 +425,23 @@ impl<'a> From<&'a Arc<Connection>> for Status<'a> {
             clients,
             peers,
             capacity,
+            retries,
+            recoveries,
+            probe_failures,
+            probe_error,
+            slow,
+            latency,
+            suspect,
+            failure_reports,
+            daa_score,
+            lagging,
+            outdated,
+            isolated,
+            cert_expiry,
+            cert_expiring,
+            bandwidth,
             delegates,
+            annotation: None,
         }
     }
 }
@@ -0,0 +1,219 @@
+//!
+//! Live resolution subscriptions (`/v1/subscribe`): a client opens a
+//! WebSocket with its election parameters and the uid of its assigned
+//! node, and is pushed a replacement assignment as soon as that node
+//! leaves the available set, instead of detecting the failure itself
+//! and re-querying. Assignments are checked every `[subscribe]
+//! check-msec`; at most `max-clients` subscriptions are served at once.
+//!
+
+use crate::imports::*;
+use crate::status::json_response;
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::SinkExt;
+use serde_json::{json, Value};
+use std::net::IpAddr;
+
+#[derive(Debug, Deserialize)]
+pub struct SubscribeQuery {
+    /// service of the nodes (`vecno` by default)
+    pub service: Option<Service>,
+    pub network: NetworkId,
+    pub tls: TlsKind,
+    pub protocol: ProtocolKind,
+    pub encoding: EncodingKind,
+    /// uid (or short id) of the node the client is currently using,
+    /// a node is elected on subscription if omitted or unavailable
+    pub uid: Option<String>,
+    /// node role to elect (`public-api` by default)
+    pub role: Option<Role>,
+}
+
+/// Open subscription, released when the socket closes
+struct SubscriptionGuard;
+
+impl SubscriptionGuard {
+    fn subscribers() -> &'static AtomicU64 {
+        static SUBSCRIBERS: AtomicU64 = AtomicU64::new(0);
+        &SUBSCRIBERS
+    }
+
+    fn try_new() -> Option<Self> {
+        let subscribers = Self::subscribers();
+        if subscribers.fetch_add(1, Ordering::Relaxed) >= SubscribeSettings::max_clients() {
+            subscribers.fetch_sub(1, Ordering::Relaxed);
+            None
+        } else {
+            Some(Self)
+        }
+    }
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        Self::subscribers().fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Subscription of a client to its node assignment
+struct Subscription {
+    resolver: Arc<Resolver>,
+    service: Service,
+    params: PathParams,
+    role: Role,
+    reserved: bool,
+    client: IpAddr,
+    assigned: Option<Arc<Connection>>,
+}
+
+impl Subscription {
+    /// Node `uid` if it is available to the subscriber
+    fn find(&self, uid: &str) -> Option<Arc<Connection>> {
+        self.resolver
+            .service_monitor(self.service)
+            .find(uid)
+            .filter(|connection| {
+                let params = connection.params();
+                connection.is_delegate()
+                    && (params == self.params || params.to_tls(TlsKind::Any) == self.params)
+                    && connection.node().has_role(self.role)
+                    && connection.is_available_for(self.reserved)
+            })
+    }
+
+    /// Elect a node for the subscriber, moving it off `previous`
+    fn elect(&mut self, previous: Option<&str>) -> Option<Value> {
+        let monitor = self.resolver.service_monitor(self.service);
+        let elected =
+            monitor.election(&self.params, self.reserved, previous, self.role, None, self.client)?;
+        let output = serde_json::from_slice::<Value>(&elected.json).ok()?;
+        self.assigned = output
+            .get("uid")
+            .and_then(Value::as_str)
+            .and_then(|uid| monitor.find(uid));
+        Some(output)
+    }
+
+    /// Initial assignment: the node the client is using if available
+    fn assign(&mut self, uid: Option<&str>) -> Value {
+        if let Some(connection) = uid.and_then(|uid| self.find(uid)) {
+            let output = serde_json::from_slice::<Value>(&connection.output_json()).unwrap_or_default();
+            self.assigned = Some(connection);
+            return json!({ "event" : "assigned", "node" : output });
+        }
+        match self.elect(uid) {
+            Some(output) => json!({ "event" : "assigned", "node" : output }),
+            None => json!({ "event" : "unavailable" }),
+        }
+    }
+
+    /// Replacement assignment if the assigned node left the available
+    /// set (or no node was available before), `None` if unchanged
+    fn check(&mut self) -> Option<Value> {
+        let previous = match self.assigned.as_ref() {
+            Some(connection) => {
+                let reason = connection.switch_reason(self.reserved)?;
+                Some((connection.node().uid_as_str().to_string(), reason))
+            }
+            None => None,
+        };
+
+        let uid = previous.as_ref().map(|(uid, _)| uid.as_str());
+        match (self.elect(uid), previous) {
+            (Some(output), Some((uid, reason))) => Some(json!({
+                "event" : "reassigned",
+                "previous" : uid,
+                "reason" : reason,
+                "node" : output,
+            })),
+            (Some(output), None) => Some(json!({ "event" : "assigned", "node" : output })),
+            (None, Some((uid, reason))) => {
+                self.assigned = None;
+                Some(json!({
+                    "event" : "unavailable",
+                    "previous" : uid,
+                    "reason" : reason,
+                }))
+            }
+            // still no node available
+            (None, None) => None,
+        }
+    }
+}
+
+/// `GET /v1/subscribe`: upgrade to a subscription WebSocket
+pub async fn subscribe_handler(
+    resolver: &Arc<Resolver>,
+    client: IpAddr,
+    headers: &HeaderMap,
+    query: SubscribeQuery,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if resolver.is_shutting_down() {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    if FdGuard::get().is_exhausted() {
+        return fdguard::refuse();
+    }
+    let Some(guard) = SubscriptionGuard::try_new() else {
+        let body = json!({ "error" : "subscription limit reached, retry later" });
+        return json_response(StatusCode::SERVICE_UNAVAILABLE, body.to_string());
+    };
+
+    let subscription = Subscription {
+        resolver: resolver.clone(),
+        service: query.service.unwrap_or(Service::Vecno),
+        params: PathParams {
+            protocol: query.protocol,
+            encoding: query.encoding,
+            network: query.network,
+            tls: query.tls,
+        },
+        role: query.role.unwrap_or(Role::PublicApi),
+        reserved: resolver.is_reserved(headers),
+        client,
+        assigned: None,
+    };
+    let uid = query.uid;
+    ws.on_upgrade(move |socket| async move {
+        let _guard = guard;
+        run(socket, subscription, uid).await;
+    })
+}
+
+async fn run(socket: WebSocket, mut subscription: Subscription, uid: Option<String>) {
+    let (mut sender, mut receiver) = socket.split();
+    let initial = subscription.assign(uid.as_deref());
+    if sender.send(Message::Text(initial.to_string())).await.is_err() {
+        return;
+    }
+
+    let mut check = interval(SubscribeSettings::check());
+    loop {
+        select! {
+            _ = check.next().fuse() => {
+                if subscription.resolver.is_shutting_down() {
+                    let _ = sender.send(Message::Close(None)).await;
+                    break;
+                }
+                if let Some(event) = subscription.check() {
+                    if sender.send(Message::Text(event.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+
+            msg = receiver.next().fuse() => {
+                match msg {
+                    // pings are answered by the WebSocket implementation
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::imports::*;
+
+/// Anonymized aggregate fleet statistics. No node addresses,
+/// hostnames or client data are included; `instance` is a random
+/// id generated with the first report so that reports can be deduplicated.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Report {
+    instance: String,
+    version: &'static str,
+    nodes: usize,
+    available: usize,
+    networks: Vec<String>,
+}
+
+/// Opt-in telemetry, enabled with the `telemetry` feature
+/// toggle (off by default) and sent to `[telemetry] url`.
+#[derive(Default)]
+pub struct Telemetry {
+    // created on the first report, so that installs
+    // with telemetry off are left without an id
+    instance: OnceLock<String>,
+    is_sending: AtomicBool,
+}
+
+impl Telemetry {
+    fn instance(&self) -> &str {
+        self.instance.get_or_init(|| {
+            let path = global_config_folder().join("telemetry-id");
+            std::fs::read_to_string(&path)
+                .ok()
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .unwrap_or_else(|| {
+                    let id = uuid::Uuid::new_v4().to_string();
+                    if let Err(err) = std::fs::write(&path, &id) {
+                        log_error!("Telemetry", "{}", Error::file(&path, err));
+                    }
+                    id
+                })
+        })
+    }
+
+    pub fn is_enabled() -> bool {
+        Features::is_enabled(crate::features::TELEMETRY) && TelemetrySettings::url().is_some()
+    }
+
+    /// Send a report, skipped while the previous report is still in progress
+    pub async fn send(&self, connections: &[Arc<Connection>]) -> Result<()> {
+        if self.is_sending.swap(true, Ordering::AcqRel) {
+            log_warn!("Telemetry", "previous report still in progress, skipping");
+            return Ok(());
+        }
+        let result = self.report(connections).await;
+        self.is_sending.store(false, Ordering::Release);
+        result
+    }
+
+    async fn report(&self, connections: &[Arc<Connection>]) -> Result<()> {
+        let Some(url) = TelemetrySettings::url() else {
+            return Ok(());
+        };
+
+        let delegates = connections
+            .iter()
+            .filter(|connection| connection.is_delegate())
+            .collect::<Vec<_>>();
+        let mut networks = delegates
+            .iter()
+            .map(|connection| connection.network_id().to_string())
+            .collect::<Vec<_>>();
+        networks.sort();
+        networks.dedup();
+
+        let report = Report {
+            instance: self.instance().to_string(),
+            version: crate::VERSION,
+            nodes: delegates.len(),
+            available: delegates
+                .iter()
+                .filter(|connection| connection.is_available())
+                .count(),
+            networks,
+        };
+
+        let response = reqwest::Client::new()
+            .post(url)
+            .timeout(TelemetrySettings::timeout())
+            .header("content-type", "application/json")
+            .body(serde_json::to_string(&report)?)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(Error::custom(format!(
+                "Telemetry report failed: {}",
+                response.status()
+            )))
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//!
+//! Custom TLS trust for outbound node connections: a PEM root CA
+//! bundle (`--tls-ca`) trusted in addition to the bundled WebPKI roots
+//! (the system trust store is not consulted), and an optional PEM client
+//! certificate and key (`--tls-cert`, `--tls-key`) presented to nodes
+//! requiring mutual TLS.
+//!
+//! Applied to gRPC (`grpcs://`) connections by the tonic connector and
+//! to wRPC (`wss://`) connections by their tunnel (see [`crate::tunnel`]).
+//!
+
+use crate::imports::*;
+use tokio_rustls::rustls::{
+    self,
+    client::Resumption,
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer, PrivateSec1KeyDer},
+    ClientConfig, RootCertStore,
+};
+use x509_parser::pem::Pem;
+
+// TLS sessions kept for resumption by `wss://` node tunnels
+const SESSION_CACHE: usize = 1024;
+
+#[derive(Debug)]
+pub struct TlsConfig {
+    /// PEM root CA bundle
+    pub ca: Option<Vec<u8>>,
+    /// PEM client certificate chain and private key
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+static TLS: OnceLock<Option<TlsConfig>> = OnceLock::new();
+
+fn read_pem(path: &Path, marker: &str) -> Result<Vec<u8>> {
+    let pem = std::fs::read(path).map_err(|err| Error::file(path, err))?;
+    if !String::from_utf8_lossy(&pem).contains(marker) {
+        return Err(Error::config(format!(
+            "`{}` is not a PEM file (missing `{marker}`)",
+            path.display()
+        )));
+    }
+    Ok(pem)
+}
+
+/// Load the TLS material given on the command line
+pub fn init(args: &Args) -> Result<()> {
+    let ca = args
+        .tls_ca
+        .as_deref()
+        .map(|path| read_pem(path, "BEGIN CERTIFICATE"))
+        .transpose()?;
+    let identity = match (args.tls_cert.as_deref(), args.tls_key.as_deref()) {
+        (Some(cert), Some(key)) => Some((
+            read_pem(cert, "BEGIN CERTIFICATE")?,
+            read_pem(key, "PRIVATE KEY")?,
+        )),
+        (None, None) => None,
+        _ => {
+            return Err(Error::config(
+                "`--tls-cert` and `--tls-key` must be specified together",
+            ))
+        }
+    };
+
+    let config = (ca.is_some() || identity.is_some()).then_some(TlsConfig { ca, identity });
+    if let Some(config) = &config {
+        log_success!(
+            "TLS",
+            "Custom TLS: root CA {}, client certificate {}",
+            if config.ca.is_some() {
+                "loaded"
+            } else {
+                "webpki"
+            },
+            if config.identity.is_some() {
+                "loaded"
+            } else {
+                "none"
+            }
+        );
+    }
+    TLS.set(config)
+        .map_err(|_| Error::custom("TLS configuration already initialized"))?;
+    // reject an unusable client certificate at startup
+    client_config()?;
+    Ok(())
+}
+
+/// Custom TLS configuration, `None` if not configured
+pub fn get() -> Option<&'static TlsConfig> {
+    TLS.get().and_then(Option::as_ref)
+}
+
+/// WebPKI roots and the `--tls-ca` bundle
+pub fn roots() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(ca) = get().and_then(|config| config.ca.as_deref()) {
+        for pem in Pem::iter_from_buffer(ca).flatten() {
+            if let Err(err) = roots.add(CertificateDer::from(pem.contents)) {
+                log_warn!("TLS", "Ignoring `--tls-ca` certificate: {err}");
+            }
+        }
+    }
+    roots
+}
+
+/// Client certificate chain and private key of `--tls-cert` and `--tls-key`
+fn identity(cert: &[u8], key: &[u8]) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let chain = Pem::iter_from_buffer(cert)
+        .flatten()
+        .filter(|pem| pem.label == "CERTIFICATE")
+        .map(|pem| CertificateDer::from(pem.contents))
+        .collect::<Vec<_>>();
+    if chain.is_empty() {
+        return Err(Error::config("`--tls-cert` holds no certificate"));
+    }
+    let key = Pem::iter_from_buffer(key)
+        .flatten()
+        .find_map(|pem| match pem.label.as_str() {
+            "PRIVATE KEY" => Some(PrivateKeyDer::from(PrivatePkcs8KeyDer::from(pem.contents))),
+            "RSA PRIVATE KEY" => Some(PrivateKeyDer::from(PrivatePkcs1KeyDer::from(pem.contents))),
+            "EC PRIVATE KEY" => Some(PrivateKeyDer::from(PrivateSec1KeyDer::from(pem.contents))),
+            _ => None,
+        })
+        .ok_or_else(|| Error::config("`--tls-key` holds no supported private key"))?;
+    Ok((chain, key))
+}
+
+/// Client configuration of `wss://` node tunnels (see [`crate::tunnel`]),
+/// shared by all tunnels so that reconnects resume cached sessions
+pub fn client_config() -> Result<Arc<ClientConfig>> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    if let Some(config) = CONFIG.get() {
+        return Ok(config.clone());
+    }
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| Error::custom(format!("TLS config: {err}")))?
+        .with_root_certificates(roots());
+    let mut config = match get().and_then(|config| config.identity.as_ref()) {
+        Some((cert, key)) => {
+            let (chain, key) = identity(cert, key)?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|err| Error::config(format!("Invalid client certificate: {err}")))?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    config.resumption = Resumption::in_memory_sessions(SESSION_CACHE);
+    Ok(CONFIG.get_or_init(|| Arc::new(config)).clone())
+}
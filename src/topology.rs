@@ -0,0 +1,253 @@
+//!
+//! Fleet topology export: node connections, their delegation edges,
+//! shared system ids, failure domains (the host serving the node, so
+//! that all endpoints of one machine share a domain) and declared
+//! regions, rendered as a graph in JSON Graph Format, Graphviz DOT
+//! or GraphML for visualization in standard graph tools.
+//!
+
+use crate::imports::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Json,
+    Dot,
+    Graphml,
+}
+
+impl Format {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Dot => "text/vnd.graphviz; charset=utf-8",
+            Format::Graphml => "application/graphml+xml; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TopologyQuery {
+    /// `json` (default), `dot` or `graphml`
+    pub format: Option<Format>,
+}
+
+struct Vertex {
+    kind: &'static str,
+    label: String,
+    metadata: BTreeMap<&'static str, String>,
+}
+
+struct Edge {
+    source: String,
+    target: String,
+    relation: &'static str,
+}
+
+#[derive(Default)]
+pub struct Graph {
+    vertices: BTreeMap<String, Vertex>,
+    edges: Vec<Edge>,
+}
+
+/// Host of a node `fqdn` (without the port)
+fn domain(fqdn: &str) -> &str {
+    match fqdn.rsplit_once(':') {
+        // `host:port` or `[ipv6]:port`, but not a bare IPv6 literal
+        Some((host, port))
+            if port.parse::<u16>().is_ok() && (!host.contains(':') || host.ends_with(']')) =>
+        {
+            host
+        }
+        _ => fqdn,
+    }
+}
+
+impl Graph {
+    /// Topology of the node `connections` (all services)
+    pub fn new(connections: &[Arc<Connection>]) -> Self {
+        let mut graph = Graph::default();
+
+        for connection in connections {
+            let node = connection.node();
+            let id = format!("node:{}", node.uid_as_str());
+
+            let mut metadata = BTreeMap::new();
+            metadata.insert("uid", node.uid_as_str().to_string());
+            metadata.insert("address", node.address().to_string());
+XX, node.transport_kind().to_string());
+            metadata.insert("delegate", connection.is_delegate().to_string());
+            metadata.insert("available", connection.is_available().to_string());
+            graph.vertex(&id, "node", node.short_id().to_string(), metadata);
+
+            if !connection.is_delegate() {
+                let delegate = connection.delegate();
+                graph.edge(&id, format!("node:{}", delegate.node().uid_as_str()), "delegate");
+            }
+
+            let system_id = connection.system_id();
+            if system_id != 0 {
+                let system = format!("system:{system_id:016x}");
+                graph.vertex(&system, "system", format!("{system_id:016x}"), BTreeMap::new());
+                graph.edge(&id, system, "system");
+            }
+
+            let host = domain(&node.fqdn);
+            let domain = format!("domain:{host}");
+            graph.vertex(&domain, "domain", host.to_string(), BTreeMap::new());
+            graph.edge(&id, domain.clone(), "domain");
+
+            let region = node.region.as_deref().unwrap_or("default");
+            let region_id = format!("region:{region}");
+            graph.vertex(&region_id, "region", region.to_string(), BTreeMap::new());
+            if !graph
+                .edges
+                .iter()
+                .any(|edge| edge.source == domain && edge.target == region_id)
+            {
+                graph.edge(&domain, region_id, "region");
+            }
+        }
+
+        graph
+    }
+
+    fn vertex(
+        &mut self,
+        id: &str,
+        kind: &'static str,
+        label: String,
+        metadata: BTreeMap<&'static str, String>,
+    ) {
+        self.vertices
+            .entry(id.to_string())
+            .or_insert(Vertex { kind, label, metadata });
+    }
+
+    fn edge(&mut self, source: &str, target: String, relation: &'static str) {
+        self.edges.push(Edge {
+            source: source.to_string(),
+            target,
+            relation,
+        });
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Json => self.to_json(),
+            Format::Dot => self.to_dot(),
+            Format::Graphml => self.to_graphml(),
+        }
+    }
+
+    /// JSON Graph Format (https://jsongraphformat.info)
+    fn to_json(&self) -> String {
+        let nodes = self
+            .vertices
+            .iter()
+            .map(|(id, vertex)| {
+                let mut metadata = serde_json::Map::new();
+                metadata.insert("kind".to_string(), vertex.kind.into());
+                for (key, value) in vertex.metadata.iter() {
+                    metadata.insert(key.to_string(), value.as_str().into());
+                }
+                (
+                    id.clone(),
+                    serde_json::json!({ "label" : vertex.label, "metadata" : metadata }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| {
+                serde_json::json!({
+                    "source" : edge.source,
+                    "target" : edge.target,
+                    "relation" : edge.relation,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            "graph" : {
+                "label" : "vecno-resolver",
+                "directed" : true,
+                "nodes" : nodes,
+                "edges" : edges,
+            }
+        })
+        .to_string()
+    }
+
+    fn to_dot(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "digraph \"vecno-resolver\" {{");
+        for (id, vertex) in self.vertices.iter() {
+            let shape = match vertex.kind {
+                "node" => "box",
+                "system" => "ellipse",
+                "domain" => "component",
+                _ => "folder",
+            };
+            let _ = writeln!(
+                text,
+                "  \"{}\" [label=\"{}\", shape={shape}];",
+                escape_dot(id),
+                escape_dot(&vertex.label)
+            );
+        }
+        for edge in self.edges.iter() {
+            let _ = writeln!(
+                text,
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape_dot(&edge.source),
+                escape_dot(&edge.target),
+                edge.relation
+            );
+        }
+        let _ = writeln!(text, "}}");
+        text
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(text, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#);
+        let _ = writeln!(text, r#"  <key id="kind" for="node" attr.name="kind" attr.type="string"/>"#);
+        let _ = writeln!(text, r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#);
+        let _ = writeln!(text, r#"  <key id="relation" for="edge" attr.name="relation" attr.type="string"/>"#);
+        let _ = writeln!(text, r#"  <graph id="vecno-resolver" edgedefault="directed">"#);
+        for (id, vertex) in self.vertices.iter() {
+            let _ = writeln!(text, r#"    <node id="{}">"#, escape_xml(id));
+            let _ = writeln!(text, r#"      <data key="kind">{}</data>"#, vertex.kind);
+            let _ = writeln!(text, r#"      <data key="label">{}</data>"#, escape_xml(&vertex.label));
+            let _ = writeln!(text, "    </node>");
+        }
+        for edge in self.edges.iter() {
+            let _ = writeln!(
+                text,
+                r#"    <edge source="{}" target="{}"><data key="relation">{}</data></edge>"#,
+                escape_xml(&edge.source),
+                escape_xml(&edge.target),
+                edge.relation
+            );
+        }
+        let _ = writeln!(text, "  </graph>");
+        let _ = writeln!(text, "</graphml>");
+        text
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -140,4 +140,8 @@ impl TransportDictionary {
     pub fn get(&self, key: &str) -> Option<&Transport> {
         self.0.get(key)
     }
+
+    pub fn extend(&mut self, other: TransportDictionary) {
+        self.0.extend(other.0);
+    }
 }
@@ -0,0 +1,276 @@
+//!
+//! Loopback tunnels of wRPC node connections. The wRPC client owns the
+//! sockets and the TLS stack of its connections and exposes neither,
+//! so node connections are routed through a tunnel opened by the
+//! resolver: the wRPC client connects to
+//! `ws://127.0.0.1:<port>/<token>/<path>` and the tunnel dials the node,
+//! restoring the path and the `Host` of the node in the WebSocket
+//! handshake. Connections without the random per-tunnel token (other
+//! local processes) are refused. Node sockets get TCP keepalive
+//! (`[keepalive] tcp-*`), so half-open connections are detected by the
+//! kernel too. `wss://` nodes are dialed over TLS with the shared client
+//! configuration of [`crate::tls`]; all tunnels share one TLS session
+//! cache, so reconnects (TTL and hard resets) resume the previous
+//! session instead of performing a full handshake.
+//!
+//! A tunneled connection holds four descriptors instead of one, so
+//! plain `ws://` nodes, which gain only TCP keepalive, are tunneled
+//! with `[keepalive] tunnel-ws` only.
+//!
+
+use crate::connection::deadline;
+use crate::imports::*;
+use socket2::{SockRef, TcpKeepalive};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::TlsConnector;
+
+// largest WebSocket handshake request accepted from the wRPC client
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Node endpoint of a tunnel
+#[derive(Debug, Clone)]
+struct Target {
+    host: String,
+    port: u16,
+    tls: bool,
+    // `host[:port]` of the node url, sent as `Host`
+    authority: String,
+    // path prefix authenticating the wRPC client
+    token: String,
+}
+
+#[derive(Debug)]
+pub struct Tunnel {
+    target: Target,
+    // path and query of the node url
+    path: String,
+    listener: Mutex<Option<(String, JoinHandle<()>)>>,
+    // relays of the accepted connections
+    relays: Arc<Mutex<Vec<AbortHandle>>>,
+}
+
+impl Tunnel {
+    /// Node `url` is connected through a tunnel: `wss` urls, and `ws`
+    /// urls with `[keepalive] tunnel-ws`
+    pub fn is_tunneled(url: &str) -> bool {
+        url.starts_with("wss://") || (url.starts_with("ws://") && KeepaliveSettings::tunnel_ws())
+    }
+
+    /// Tunnel to the node `url`, `None` for urls that are not tunneled
+    /// (see [`Tunnel::is_tunneled`])
+    pub fn try_new(url: &str) -> Result<Option<Self>> {
+        if !Self::is_tunneled(url) {
+            return Ok(None);
+        }
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|err| Error::config(format!("Invalid node url `{url}`: {err}")))?;
+        let tls = parsed.scheme() == "wss";
+        let authority = parsed
+            .host_str()
+            .map(|host| match parsed.port() {
+                Some(port) => format!("{host}:{port}"),
+                None => host.to_string(),
+            })
+            .ok_or_else(|| Error::config(format!("Node url `{url}` has no host")))?;
+        let host = parsed
+            .host_str()
+            .unwrap_or_default()
+            .trim_matches(['[', ']'])
+            .to_string();
+        let port = parsed
+            .port_or_known_default()
+            .unwrap_or(if tls { 443 } else { 80 });
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{query}", parsed.path()),
+            None => parsed.path().to_string(),
+        };
+
+        Ok(Some(Self {
+            target: Target {
+                host,
+                port,
+                tls,
+                authority,
+                token: uuid::Uuid::new_v4().simple().to_string(),
+            },
+            path,
+            listener: Mutex::new(None),
+            relays: Arc::new(Mutex::new(Vec::new())),
+        }))
+    }
+
+    /// Local url of the tunnel, the listener is opened on first use
+    pub async fn url(&self) -> Result<String> {
+        if let Some((url, _)) = self.listener.lock().unwrap().as_ref() {
+            return Ok(url.clone());
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("ws://{}/{}{}", listener.local_addr()?, self.target.token, self.path);
+        let handle = tokio::spawn(accept(listener, self.target.clone(), self.relays.clone()));
+        let mut current = self.listener.lock().unwrap();
+        if let Some((url, _)) = current.as_ref() {
+            // opened concurrently, keep the first listener
+            handle.abort();
+            return Ok(url.clone());
+        }
+        *current = Some((url.clone(), handle));
+        Ok(url)
+    }
+
+    /// Abort the relays of the tunnel, closing the client and node
+    /// sockets of its connections without a WebSocket close handshake
+    pub fn abort(&self) {
+        for relay in self.relays.lock().unwrap().drain(..) {
+            relay.abort();
+        }
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        if let Some((_, handle)) = self.listener.lock().unwrap().take() {
+            handle.abort();
+        }
+        self.abort();
+    }
+}
+
+async fn accept(listener: TcpListener, target: Target, relays: Arc<Mutex<Vec<AbortHandle>>>) {
+    loop {
+        match listener.accept().await {
+            Ok((client, _)) => {
+                let target = target.clone();
+                let handle = tokio::spawn(async move {
+                    if let Err(err) = relay(client, &target).await {
+                        log_warn!("Tunnel", "{}: {err}", target.authority);
+                    }
+                });
+                let mut relays = relays.lock().unwrap();
+                relays.retain(|relay| !relay.is_finished());
+                relays.push(handle.abort_handle());
+            }
+            Err(err) => {
+                // e.g. out of file descriptors, retried by the next connect
+                log_error!("Tunnel", "{}: {err}", target.authority);
+                workflow_core::task::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Relay a wRPC client connection to the node. The handshake, the
+/// node connection and the TLS handshake are each bounded by
+/// `[keepalive] connect-timeout-sec`, so that a relay abandoned by the
+/// wRPC client does not hold its descriptors.
+async fn relay(mut client: TcpStream, target: &Target) -> Result<()> {
+    let timeout = KeepaliveSettings::connect_timeout();
+    let (head, rest) = deadline(read_head(&mut client), timeout, "tunnel handshake").await?;
+    let head = rewrite_head(&head, target)?;
+
+    let connect = TcpStream::connect((target.host.as_str(), target.port)).map(|result| Ok(result?));
+    let stream = deadline(connect, timeout, "tunnel connect").await?;
+    stream.set_nodelay(true)?;
+    keepalive(&stream)?;
+    if !target.tls {
+        return forward(client, stream, &head, &rest).await;
+    }
+
+    let server_name = ServerName::try_from(target.host.clone())
+        .map_err(|err| Error::custom(format!("Invalid TLS server name `{}`: {err}", target.host)))?;
+    let connector = TlsConnector::from(tls::client_config()?);
+    let handshake = async move {
+        connector
+            .connect(server_name, stream)
+            .await
+            .map_err(|err| Error::custom(format!("TLS handshake: {err}")))
+    };
+    let upstream = deadline(handshake, timeout, "TLS handshake").await?;
+    forward(client, upstream, &head, &rest).await
+}
+
+/// Send the handshake to the node and relay the connection
+async fn forward<S>(mut client: TcpStream, mut upstream: S, head: &str, rest: &[u8]) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    upstream.write_all(head.as_bytes()).await?;
+    upstream.write_all(rest).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+/// Enable TCP keepalive on the node socket `stream`
+fn keepalive(stream: &TcpStream) -> Result<()> {
+    let keepalive = TcpKeepalive::new()
+        .with_time(KeepaliveSettings::tcp_idle())
+        .with_interval(KeepaliveSettings::tcp_interval())
+        .with_retries(KeepaliveSettings::tcp_retries());
+    SockRef::from(stream).set_tcp_keepalive(&keepalive)?;
+    Ok(())
+}
+
+/// Read the handshake request head of the wRPC client, returning the
+/// head (without the terminating blank line) and any bytes read past it
+async fn read_head(client: &mut TcpStream) -> Result<(String, Vec<u8>)> {
+    let mut buffer = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    loop {
+        let len = client.read(&mut chunk).await?;
+        if len == 0 {
+            return Err(Error::custom("client closed before the handshake"));
+        }
+        buffer.extend_from_slice(&chunk[..len]);
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            let rest = buffer.split_off(end + 4);
+            buffer.truncate(end);
+            let head = String::from_utf8(buffer)
+                .map_err(|_| Error::custom("invalid handshake request"))?;
+            return Ok((head, rest));
+        }
+        if buffer.len() > MAX_HEAD {
+            return Err(Error::custom("handshake request too large"));
+        }
+    }
+}
+
+/// Strip the token from the request path of the handshake request
+/// `head` (refusing requests without it) and replace its `Host` header
+fn rewrite_head(head: &str, target: &Target) -> Result<String> {
+    let authority = target.authority.as_str();
+    let (request, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+    let mut parts = request.splitn(3, ' ');
+    let (Some(method), Some(path), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(Error::custom("invalid handshake request"));
+    };
+    let path = path
+        .strip_prefix('/')
+        .and_then(|path| path.strip_prefix(target.token.as_str()))
+        .filter(|path| path.starts_with('/'))
+        .ok_or_else(|| Error::custom("refused a tunnel client without the tunnel token"))?;
+
+    let mut found = false;
+    let mut rewritten = String::with_capacity(head.len() + authority.len());
+    rewritten.push_str(&format!("{method} {path} {version}\r\n"));
+    for line in headers.split("\r\n").filter(|line| !line.is_empty()) {
+        let is_host = line
+            .split_once(':')
+            .is_some_and(|(name, _)| name.trim().eq_ignore_ascii_case("host"));
+        if is_host {
+            found = true;
+            rewritten.push_str("Host: ");
+            rewritten.push_str(authority);
+        } else {
+            rewritten.push_str(line);
+        }
+        rewritten.push_str("\r\n");
+    }
+    if !found {
+        return Err(Error::custom("handshake request without `Host`"));
+    }
+    rewritten.push_str("\r\n");
+    Ok(rewritten)
+}
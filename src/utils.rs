@@ -23,3 +23,123 @@ where
         result
     }
 }
+
+/// Current UNIX time in seconds
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Lowercase hex encoding of `bytes`
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Node `version` is at or above `min`. Versions are compared
+/// numerically by their dot-separated components (`1.2.10 > 1.2.9`),
+/// missing components count as zero (`1.2 == 1.2.0`). A pre-release
+/// (`1.2.0-rc1`) is below its release, build metadata (`+...`) is ignored.
+pub fn is_version_at_least(version: &str, min: &str) -> bool {
+    use std::cmp::Ordering;
+
+    fn parse(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.trim_start_matches('v');
+        let version = version.split_once('+').map_or(version, |(version, _)| version);
+        let (release, pre) = match version.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (version, None),
+        };
+        let release = release
+            .split('.')
+            .map_while(|part| part.parse().ok())
+            .collect();
+        (release, pre)
+    }
+
+    // pre-release identifiers: numeric ones compare numerically
+    // and below alphanumeric ones, as in semver
+    fn compare_pre(a: &str, b: &str) -> Ordering {
+        let mut a = a.split('.');
+        let mut b = b.split('.');
+        loop {
+            let ordering = match (a.next(), b.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => a.cmp(b),
+                },
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    }
+
+    let (release, pre) = parse(version);
+    let (min_release, min_pre) = parse(min);
+    let len = release.len().max(min_release.len());
+    let component = |release: &[u64], i: usize| release.get(i).copied().unwrap_or(0);
+    let ordering = (0..len)
+        .map(|i| component(&release, i).cmp(&component(&min_release, i)))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or_else(|| match (pre, min_pre) {
+            (None, None) => Ordering::Equal,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(pre), Some(min_pre)) => compare_pre(pre, min_pre),
+        });
+
+    ordering != Ordering::Less
+}
+
+/// Current UNIX time in milliseconds
+pub fn unix_now_msec() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_components_compare_numerically() {
+        assert!(is_version_at_least("1.2.10", "1.2.9"));
+        assert!(!is_version_at_least("1.2.9", "1.2.10"));
+        assert!(is_version_at_least("1.2.0", "1.2.0"));
+        assert!(is_version_at_least("2.0.0", "1.9.9"));
+        assert!(!is_version_at_least("0.9", "1.0.0"));
+    }
+
+    #[test]
+    fn missing_components_count_as_zero() {
+        assert!(is_version_at_least("1.2", "1.2.0"));
+        assert!(is_version_at_least("1.2.0", "1.2"));
+        assert!(!is_version_at_least("1.2", "1.2.1"));
+    }
+
+    #[test]
+    fn version_prefix_and_build_metadata_are_ignored() {
+        assert!(is_version_at_least("v1.2.0", "1.2.0"));
+        assert!(is_version_at_least("1.2.0+build.5", "v1.2"));
+        assert!(is_version_at_least("1.2.0", "1.2.0+build.5"));
+    }
+
+    #[test]
+    fn pre_releases_are_below_the_release() {
+        assert!(!is_version_at_least("1.2.0-rc1", "1.2.0"));
+        assert!(is_version_at_least("1.2.0", "1.2.0-rc1"));
+        assert!(is_version_at_least("1.3.0-rc1", "1.2.9"));
+        assert!(!is_version_at_least("1.1.9-rc1", "1.2.0"));
+        assert!(is_version_at_least("1.2.0-rc.10", "1.2.0-rc.9"));
+        assert!(!is_version_at_least("1.2.0-alpha", "1.2.0-beta"));
+    }
+}
@@ -0,0 +1,85 @@
+//!
+//! DNS zone file fragment of the available nodes, allowing operators
+//! to expose healthy nodes via round-robin DNS driven by the resolver's
+//! health data. Each network of each service is rendered as an A/AAAA
+//! record set named after the `[zone] name` template (`${network}` and
+//! `${service}`), listing the addresses of available nodes in election
+//! preference order (lowest score first), limited to `max-records`
+//! addresses per network.
+//!
+
+use crate::imports::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::net::IpAddr;
+
+/// Addresses of a node url (the url host if it is an IP literal)
+async fn addresses(address: &str) -> BTreeSet<IpAddr> {
+    match crate::pinning::resolve(address).await {
+        Ok(Some(addresses)) => addresses,
+        Ok(None) => reqwest::Url::parse(address)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.trim_matches(['[', ']']).to_string()))
+            .and_then(|host| host.parse().ok())
+            .into_iter()
+            .collect(),
+        Err(err) => {
+            log_error!("Zone", "{err}");
+            BTreeSet::new()
+        }
+    }
+}
+
+/// Render the zone file fragment of the currently available nodes
+/// of `connections` (all services)
+pub async fn render(connections: &[Arc<Connection>]) -> String {
+    let mut connections = connections
+        .iter()
+        .filter(|connection| connection.is_available_for(false))
+        .collect::<Vec<_>>();
+    connections.sort_by_key(|connection| connection.score());
+
+    let mut networks = BTreeMap::<(Service, String), Vec<IpAddr>>::new();
+    for connection in connections.iter() {
+        let network = connection.network_id().to_string();
+        let records = networks
+            .entry((connection.node().service(), network))
+            .or_default();
+        if records.len() >= ZoneSettings::max_records() {
+            continue;
+        }
+        for ip in addresses(connection.address()).await {
+            if !records.contains(&ip) && records.len() < ZoneSettings::max_records() {
+                records.push(ip);
+            }
+        }
+    }
+
+    let ttl = ZoneSettings::ttl();
+    let mut text = String::new();
+    let _ = writeln!(text, "; vecno-resolver available nodes ({})", unix_now());
+    for ((service, network), records) in networks.iter() {
+        let service = service.to_string();
+        let tpl = Tpl::from(&[("network", network.as_str()), ("service", service.as_str())][..]);
+        let name = tpl.render(ZoneSettings::name());
+        for ip in records {
+            let kind = if ip.is_ipv4() { "A" } else { "AAAA" };
+            let _ = writeln!(text, "{name}\t{ttl}\tIN\t{kind}\t{ip}");
+        }
+    }
+    text
+}
+
+/// Write the zone file fragment to `[zone] path` (if configured)
+pub async fn publish(connections: &[Arc<Connection>]) {
+    let Some(path) = ZoneSettings::path() else {
+        return;
+    };
+
+    let text = render(connections).await;
+    // write and rename so that readers never observe a partial file
+    let tmp = path.with_extension("tmp");
+    if let Err(err) = std::fs::write(&tmp, text).and_then(|_| std::fs::rename(&tmp, &path)) {
+        log_error!("Zone", "Unable to write `{}`: {err}", path.display());
+    }
+}
@@ -128,6 +128,7 @@ function render() {
             version,
             sid,
             uid,
+            id,
             fqdn,
             service,
             url,
@@ -142,6 +143,12 @@ function render() {
             clients,
             capacity,
             delegates,
+            annotation,
+            slow,
+            suspect,
+            lagging,
+            isolated,
+            cert_expiring,
         } = node;
 
         let el = document.getElementById(uid);
@@ -161,7 +168,7 @@ function render() {
         let peers_ = pad(peers.toLocaleString(),4);
         let clients_ = pad(clients.toLocaleString(),6);
         let capacity_ = pad(capacity.toLocaleString(),6);
-        el.innerHTML = `<td>${sid}:${uid}</td><td>${service}</td><td>${version}</td><td class='fqdn'>${fqdn}</td><td>${protocol}</td><td>${encoding}</td><td>${network}</td><td>${status}</td>`;
+        el.innerHTML = `<td title='${sid}:${uid}'>${id}</td><td>${service}</td><td>${version}</td><td class='fqdn'>${fqdn}${annotate(annotation)}</td><td>${protocol}</td><td>${encoding}</td><td>${network}</td><td>${status}${slow ? ' (slow)' : ''}${suspect ? ' (suspect)' : ''}${lagging ? ' (lagging)' : ''}${isolated ? ' (isolated)' : ''}${cert_expiring ? ' (cert expiring)' : ''}</td>`;
         if (status != "offline") {
             el.innerHTML += `<td class='wide right pre'>${peers_}</td><td class='wide right pre'>${clients_} / ${capacity_}</td><td class='wide right'>${load}%</td>`;
         }
@@ -201,6 +208,25 @@ function render() {
 
 }
 
+function escapeHtml(str) {
+    return String(str)
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;');
+}
+
+function annotate(annotation) {
+    if (!annotation) {
+        return "";
+    }
+    let labels = Object.entries(annotation.labels || {})
+        .map(([k, v]) => `<span class='label'>${escapeHtml(k)}=${escapeHtml(v)}</span>`)
+        .join('');
+    let note = annotation.note ? `<span class='note'>${escapeHtml(annotation.note)}</span>` : "";
+    return `<br>${labels}${note}`;
+}
+
 function sort() {
     let tbody = document.getElementById("nodes");
     let rows = Array.from(tbody.getElementsByTagName('tr'));
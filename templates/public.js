@@ -87,6 +87,7 @@ function render() {
             version,
             sid,
             uid,
+            id,
             service,
             url,
             protocol,
@@ -116,7 +117,7 @@ function render() {
         let peers_ = pad(peers.toLocaleString(),4);
         let clients_ = pad(clients.toLocaleString(),6);
         let capacity_ = pad(capacity.toLocaleString(),6);
-        el.innerHTML = `<td>${sid}:${uid}</td><td>${service}</td><td>${version}</td><td>${network}</td><td>${status}</td>`;
+        el.innerHTML = `<td title='${sid}:${uid}'>${id}</td><td>${service}</td><td>${version}</td><td>${network}</td><td>${status}</td>`;
         if (status != "offline") {
             el.innerHTML += `<td class='wide right pre'>${peers_}</td><td class='wide right pre'>${clients_} / ${capacity_}</td><td class='wide right'>${load}%</td>`;
         }